@@ -26,6 +26,7 @@ where
     horizontal_alignment: alignment::Horizontal,
     vertical_alignment: alignment::Vertical,
     borders: Borders,
+    background: Option<Color>,
 }
 
 impl<'a, Message, Renderer> GridCell<'a, Message, Renderer>
@@ -46,6 +47,7 @@ where
             horizontal_alignment: alignment::Horizontal::Center,
             vertical_alignment: alignment::Vertical::Center,
             borders: Borders::NONE,
+            background: None,
         }
     }
 
@@ -61,6 +63,12 @@ where
         self
     }
 
+    /// Sets the [`Color`] the [`GridCell`] is filled with behind its content.
+    pub fn background(mut self, color: impl Into<Color>) -> Self {
+        self.background = Some(color.into());
+        self
+    }
+
     /// Sets the content alignment for the horizontal axis of the [`GridCell`].
     pub fn align_x(mut self, alignment: alignment::Horizontal) -> Self {
         self.horizontal_alignment = alignment;
@@ -84,6 +92,7 @@ where
             horizontal_alignment: self.horizontal_alignment,
             vertical_alignment: self.vertical_alignment,
             borders: self.borders,
+            background: self.background,
             info,
         }
     }
@@ -100,6 +109,7 @@ where
     horizontal_alignment: alignment::Horizontal,
     vertical_alignment: alignment::Vertical,
     borders: Borders,
+    background: Option<Color>,
     info: Rc<RefCell<GridInfo<Renderer>>>,
 }
 
@@ -225,6 +235,18 @@ where
         let info = (*self.info).borrow();
         let appearance = theme.appearance(&info.style);
 
+        if let Some(background) = self.background {
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: layout.bounds(),
+                    border_radius: 0.0.into(),
+                    border_width: 0.0,
+                    border_color: Color::TRANSPARENT,
+                },
+                background,
+            );
+        }
+
         // Rule lines for this (posssible spanning) cell
         renderer.fill_quad(
             renderer::Quad {
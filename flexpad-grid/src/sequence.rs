@@ -21,7 +21,7 @@ type CompactArray = [Repeat; COMPACT_SIZE];
 /// assert_eq!(70.0, seq.sum());
 /// ```
 #[derive(Debug, Clone)]
-// TODO Tree representation for larger sequences
+// TODO Tree representation for faster searches of larger sequences
 // TODO Insert
 // TODO Delete
 pub struct SumSeq {
@@ -40,6 +40,9 @@ impl SumSeq {
     }
 
     pub fn push_many(&mut self, repeat: u32, value: f32) {
+        if repeat == 0 {
+            return;
+        }
         match self.data {
             Representation::Compact(ref mut values) => {
                 for index in 0..COMPACT_SIZE {
@@ -55,18 +58,29 @@ impl SumSeq {
                         return;
                     }
                 }
-                todo!("Representation is full - switch to tree")
+                // Full so switch to a representation that can grow
+                let mut runs = values.to_vec();
+                runs.push((repeat, value));
+                self.data = Representation::Runs(runs);
             }
-            Representation::Tree => todo!(),
+            Representation::Runs(ref mut runs) => match runs.last_mut() {
+                Some(last) if last.1 == value => last.0 += repeat,
+                _ => runs.push((repeat, value)),
+            },
+        }
+    }
+
+    /// Returns the runs of repeated values of this [`SumSeq`]
+    fn repeats(&self) -> &[Repeat] {
+        match &self.data {
+            Representation::Compact(values) => values,
+            Representation::Runs(runs) => runs,
         }
     }
 
     /// Returns the number of terms in this [`Sequence`]
     pub fn len(&self) -> usize {
-        match self.data {
-            Representation::Compact(values) => values.iter().map(|(n, _)| *n as usize).sum(),
-            Representation::Tree => todo!(),
-        }
+        self.repeats().iter().map(|(n, _)| *n as usize).sum()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -75,32 +89,24 @@ impl SumSeq {
 
     /// Returns the sum of the terms in this [`Sequence`]
     pub fn sum(&self) -> f32 {
-        match self.data {
-            Representation::Compact(values) => values.iter().map(|(n, v)| (*n as f32) * v).sum(),
-            Representation::Tree => todo!(),
-        }
+        self.repeats().iter().map(|(n, v)| (*n as f32) * v).sum()
     }
 
     /// Returns the sum of the terms in this [`Sequence`] whose index is less than `end`.
     /// If `end >= len()` the sum of the whole sequence is returned
     pub fn sum_to(&self, end: usize) -> f32 {
-        match self.data {
-            Representation::Compact(values) => {
-                let mut remaining = end;
-                let mut sum = 0.0;
-                for repeat in values {
-                    if repeat.0 as usize <= remaining {
-                        sum += (repeat.0 as f32) * repeat.1;
-                        remaining -= repeat.0 as usize;
-                    } else if remaining > 0 {
-                        sum += (remaining as f32) * repeat.1;
-                        remaining = 0;
-                    }
-                }
-                sum
+        let mut remaining = end;
+        let mut sum = 0.0;
+        for repeat in self.repeats() {
+            if repeat.0 as usize <= remaining {
+                sum += (repeat.0 as f32) * repeat.1;
+                remaining -= repeat.0 as usize;
+            } else if remaining > 0 {
+                sum += (remaining as f32) * repeat.1;
+                remaining = 0;
             }
-            Representation::Tree => todo!(),
         }
+        sum
     }
 
     /// Given a value this returns the index of the element whose sum it most closely represents.
@@ -120,42 +126,34 @@ impl SumSeq {
             return None;
         }
 
-        match self.data {
-            Representation::Compact(values) => {
-                let mut remaining = sum;
-                let mut index: isize = -1;
-                for repeat in values {
-                    let repeat_sum = (repeat.0 as f32) * repeat.1;
-                    if repeat_sum <= remaining {
-                        index += repeat.0 as isize;
-                        remaining -= repeat_sum;
-                    } else if remaining > 0.0 {
-                        let full_units = (remaining / repeat.1).floor();
-                        remaining -= full_units * repeat.1;
-                        index += full_units as isize;
-                        break;
-                    }
-                }
-                if remaining > 0.0 || rounding == Rounding::Up {
-                    index += 1;
-                }
-                let index = index as usize;
-                if index < len {
-                    Some(index)
-                } else {
-                    None
-                }
+        let mut remaining = sum;
+        let mut index: isize = -1;
+        for repeat in self.repeats() {
+            let repeat_sum = (repeat.0 as f32) * repeat.1;
+            if repeat_sum <= remaining {
+                index += repeat.0 as isize;
+                remaining -= repeat_sum;
+            } else if remaining > 0.0 {
+                let full_units = (remaining / repeat.1).floor();
+                remaining -= full_units * repeat.1;
+                index += full_units as isize;
+                break;
             }
-            Representation::Tree => todo!(),
+        }
+        if remaining > 0.0 || rounding == Rounding::Up {
+            index += 1;
+        }
+        let index = index as usize;
+        if index < len {
+            Some(index)
+        } else {
+            None
         }
     }
 
     /// Returns an iterator of the values in this [`SumSeq`]
     pub fn values(&self) -> impl Iterator<Item = f32> {
-        match self.data {
-            Representation::Compact(values) => Iter::Compact(CompactIter::new(values)),
-            Representation::Tree => todo!(),
-        }
+        RepeatIter::new(self.repeats().to_vec())
     }
 }
 
@@ -172,48 +170,38 @@ pub enum Rounding {
     Up,
 }
 
+/// Sequences start with a fixed number of runs of repeated values and grow once that is full
 #[derive(Clone)]
-#[allow(dead_code)]
 enum Representation {
     Compact(CompactArray),
-    Tree,
-}
-
-enum Iter {
-    Compact(CompactIter),
+    Runs(Vec<Repeat>),
 }
 
-impl Iterator for Iter {
-    type Item = f32;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            Iter::Compact(ci) => ci.next(),
-        }
-    }
-}
-
-struct CompactIter {
-    values: [Repeat; 10],
+struct RepeatIter {
+    values: Vec<Repeat>,
     index: usize,
     emitted: u32,
 }
 
-impl CompactIter {
-    fn new(values: CompactArray) -> Self {
+impl RepeatIter {
+    fn new(values: Vec<Repeat>) -> Self {
         Self {
             values,
             index: 0,
             emitted: 0,
         }
     }
+}
+
+impl Iterator for RepeatIter {
+    type Item = f32;
 
     fn next(&mut self) -> Option<f32> {
-        while self.index < COMPACT_SIZE && self.emitted >= self.values[self.index].0 {
+        while self.index < self.values.len() && self.emitted >= self.values[self.index].0 {
             self.index += 1;
             self.emitted = 0;
         }
-        if self.index >= COMPACT_SIZE {
+        if self.index >= self.values.len() {
             None
         } else {
             self.emitted += 1;
@@ -234,7 +222,13 @@ impl std::fmt::Debug for Representation {
                 }
                 Ok(())
             }
-            Representation::Tree => todo!(),
+            Representation::Runs(runs) => {
+                write!(f, "Runs")?;
+                for (repeat, value) in runs {
+                    write!(f, " {}x{}", repeat, value)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -347,6 +341,23 @@ mod tests {
         assert_eq!(None, seq.index_of_sum(20.0, Rounding::Up));
     }
 
+    #[test]
+    fn grows_beyond_compact() {
+        let mut seq = SumSeq::new();
+        for v in 1..=12 {
+            seq.push_many(2, v as f32);
+        }
+        seq.push(12.0);
+        assert!(matches!(seq.data, Representation::Runs(ref runs) if runs.len() == 12));
+
+        assert_eq!(25, seq.len());
+        assert_eq!(168.0, seq.sum());
+        assert_eq!(6.0, seq.sum_to(4));
+        assert_eq!(Some(4), seq.index_of_sum(6.5, Rounding::Down));
+        assert_eq!(Some(24), seq.index_of_sum(167.0, Rounding::Down));
+        assert_eq!(seq.len(), seq.values().count());
+    }
+
     fn assert_compact(seq: &SumSeq, expected: Vec<Repeat>) {
         assert!(expected.len() <= COMPACT_SIZE, "Expected is too large");
         let mut exp_array = [(0, 0.0); COMPACT_SIZE];
@@ -0,0 +1,86 @@
+//! Benchmarks for reading a workpad as its history grows.
+//!
+//! Each benchmark is run against workpads in which the same cells have been edited
+//! increasing numbers of times.  Read times should stay flat as the history grows.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use flexpad_model::{Workpad, WorkpadMaster, WorkpadUpdate};
+
+const HISTORY_LENGTHS: [usize; 4] = [10, 100, 1_000, 10_000];
+
+/// Returns a workpad in which cell A1 and a 10 x 10 block of cells have each been set
+/// `edits` times
+fn edited_workpad(edits: usize) -> (WorkpadMaster, Workpad) {
+    let mut master = WorkpadMaster::new_starter();
+    let sheet = master.active_version().active_sheet().unwrap();
+    let mut pad = master.active_version();
+    for edit in 0..edits {
+        let cell = sheet.cell(edit % 10, (edit / 10) % 10);
+        pad = master
+            .update(WorkpadUpdate::SheetSetCellValue {
+                sheet_id: sheet.id(),
+                row_id: cell.row().id(),
+                column_id: cell.column().id(),
+                value: edit.to_string(),
+            })
+            .unwrap();
+    }
+    (master, pad)
+}
+
+fn read_cell(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_cell");
+    for edits in HISTORY_LENGTHS {
+        let (_master, pad) = edited_workpad(edits);
+        let sheet = pad.active_sheet().unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(edits), &sheet, |b, sheet| {
+            b.iter(|| black_box(sheet.cell(black_box(3), black_box(4)).value().len()))
+        });
+    }
+    group.finish();
+}
+
+fn render_page(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_page");
+    for edits in HISTORY_LENGTHS {
+        let (_master, pad) = edited_workpad(edits);
+        group.bench_with_input(BenchmarkId::from_parameter(edits), &pad, |b, pad| {
+            b.iter(|| {
+                let sheet = pad.active_sheet().unwrap();
+                let mut len = 0;
+                for row in 0..40 {
+                    for column in 0..15 {
+                        len += sheet.cell(row, column).value().len();
+                    }
+                }
+                black_box(len)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn update_cell(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_cell");
+    for edits in HISTORY_LENGTHS {
+        let (mut master, pad) = edited_workpad(edits);
+        let sheet = pad.active_sheet().unwrap();
+        let cell = sheet.cell(3, 4);
+        group.bench_function(BenchmarkId::from_parameter(edits), |b| {
+            b.iter(|| {
+                master
+                    .update(WorkpadUpdate::SheetSetCellValue {
+                        sheet_id: sheet.id(),
+                        row_id: cell.row().id(),
+                        column_id: cell.column().id(),
+                        value: String::from("x"),
+                    })
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, read_cell, render_page, update_cell);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+/// A comment attached to a cell, or a reply to the comments before it.
+///
+/// The comments of a cell form a thread: the first starts it and those after it are the
+/// replies, in the order they were made.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    /// The name of the user who made the comment
+    pub author: String,
+    /// When the comment was made
+    pub timestamp: SystemTime,
+    pub text: String,
+}
+
+impl Comment {
+    /// Create a [`Comment`] made now by the user running this process
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            author: crate::AUTHOR.to_string(),
+            timestamp: SystemTime::now(),
+            text: text.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, WorkpadMaster, WorkpadUpdate};
+
+    #[test]
+    fn comment_threads() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let (sheet_id, row_id, column_id) = (sheet.id(), sheet.row(1).id(), sheet.column(2).id());
+
+        for text in ["Check this", "Done", "Thanks"] {
+            master
+                .update(WorkpadUpdate::SheetAddCellComment {
+                    sheet_id,
+                    row_id,
+                    column_id,
+                    comment: Comment::new(text),
+                })
+                .unwrap();
+        }
+        master
+            .update(WorkpadUpdate::SheetEditCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                index: 1,
+                text: String::from("Fixed"),
+            })
+            .unwrap();
+
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(1, 2);
+        let texts: Vec<_> = cell.comments().iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(vec!["Check this", "Fixed", "Thanks"], texts);
+        assert_eq!(1, sheet.commented_cells().count());
+        assert!(sheet.non_empty_cells().next().is_none());
+
+        let err = master
+            .update(WorkpadUpdate::SheetDeleteCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                index: 3,
+            })
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::MissingComment(3)));
+
+        // Deleting the first comment deletes its replies too
+        master
+            .update(WorkpadUpdate::SheetDeleteCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                index: 0,
+            })
+            .unwrap();
+        let sheet = master.active_version().active_sheet().unwrap();
+        assert!(sheet.cell(1, 2).comments().is_empty());
+        assert_eq!(0, sheet.commented_cells().count());
+    }
+}
@@ -0,0 +1,379 @@
+use std::{cmp::Ordering, collections::HashMap, fmt, sync::Arc};
+
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+use crate::{Cell, Color, Format, Sheet, SheetRange};
+
+/// A rule that changes how the cells of a range of a sheet are displayed depending on their
+/// values.  A sheet's rules are held in order: where several rules apply to a cell the
+/// earlier ones take precedence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConditionalFormat {
+    /// The cells the rule applies to
+    #[serde(flatten)]
+    pub range: SheetRange,
+    pub condition: Condition,
+    /// The formatting of the cells that meet the condition.  Not used by
+    /// [`Condition::ColorScale`], which sets the fill colour of each cell itself.
+    pub format: Format,
+}
+
+/// The condition that the value of a cell must meet for a [`ConditionalFormat`] to apply.
+/// Empty cells never meet a condition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// The value compares with another value as given.  Values are compared as numbers if
+    /// both are numbers, as text ignoring case if neither is, and otherwise are only unequal.
+    Compare {
+        comparison: Comparison,
+        value: String,
+    },
+    /// The value contains some text, ignoring case
+    Contains(String),
+    /// The value is one of the highest `n` numbers in the range
+    Top(usize),
+    /// The value is one of the lowest `n` numbers in the range
+    Bottom(usize),
+    /// The value occurs more than once in the range, ignoring case
+    Duplicates,
+    /// Numbers are filled with a colour between `low`, for the lowest number in the range,
+    /// and `high`, for the highest
+    ColorScale { low: Color, high: Color },
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Compare { comparison, value } => write!(f, "{comparison} {value}"),
+            Condition::Contains(text) => {
+                f.write_str(&t!("Condition.Contains").replace("{text}", text))
+            }
+            Condition::Top(n) => f.write_str(&t!("Condition.Top").replace("{n}", &n.to_string())),
+            Condition::Bottom(n) => {
+                f.write_str(&t!("Condition.Bottom").replace("{n}", &n.to_string()))
+            }
+            Condition::Duplicates => f.write_str(&t!("Condition.Duplicates")),
+            Condition::ColorScale { .. } => f.write_str(&t!("Condition.ColorScale")),
+        }
+    }
+}
+
+/// How a [`Condition::Compare`] compares values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 6] = [
+        Comparison::Equal,
+        Comparison::NotEqual,
+        Comparison::Less,
+        Comparison::LessOrEqual,
+        Comparison::Greater,
+        Comparison::GreaterOrEqual,
+    ];
+
+    fn is_met_by(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Equal => ordering.is_eq(),
+            Comparison::NotEqual => ordering.is_ne(),
+            Comparison::Less => ordering.is_lt(),
+            Comparison::LessOrEqual => ordering.is_le(),
+            Comparison::Greater => ordering.is_gt(),
+            Comparison::GreaterOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "<>",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        })
+    }
+}
+
+/// The [`ConditionalFormat`]s of a version of a [`Sheet`], ready to be evaluated against its
+/// cells.  Rules that depend on all the values of their range (such as [`Condition::Top`])
+/// have those values summarised once, when this is created, rather than for each cell.
+#[derive(Debug)]
+pub struct ConditionalEvaluator {
+    rules: Arc<Vec<ConditionalFormat>>,
+    summaries: Vec<Summary>,
+}
+
+/// What a rule needs to know about the values of its range
+#[derive(Debug)]
+enum Summary {
+    None,
+    /// The lowest (or highest) number that is in the top (or bottom) n, if there are numbers
+    Threshold(Option<f64>),
+    /// The number of times each value occurs, by lower case value
+    Counts(HashMap<String, usize>),
+    /// The lowest and highest numbers, if there are numbers
+    Bounds(Option<(f64, f64)>),
+}
+
+impl ConditionalEvaluator {
+    pub(crate) fn new(sheet: &Sheet, rules: Arc<Vec<ConditionalFormat>>) -> Self {
+        // The values of the cells in the range of each rule that needs a summary
+        let needs_values = |rule: &ConditionalFormat| {
+            !matches!(
+                rule.condition,
+                Condition::Compare { .. } | Condition::Contains(_)
+            )
+        };
+        let mut values: Vec<Vec<String>> = vec![vec![]; rules.len()];
+        if rules.iter().any(needs_values) {
+            for cell in sheet.non_empty_cells() {
+                let (row, column) = (cell.row().index(), cell.column().index());
+                for (rule, values) in rules.iter().zip(values.iter_mut()) {
+                    if needs_values(rule) && rule.range.contains(row, column) {
+                        values.push(cell.value().to_owned());
+                    }
+                }
+            }
+        }
+
+        let summaries = rules
+            .iter()
+            .zip(values)
+            .map(|(rule, values)| {
+                let mut numbers: Vec<f64> = values.iter().filter_map(|v| number(v)).collect();
+                numbers.sort_by(f64::total_cmp);
+                match rule.condition {
+                    Condition::Compare { .. } | Condition::Contains(_) => Summary::None,
+                    Condition::Top(n) => {
+                        let index = numbers.len().saturating_sub(n.max(1));
+                        Summary::Threshold(numbers.get(index).copied())
+                    }
+                    Condition::Bottom(n) => {
+                        let index = n.max(1).min(numbers.len()).saturating_sub(1);
+                        Summary::Threshold(numbers.get(index).copied())
+                    }
+                    Condition::Duplicates => {
+                        let mut counts = HashMap::new();
+                        for value in values {
+                            *counts.entry(value.trim().to_lowercase()).or_default() += 1;
+                        }
+                        Summary::Counts(counts)
+                    }
+                    Condition::ColorScale { .. } => Summary::Bounds(
+                        numbers
+                            .first()
+                            .zip(numbers.last())
+                            .map(|(low, high)| (*low, *high)),
+                    ),
+                }
+            })
+            .collect();
+
+        Self { rules, summaries }
+    }
+
+    /// Returns true if there are no rules to evaluate
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the formatting that the rules give a cell, to be overlaid on its own
+    /// formatting, or `None` if no rule applies to it
+    pub fn format_of(&self, cell: &Cell) -> Option<Format> {
+        let value = cell.value();
+        if value.trim().is_empty() {
+            return None;
+        }
+        let (row, column) = (cell.row().index(), cell.column().index());
+
+        // Earlier rules take precedence so are overlaid last
+        self.rules
+            .iter()
+            .zip(&self.summaries)
+            .rev()
+            .filter(|(rule, _)| rule.range.contains(row, column))
+            .filter_map(|(rule, summary)| evaluate(rule, summary, value))
+            .reduce(|format, other| format.overlay(&other))
+    }
+}
+
+/// Returns the formatting a rule gives a value in its range, if any
+fn evaluate(rule: &ConditionalFormat, summary: &Summary, value: &str) -> Option<Format> {
+    let met = match (&rule.condition, summary) {
+        (
+            Condition::Compare {
+                comparison,
+                value: other,
+            },
+            _,
+        ) => match (number(value), number(other)) {
+            (Some(value), Some(other)) => comparison.is_met_by(value.total_cmp(&other)),
+            (None, None) => comparison.is_met_by(
+                value
+                    .trim()
+                    .to_lowercase()
+                    .cmp(&other.trim().to_lowercase()),
+            ),
+            _ => *comparison == Comparison::NotEqual,
+        },
+        (Condition::Contains(text), _) => value.to_lowercase().contains(&text.to_lowercase()),
+        (Condition::Top(_), Summary::Threshold(Some(threshold))) => {
+            number(value).map_or(false, |value| value >= *threshold)
+        }
+        (Condition::Bottom(_), Summary::Threshold(Some(threshold))) => {
+            number(value).map_or(false, |value| value <= *threshold)
+        }
+        (Condition::Duplicates, Summary::Counts(counts)) => counts
+            .get(&value.trim().to_lowercase())
+            .map_or(false, |count| *count > 1),
+        (Condition::ColorScale { low, high }, Summary::Bounds(Some((min, max)))) => {
+            let value = number(value)?;
+            let fraction = if max > min {
+                ((value - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            return Some(Format {
+                fill_color: Some(blend(*low, *high, fraction)),
+                ..Format::NONE
+            });
+        }
+        _ => false,
+    };
+    met.then(|| rule.format.clone())
+}
+
+fn number(value: &str) -> Option<f64> {
+    value.trim().parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+/// Returns the colour a fraction of the way from one colour to another
+fn blend(from: Color, to: Color, fraction: f64) -> Color {
+    let channel =
+        |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * fraction).round() as u8;
+    Color::rgb(
+        channel(from.r, to.r),
+        channel(from.g, to.g),
+        channel(from.b, to.b),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::set_cell_values, WorkpadMaster, WorkpadUpdate};
+
+    #[test]
+    fn rules_are_evaluated_against_their_range() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let values = ["5", "12", "apple", "7", "Apple", "30", "", "1"];
+        let values: Vec<(usize, usize, &str)> = values
+            .iter()
+            .enumerate()
+            .map(|(row, value)| (row, 0, *value))
+            .collect();
+        set_cell_values(&mut master, &sheet, &values);
+
+        let red = Color::rgb(255, 0, 0);
+        let fill = |color| Format {
+            fill_color: Some(color),
+            ..Format::NONE
+        };
+        let bold = Format {
+            bold: Some(true),
+            ..Format::NONE
+        };
+        let rule = |condition, format| ConditionalFormat {
+            range: SheetRange::new(0..=7, 0..=0),
+            condition,
+            format,
+        };
+        let rules = vec![
+            rule(
+                Condition::Compare {
+                    comparison: Comparison::Greater,
+                    value: "10".to_owned(),
+                },
+                fill(red),
+            ),
+            rule(Condition::Top(2), bold.clone()),
+            rule(Condition::Duplicates, fill(Color::BLACK)),
+            rule(
+                Condition::ColorScale {
+                    low: Color::WHITE,
+                    high: Color::rgb(0, 255, 255),
+                },
+                Format::NONE,
+            ),
+            ConditionalFormat {
+                range: SheetRange::new(0..=1, 0..=0),
+                ..rule(Condition::Contains("5".to_owned()), bold.clone())
+            },
+        ];
+        let pad = master
+            .update(WorkpadUpdate::SheetSetConditionalFormats {
+                sheet_id: sheet.id(),
+                conditional_formats: rules.clone(),
+            })
+            .unwrap();
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!(&rules[..], sheet.conditional_formats());
+
+        let evaluator = sheet.conditional_evaluator();
+        let format_of = |row| evaluator.format_of(&sheet.cell(row, 0));
+
+        // 1 is the lowest number so gets the low colour of the scale
+        assert_eq!(Some(fill(Color::WHITE)), format_of(7));
+        // 30 is over 10 and in the top 2, and the comparison takes precedence over the scale
+        assert_eq!(
+            Some(Format {
+                bold: Some(true),
+                ..fill(red)
+            }),
+            format_of(5)
+        );
+        // 12 is over 10 and in the top 2
+        assert_eq!(
+            Some(Format {
+                bold: Some(true),
+                ..fill(red)
+            }),
+            format_of(1)
+        );
+        // 5 is in the range of the Contains rule, 7 is not
+        assert_eq!(Some(true), format_of(0).unwrap().bold);
+        assert_eq!(None, format_of(3).unwrap().bold);
+        // Text duplicated ignoring case
+        assert_eq!(Some(fill(Color::BLACK)), format_of(2));
+        assert_eq!(Some(fill(Color::BLACK)), format_of(4));
+        // Empty cells and cells outside the ranges are not formatted
+        assert_eq!(None, format_of(6));
+        assert_eq!(None, evaluator.format_of(&sheet.cell(0, 1)));
+        assert_eq!(None, format_of(8));
+
+        // Rules are versioned
+        let before = master.active_version();
+        assert!(before.active_sheet().unwrap().conditional_formats().len() == rules.len());
+        master
+            .update(WorkpadUpdate::SheetSetConditionalFormats {
+                sheet_id: sheet.id(),
+                conditional_formats: vec![],
+            })
+            .unwrap();
+        let after = master.active_version().active_sheet().unwrap();
+        assert!(after.conditional_evaluator().is_empty());
+        assert_eq!(rules.len(), sheet.conditional_formats().len());
+    }
+}
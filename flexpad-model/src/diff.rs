@@ -0,0 +1,642 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use rust_i18n::t;
+
+use crate::{Format, Sheet, SheetId, Version, Workpad, WorkpadMaster};
+
+/// A single difference between two versions of a workpad.  See [`WorkpadMaster::diff`].
+///
+/// Rows and columns are identified by index as these do not change between versions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// The name of the workpad changed
+    WorkpadName { old_name: String, new_name: String },
+    /// The author of the workpad changed
+    WorkpadAuthor {
+        old_author: String,
+        new_author: String,
+    },
+    /// A sheet exists in the later version only
+    SheetAdded { sheet_id: SheetId, name: String },
+    /// A sheet exists in the earlier version only
+    SheetRemoved { sheet_id: SheetId, name: String },
+    /// The name of a sheet changed
+    SheetRenamed {
+        sheet_id: SheetId,
+        old_name: String,
+        new_name: String,
+    },
+    /// The value of a cell changed.  The cells of added and removed sheets are compared
+    /// with empty cells.
+    CellValue {
+        sheet_id: SheetId,
+        row: usize,
+        column: usize,
+        old_value: String,
+        new_value: String,
+    },
+    /// The named style of a cell changed, `None` being no style
+    CellStyle {
+        sheet_id: SheetId,
+        row: usize,
+        column: usize,
+        old_style: Option<String>,
+        new_style: Option<String>,
+    },
+    /// The [`Format`] set on a cell itself changed
+    CellFormat {
+        sheet_id: SheetId,
+        row: usize,
+        column: usize,
+        old_format: Format,
+        new_format: Format,
+    },
+    /// The width of a column changed
+    ColumnWidth {
+        sheet_id: SheetId,
+        column: usize,
+        old_width: f32,
+        new_width: f32,
+    },
+    /// The height of a row changed
+    RowHeight {
+        sheet_id: SheetId,
+        row: usize,
+        old_height: f32,
+        new_height: f32,
+    },
+}
+
+impl Change {
+    /// Returns the id of the sheet changed or `None` for changes to the workpad itself
+    pub fn sheet_id(&self) -> Option<SheetId> {
+        match self {
+            Self::WorkpadName { .. } | Self::WorkpadAuthor { .. } => None,
+            Self::SheetAdded { sheet_id, .. }
+            | Self::SheetRemoved { sheet_id, .. }
+            | Self::SheetRenamed { sheet_id, .. }
+            | Self::CellValue { sheet_id, .. }
+            | Self::CellStyle { sheet_id, .. }
+            | Self::CellFormat { sheet_id, .. }
+            | Self::ColumnWidth { sheet_id, .. }
+            | Self::RowHeight { sheet_id, .. } => Some(*sheet_id),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::WorkpadName { old_name, new_name } => t!("Change.WorkpadName")
+                .replace("{old}", old_name)
+                .replace("{new}", new_name),
+            Self::WorkpadAuthor {
+                old_author,
+                new_author,
+            } => t!("Change.WorkpadAuthor")
+                .replace("{old}", old_author)
+                .replace("{new}", new_author),
+            Self::SheetAdded { name, .. } => t!("Change.SheetAdded").replace("{name}", name),
+            Self::SheetRemoved { name, .. } => t!("Change.SheetRemoved").replace("{name}", name),
+            Self::SheetRenamed {
+                old_name, new_name, ..
+            } => t!("Change.SheetRenamed")
+                .replace("{old}", old_name)
+                .replace("{new}", new_name),
+            Self::CellValue {
+                row,
+                column,
+                old_value,
+                new_value,
+                ..
+            } => t!("Change.CellValue")
+                .replace("{cell}", &cell_name(*row, *column))
+                .replace("{old}", old_value)
+                .replace("{new}", new_value),
+            Self::CellStyle {
+                row,
+                column,
+                old_style,
+                new_style,
+                ..
+            } => {
+                let style = |style: &Option<String>| match style {
+                    Some(style) => format!("\"{style}\""),
+                    None => t!("Change.NoStyle").to_string(),
+                };
+                t!("Change.CellStyle")
+                    .replace("{cell}", &cell_name(*row, *column))
+                    .replace("{old}", &style(old_style))
+                    .replace("{new}", &style(new_style))
+            }
+            Self::CellFormat { row, column, .. } => {
+                t!("Change.CellFormat").replace("{cell}", &cell_name(*row, *column))
+            }
+            Self::ColumnWidth {
+                column,
+                old_width,
+                new_width,
+                ..
+            } => t!("Change.ColumnWidth")
+                .replace("{column}", &crate::create_column_name(*column))
+                .replace("{old}", &old_width.to_string())
+                .replace("{new}", &new_width.to_string()),
+            Self::RowHeight {
+                row,
+                old_height,
+                new_height,
+                ..
+            } => t!("Change.RowHeight")
+                .replace("{row}", &(row + 1).to_string())
+                .replace("{old}", &old_height.to_string())
+                .replace("{new}", &new_height.to_string()),
+        };
+        f.write_str(&text)
+    }
+}
+
+fn cell_name(row: usize, column: usize) -> String {
+    format!("{}{}", crate::create_column_name(column), row + 1)
+}
+
+/// The differences between two versions of a workpad.  See [`WorkpadMaster::diff`].
+#[derive(Debug, Clone)]
+pub struct WorkpadDiff {
+    from: Version,
+    to: Version,
+    changes: Vec<Change>,
+}
+
+impl WorkpadDiff {
+    /// Returns the version compared from
+    pub fn from(&self) -> Version {
+        self.from
+    }
+
+    /// Returns the version compared to
+    pub fn to(&self) -> Version {
+        self.to
+    }
+
+    /// Returns the changes made going from one version to the other.  Changes to the workpad
+    /// come first then those to each sheet, in sheet order.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns true if the versions are the same in every compared respect
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the (row, column) indices of the cells of a sheet whose values, styles or
+    /// formats changed, row by row
+    pub fn changed_cells(&self, sheet_id: SheetId) -> impl Iterator<Item = (usize, usize)> {
+        let cells: BTreeSet<_> = self
+            .changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::CellValue {
+                    sheet_id: id,
+                    row,
+                    column,
+                    ..
+                }
+                | Change::CellStyle {
+                    sheet_id: id,
+                    row,
+                    column,
+                    ..
+                }
+                | Change::CellFormat {
+                    sheet_id: id,
+                    row,
+                    column,
+                    ..
+                } if *id == sheet_id => Some((*row, *column)),
+                _ => None,
+            })
+            .collect();
+        cells.into_iter()
+    }
+}
+
+/// The error returned when two versions of a workpad cannot be compared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffError {
+    MissingVersion(Version),
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingVersion(version) => f.write_str(
+                &t!("DiffError.MissingVersion").replace("{version}", &version.to_string()),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+impl WorkpadMaster {
+    /// Returns the differences between two versions of the workpad: the changes which take
+    /// the workpad from version `from` to version `to`.  The versions need not be on the
+    /// same branch of the history.
+    ///
+    /// Which sheet and cell are active are not treated as changes, and neither (as yet) are
+    /// the styles of sheets, rows and columns, the definitions of named styles, conditional
+    /// formats, comments, hyperlinks, merged cells and validation rules.
+    pub fn diff(&self, from: Version, to: Version) -> Result<WorkpadDiff, DiffError> {
+        let from_pad = self.version_at(from)?;
+        let to_pad = self.version_at(to)?;
+
+        let mut changes = vec![];
+        if from_pad.name() != to_pad.name() {
+            changes.push(Change::WorkpadName {
+                old_name: from_pad.name().to_owned(),
+                new_name: to_pad.name().to_owned(),
+            });
+        }
+        if from_pad.author() != to_pad.author() {
+            changes.push(Change::WorkpadAuthor {
+                old_author: from_pad.author().to_owned(),
+                new_author: to_pad.author().to_owned(),
+            });
+        }
+
+        for from_sheet in from_pad.sheets() {
+            if to_pad.sheet_by_id(from_sheet.id()).is_none() {
+                changes.push(Change::SheetRemoved {
+                    sheet_id: from_sheet.id(),
+                    name: from_sheet.name().to_owned(),
+                });
+                diff_cells(Some(&from_sheet), None, &mut changes);
+            }
+        }
+
+        for to_sheet in to_pad.sheets() {
+            match from_pad.sheet_by_id(to_sheet.id()) {
+                Some(from_sheet) => {
+                    if from_sheet.name() != to_sheet.name() {
+                        changes.push(Change::SheetRenamed {
+                            sheet_id: to_sheet.id(),
+                            old_name: from_sheet.name().to_owned(),
+                            new_name: to_sheet.name().to_owned(),
+                        });
+                    }
+                    diff_axes(&from_sheet, &to_sheet, &mut changes);
+                    diff_cells(Some(&from_sheet), Some(&to_sheet), &mut changes);
+                }
+                None => {
+                    changes.push(Change::SheetAdded {
+                        sheet_id: to_sheet.id(),
+                        name: to_sheet.name().to_owned(),
+                    });
+                    diff_cells(None, Some(&to_sheet), &mut changes);
+                }
+            }
+        }
+
+        Ok(WorkpadDiff { from, to, changes })
+    }
+
+    fn version_at(&self, version: Version) -> Result<Workpad, DiffError> {
+        if version as usize >= self.data.history.read().unwrap().len() {
+            return Err(DiffError::MissingVersion(version));
+        }
+        Ok(Workpad {
+            master: self.clone(),
+            version,
+            data: self.data.read_workpad(version),
+        })
+    }
+}
+
+/// Adds the changes to the sizes of the columns and rows of a sheet.  Only rows and columns
+/// allocated in either version can differ from the default sizes.
+fn diff_axes(from: &Sheet, to: &Sheet, changes: &mut Vec<Change>) {
+    let columns = from.data.columns.allocated.keys();
+    let columns = columns.chain(to.data.columns.allocated.keys());
+    for column in columns.copied().collect::<BTreeSet<_>>() {
+        let old_width = from.column(column).width();
+        let new_width = to.column(column).width();
+        if old_width != new_width {
+            changes.push(Change::ColumnWidth {
+                sheet_id: to.id(),
+                column,
+                old_width,
+                new_width,
+            });
+        }
+    }
+
+    let rows = from.data.rows.allocated.keys();
+    let rows = rows.chain(to.data.rows.allocated.keys());
+    for row in rows.copied().collect::<BTreeSet<_>>() {
+        let old_height = from.row(row).height();
+        let new_height = to.row(row).height();
+        if old_height != new_height {
+            changes.push(Change::RowHeight {
+                sheet_id: to.id(),
+                row,
+                old_height,
+                new_height,
+            });
+        }
+    }
+}
+
+/// The compared properties of a cell
+#[derive(Default)]
+struct CellState {
+    value: String,
+    style: Option<String>,
+    format: Format,
+}
+
+/// Adds the changes to the values, styles and formats of the cells of a sheet, row by row.
+/// A missing sheet is treated as one with only empty cells.
+fn diff_cells(from: Option<&Sheet>, to: Option<&Sheet>, changes: &mut Vec<Change>) {
+    let states = |sheet: Option<&Sheet>| -> BTreeMap<(usize, usize), CellState> {
+        sheet
+            .map(|sheet| {
+                sheet
+                    .cells()
+                    .map(|cell| {
+                        let key = (cell.row().index(), cell.column().index());
+                        let state = CellState {
+                            value: cell.value().to_owned(),
+                            style: cell.style().map(str::to_owned),
+                            format: cell.format().clone(),
+                        };
+                        (key, state)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let sheet_id = to.or(from).unwrap().id();
+    let mut old_states = states(from);
+    let new_states = states(to);
+
+    let mut cells: Vec<_> = new_states
+        .into_iter()
+        .map(|(key, new)| (key, old_states.remove(&key).unwrap_or_default(), new))
+        .collect();
+    cells.extend(
+        old_states
+            .into_iter()
+            .map(|(key, old)| (key, old, CellState::default())),
+    );
+    cells.sort_by_key(|(key, _, _)| *key);
+
+    for ((row, column), old, new) in cells {
+        if old.value != new.value {
+            changes.push(Change::CellValue {
+                sheet_id,
+                row,
+                column,
+                old_value: old.value,
+                new_value: new.value,
+            });
+        }
+        if old.style != new.style {
+            changes.push(Change::CellStyle {
+                sheet_id,
+                row,
+                column,
+                old_style: old.style,
+                new_style: new.style,
+            });
+        }
+        if old.format != new.format {
+            changes.push(Change::CellFormat {
+                sheet_id,
+                row,
+                column,
+                old_format: old.format,
+                new_format: new.format,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::set_cell_values, SheetKind, WorkpadUpdate};
+
+    #[test]
+    fn diff_cells_and_sheets() {
+        let mut master = WorkpadMaster::new_starter();
+        let v0 = master.active_version().version().version();
+        let pad = master.active_version();
+        let sheets: Vec<Sheet> = pad.sheets().collect();
+        let sheet = &sheets[0];
+
+        set_cell_values(&mut master, sheet, &[(0, 0, "a"), (2, 1, "b")]);
+        let v1 = master.active_version().version().version();
+        set_cell_values(&mut master, sheet, &[(0, 0, "A"), (2, 1, ""), (3, 3, "c")]);
+        master
+            .update(WorkpadUpdate::SheetSetProperties {
+                sheet_id: sheets[1].id(),
+                new_name: String::from("Renamed"),
+            })
+            .unwrap();
+        master
+            .update(WorkpadUpdate::SheetDelete {
+                sheet_id: sheets[2].id(),
+            })
+            .unwrap();
+        let added = master
+            .update(WorkpadUpdate::SheetAdd {
+                kind: SheetKind::Worksheet,
+                name: String::from("Added"),
+            })
+            .unwrap();
+        let added_id = added.sheets().last().unwrap().id();
+        master
+            .update(WorkpadUpdate::WorkpadSetProperties {
+                new_name: String::from("Changed"),
+                new_author: added.author().to_owned(),
+            })
+            .unwrap();
+        let v2 = master.active_version().version().version();
+
+        let diff = master.diff(v1, v2).unwrap();
+        assert_eq!((v1, v2), (diff.from(), diff.to()));
+        assert_eq!(
+            vec![
+                Change::WorkpadName {
+                    old_name: String::from("Unnamed"),
+                    new_name: String::from("Changed"),
+                },
+                Change::SheetRemoved {
+                    sheet_id: sheets[2].id(),
+                    name: sheets[2].name().to_owned(),
+                },
+                Change::CellValue {
+                    sheet_id: sheet.id(),
+                    row: 0,
+                    column: 0,
+                    old_value: String::from("a"),
+                    new_value: String::from("A"),
+                },
+                Change::CellValue {
+                    sheet_id: sheet.id(),
+                    row: 2,
+                    column: 1,
+                    old_value: String::from("b"),
+                    new_value: String::new(),
+                },
+                Change::CellValue {
+                    sheet_id: sheet.id(),
+                    row: 3,
+                    column: 3,
+                    old_value: String::new(),
+                    new_value: String::from("c"),
+                },
+                Change::SheetRenamed {
+                    sheet_id: sheets[1].id(),
+                    old_name: sheets[1].name().to_owned(),
+                    new_name: String::from("Renamed"),
+                },
+                Change::SheetAdded {
+                    sheet_id: added_id,
+                    name: String::from("Added"),
+                },
+            ],
+            diff.changes()
+        );
+        assert_eq!(
+            vec![(0, 0), (2, 1), (3, 3)],
+            diff.changed_cells(sheet.id()).collect::<Vec<_>>()
+        );
+
+        let diff = master.diff(v0, v1).unwrap();
+        assert_eq!(
+            vec![(0, 0), (2, 1)],
+            diff.changed_cells(sheet.id()).collect::<Vec<_>>()
+        );
+        assert!(master.diff(v2, v2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diff_across_branches() {
+        let mut master = WorkpadMaster::new_blank();
+        let pad = master
+            .update(WorkpadUpdate::SheetAdd {
+                kind: SheetKind::Worksheet,
+                name: String::from("Sheet"),
+            })
+            .unwrap();
+        let base = pad.version().version();
+        let sheet = pad.sheets().next().unwrap();
+        let cell = sheet.cell(1, 1);
+        let set = |master: &mut WorkpadMaster, value: &str| {
+            master
+                .update(WorkpadUpdate::SheetSetCellValue {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    value: value.to_owned(),
+                })
+                .unwrap()
+                .version()
+                .version()
+        };
+
+        let left = set(&mut master, "left");
+        master
+            .update(WorkpadUpdate::SetVersion { version: base })
+            .unwrap();
+        let right = set(&mut master, "right");
+
+        let diff = master.diff(left, right).unwrap();
+        assert_eq!(
+            vec![Change::CellValue {
+                sheet_id: sheet.id(),
+                row: 1,
+                column: 1,
+                old_value: String::from("left"),
+                new_value: String::from("right"),
+            }],
+            diff.changes()
+        );
+        assert_eq!(
+            "B2: \"left\" \u{2192} \"right\"",
+            diff.changes()[0].to_string()
+        );
+    }
+
+    #[test]
+    fn diff_cell_styles_and_formats() {
+        let mut master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+        let from = pad.version().version();
+        let sheet = pad.sheets().next().unwrap();
+        let (styled, formatted) = (sheet.cell(1, 1), sheet.cell(2, 0));
+        master
+            .update(WorkpadUpdate::SheetSetCellStyle {
+                sheet_id: sheet.id(),
+                row_id: styled.row().id(),
+                column_id: styled.column().id(),
+                style: Some(String::from("Heading")),
+            })
+            .unwrap();
+        let format = Format {
+            bold: Some(true),
+            ..Default::default()
+        };
+        let to = master
+            .update(WorkpadUpdate::SheetSetCellFormat {
+                sheet_id: sheet.id(),
+                row_id: formatted.row().id(),
+                column_id: formatted.column().id(),
+                format: format.clone(),
+            })
+            .unwrap()
+            .version()
+            .version();
+
+        let diff = master.diff(from, to).unwrap();
+        assert_eq!(
+            vec![
+                Change::CellStyle {
+                    sheet_id: sheet.id(),
+                    row: 1,
+                    column: 1,
+                    old_style: None,
+                    new_style: Some(String::from("Heading")),
+                },
+                Change::CellFormat {
+                    sheet_id: sheet.id(),
+                    row: 2,
+                    column: 0,
+                    old_format: Format::default(),
+                    new_format: format,
+                },
+            ],
+            diff.changes()
+        );
+        assert_eq!(
+            "B2 style: none \u{2192} \"Heading\"",
+            diff.changes()[0].to_string()
+        );
+        assert_eq!(
+            vec![(1, 1), (2, 0)],
+            diff.changed_cells(sheet.id()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn diff_errors() {
+        let master = WorkpadMaster::new_blank();
+        assert_eq!(DiffError::MissingVersion(7), master.diff(0, 7).unwrap_err());
+        assert_eq!(DiffError::MissingVersion(5), master.diff(5, 0).unwrap_err());
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::NumberFormat;
+
+/// A colour given by its red, green and blue components
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+
+    /// Create a [`Color`] from its red, green and blue components
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// The horizontal alignment of the content of a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HorizontalAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// How text that is too wide for its cell is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextOverflow {
+    /// The text is cut off at the edges of the cell
+    Clip,
+    /// The text spills over into empty neighbouring cells
+    Overflow,
+    /// The text is broken onto as many lines as needed, growing the row to fit them
+    Wrap,
+    /// The text is made smaller until it fits the cell
+    ShrinkToFit,
+}
+
+/// The vertical alignment of the content of a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VerticalAlignment {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// The weight of the line drawn for a [`Border`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BorderStyle {
+    Thin,
+    Medium,
+    Thick,
+}
+
+impl BorderStyle {
+    /// Returns the width of the line drawn, in pixels
+    pub fn width(&self) -> f32 {
+        match self {
+            BorderStyle::Thin => 1.0,
+            BorderStyle::Medium => 2.0,
+            BorderStyle::Thick => 3.0,
+        }
+    }
+}
+
+/// A line drawn along one side of a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Border {
+    pub style: BorderStyle,
+    pub color: Color,
+}
+
+impl Border {
+    /// Create a new [`Border`]
+    pub fn new(style: BorderStyle, color: Color) -> Self {
+        Self { style, color }
+    }
+}
+
+/// The [`Border`]s of each side of a cell.  Sides without a border are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Borders {
+    pub top: Option<Border>,
+    pub right: Option<Border>,
+    pub bottom: Option<Border>,
+    pub left: Option<Border>,
+}
+
+impl Borders {
+    pub const NONE: Borders = Borders {
+        top: None,
+        right: None,
+        bottom: None,
+        left: None,
+    };
+
+    /// Create a [`Borders`] that is the same on all sides
+    pub fn all(border: Border) -> Self {
+        Self {
+            top: Some(border),
+            right: Some(border),
+            bottom: Some(border),
+            left: Some(border),
+        }
+    }
+
+    /// Returns true if no side has a border
+    pub fn is_empty(&self) -> bool {
+        *self == Borders::NONE
+    }
+
+    /// Returns these borders with the sides that have a border in `other` replaced
+    pub fn overlay(&self, other: &Borders) -> Self {
+        Self {
+            top: other.top.or(self.top),
+            right: other.right.or(self.right),
+            bottom: other.bottom.or(self.bottom),
+            left: other.left.or(self.left),
+        }
+    }
+}
+
+/// The formatting of a cell: how its content is drawn.
+///
+/// Each property is optional.  Properties that are not set take their default values, as
+/// chosen by the application displaying the cell.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Format {
+    /// The name of the font family of the text
+    pub font_family: Option<String>,
+    /// The size of the text
+    pub font_size: Option<f32>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+    /// The colour of the text
+    pub text_color: Option<Color>,
+    /// The colour the cell is filled with behind the text
+    pub fill_color: Option<Color>,
+    pub horizontal_alignment: Option<HorizontalAlignment>,
+    pub vertical_alignment: Option<VerticalAlignment>,
+    /// How text too wide for the cell is displayed, clipped if not set
+    pub text_overflow: Option<TextOverflow>,
+    pub borders: Borders,
+    /// How the value is displayed when it is a number, date or time
+    pub number_format: Option<NumberFormat>,
+}
+
+impl Format {
+    /// A [`Format`] with no properties set
+    pub const NONE: Format = Format {
+        font_family: None,
+        font_size: None,
+        bold: None,
+        italic: None,
+        underline: None,
+        text_color: None,
+        fill_color: None,
+        horizontal_alignment: None,
+        vertical_alignment: None,
+        text_overflow: None,
+        borders: Borders::NONE,
+        number_format: None,
+    };
+
+    /// Returns true if no properties are set
+    pub fn is_empty(&self) -> bool {
+        *self == Format::NONE
+    }
+
+    /// Returns this format with the properties that are set in `other` replaced
+    pub fn overlay(&self, other: &Format) -> Self {
+        Self {
+            font_family: other.font_family.clone().or(self.font_family.clone()),
+            font_size: other.font_size.or(self.font_size),
+            bold: other.bold.or(self.bold),
+            italic: other.italic.or(self.italic),
+            underline: other.underline.or(self.underline),
+            text_color: other.text_color.or(self.text_color),
+            fill_color: other.fill_color.or(self.fill_color),
+            horizontal_alignment: other.horizontal_alignment.or(self.horizontal_alignment),
+            vertical_alignment: other.vertical_alignment.or(self.vertical_alignment),
+            text_overflow: other.text_overflow.or(self.text_overflow),
+            borders: self.borders.overlay(&other.borders),
+            number_format: other.number_format.clone().or(self.number_format.clone()),
+        }
+    }
+}
+
+/// Returns the named styles that new workpads start with
+pub(crate) fn default_styles() -> BTreeMap<String, Format> {
+    let thin = Border::new(BorderStyle::Thin, Color::BLACK);
+    let styles = [
+        (
+            "Heading",
+            Format {
+                font_size: Some(14.0),
+                bold: Some(true),
+                ..Format::NONE
+            },
+        ),
+        (
+            "Input",
+            Format {
+                text_color: Some(Color::rgb(0, 0, 160)),
+                fill_color: Some(Color::rgb(255, 242, 204)),
+                ..Format::NONE
+            },
+        ),
+        (
+            "Total",
+            Format {
+                bold: Some(true),
+                borders: Borders {
+                    top: Some(thin),
+                    bottom: Some(Border::new(BorderStyle::Medium, Color::BLACK)),
+                    ..Borders::NONE
+                },
+                ..Format::NONE
+            },
+        ),
+    ];
+    styles
+        .into_iter()
+        .map(|(name, format)| (name.to_owned(), format))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_replaces_only_properties_set() {
+        let thin = Border::new(BorderStyle::Thin, Color::BLACK);
+        let thick = Border::new(BorderStyle::Thick, Color::rgb(255, 0, 0));
+        let base = Format {
+            font_family: Some("Serif".to_owned()),
+            bold: Some(true),
+            fill_color: Some(Color::WHITE),
+            borders: Borders::all(thin),
+            ..Default::default()
+        };
+        let other = Format {
+            bold: Some(false),
+            italic: Some(true),
+            text_overflow: Some(TextOverflow::Wrap),
+            number_format: Some(NumberFormat::Percentage { decimals: 0 }),
+            borders: Borders {
+                bottom: Some(thick),
+                ..Borders::NONE
+            },
+            ..Default::default()
+        };
+
+        let format = base.overlay(&other);
+        assert_eq!(Some("Serif"), format.font_family.as_deref());
+        assert_eq!(Some(false), format.bold);
+        assert_eq!(Some(true), format.italic);
+        assert_eq!(Some(Color::WHITE), format.fill_color);
+        assert_eq!(None, format.underline);
+        assert_eq!(Some(TextOverflow::Wrap), format.text_overflow);
+        assert_eq!(other.number_format, format.number_format);
+        assert_eq!(Some(thin), format.borders.top);
+        assert_eq!(Some(thick), format.borders.bottom);
+
+        assert!(Format::default().is_empty());
+        assert_eq!(base, base.overlay(&Format::NONE));
+    }
+}
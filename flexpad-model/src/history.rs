@@ -0,0 +1,603 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{is_implied_id, Cell, HistoryEntry, Version, Workpad};
+
+/// Describes a version of a workpad: the update that created it, and when and by whom
+/// that update was made.  See [`Workpad::version`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionInfo {
+    version: Version,
+    description: String,
+    timestamp: SystemTime,
+    author: String,
+}
+
+impl VersionInfo {
+    pub(crate) fn new(version: Version, entry: &HistoryEntry) -> Self {
+        Self {
+            version,
+            description: entry.update.to_string(),
+            timestamp: entry.timestamp,
+            author: entry.author.to_string(),
+        }
+    }
+
+    /// Returns the version described
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns a description of the update that created the version
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns when the version was created
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// Returns the name of the user who created the version
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+}
+
+/// A label, and optional note, marking an important version of a workpad (e.g. "Q3 final").
+///
+/// Checkpoints are held in the history of the workpad and are set and cleared with
+/// [`WorkpadUpdate::SetCheckpoint`](crate::WorkpadUpdate::SetCheckpoint) and
+/// [`WorkpadUpdate::ClearCheckpoint`](crate::WorkpadUpdate::ClearCheckpoint).  Versions with a
+/// checkpoint are never removed when the history is compacted.  Checkpoints are serialized
+/// with the content of a workpad, which only holds its current version, so only the
+/// checkpoint of that version is kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    label: String,
+    note: String,
+}
+
+impl Checkpoint {
+    pub(crate) fn new(label: &str, note: &str) -> Self {
+        Self {
+            label: label.to_owned(),
+            note: note.to_owned(),
+        }
+    }
+
+    /// Returns the label of the checkpoint
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the note of the checkpoint which may be empty
+    pub fn note(&self) -> &str {
+        &self.note
+    }
+}
+
+/// A version of a workpad as a node of the tree formed by its history.
+///
+/// Each version is based on a prior version.  Stepping back to an earlier version and then
+/// updating the workpad starts a new branch of the tree from that earlier version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionNode {
+    info: VersionInfo,
+    prior_version: Option<Version>,
+    next_versions: Vec<Version>,
+    depth: usize,
+    checkpoint: Option<Checkpoint>,
+}
+
+impl VersionNode {
+    /// Returns the version represented by this node
+    pub fn version(&self) -> Version {
+        self.info.version
+    }
+
+    /// Returns a description of the update that created this version
+    pub fn description(&self) -> &str {
+        &self.info.description
+    }
+
+    /// Returns the full [`VersionInfo`] for this version
+    pub fn info(&self) -> &VersionInfo {
+        &self.info
+    }
+
+    /// Returns the version this version is based on, `None` for the first version
+    pub fn prior_version(&self) -> Option<Version> {
+        self.prior_version
+    }
+
+    /// Returns the versions based on this version, oldest first.  There is more than one
+    /// when a branch was started from this version.
+    pub fn next_versions(&self) -> &[Version] {
+        &self.next_versions
+    }
+
+    /// Returns the [`Checkpoint`] marking this version, if any
+    pub fn checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    /// Returns the number of branches taken to reach this version from the first version,
+    /// where following the oldest of the next versions is not counted as a branch.  This
+    /// is suitable as the indent level when displaying the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+/// A change to the value of a cell.  See [`Cell::history`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellChange {
+    info: VersionInfo,
+    old_value: String,
+    new_value: String,
+}
+
+impl CellChange {
+    /// Returns the version in which the value changed
+    pub fn version(&self) -> Version {
+        self.info.version
+    }
+
+    /// Returns the full [`VersionInfo`] for the version in which the value changed
+    pub fn info(&self) -> &VersionInfo {
+        &self.info
+    }
+
+    /// Returns the value before the change, empty if the cell had no value
+    pub fn old_value(&self) -> &str {
+        &self.old_value
+    }
+
+    /// Returns the value after the change, empty if the value was cleared
+    pub fn new_value(&self) -> &str {
+        &self.new_value
+    }
+}
+
+impl Cell {
+    /// Returns the changes to the value of this cell in the versions leading to the version
+    /// it is part of, most recent first.  Changes on other branches of the history are not
+    /// included.
+    pub fn history(&self) -> Vec<CellChange> {
+        let (row_id, column_id) = (self.row.id(), self.column.id());
+        // A cell has only been written if ids have been allocated for its row and column
+        if is_implied_id(row_id.0) || is_implied_id(column_id.0) {
+            return vec![];
+        }
+
+        let master_data = &self.sheet.workpad.master.data;
+        let sheet_id = self.sheet.id;
+        let candidates = master_data.sheet_cell_versions(sheet_id, row_id, column_id);
+        let value_at =
+            |version| master_data.read_sheet_cell_value(sheet_id, row_id, column_id, version);
+
+        let history = master_data.history.read().unwrap();
+        let mut changes = vec![];
+        let mut version = Some(self.sheet.version);
+        while let Some(ver) = version {
+            let entry = &history[ver as usize];
+            if candidates.contains(&ver) {
+                let new_value = value_at(ver);
+                let old_value = entry.prior_version.map(value_at).unwrap_or_default();
+                if old_value != new_value {
+                    changes.push(CellChange {
+                        info: VersionInfo::new(ver, entry),
+                        old_value,
+                        new_value,
+                    });
+                }
+            }
+            version = entry.prior_version;
+        }
+        changes
+    }
+}
+
+impl Workpad {
+    /// Returns the [`Checkpoint`] marking this version, if any
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        let history = self.master.data.history.read().unwrap();
+        history[self.version as usize].checkpoint.clone()
+    }
+
+    /// Returns all the versions of the workpad that are marked with a [`Checkpoint`], oldest
+    /// first, whichever branch they are on.
+    pub fn checkpoints(&self) -> Vec<(Version, Checkpoint)> {
+        let history = self.master.data.history.read().unwrap();
+        history
+            .iter()
+            .enumerate()
+            .filter_map(|(version, entry)| {
+                entry
+                    .checkpoint
+                    .clone()
+                    .map(|checkpoint| (version as Version, checkpoint))
+            })
+            .collect()
+    }
+
+    /// Returns all the versions of the workpad as a tree in depth-first order.  Each version
+    /// is followed by its oldest next version (and that version's successors) and then by
+    /// any branches from it.
+    pub fn version_tree(&self) -> Vec<VersionNode> {
+        let history = self.master.data.history.read().unwrap();
+
+        let mut next_versions: Vec<Vec<Version>> = vec![vec![]; history.len()];
+        let mut roots = vec![];
+        for (version, entry) in history.iter().enumerate() {
+            match entry.prior_version {
+                Some(prior_version) => {
+                    next_versions[prior_version as usize].push(version as Version)
+                }
+                None => roots.push(version as Version),
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(history.len());
+        let mut stack: Vec<(Version, usize)> = roots.into_iter().rev().map(|v| (v, 0)).collect();
+        while let Some((version, depth)) = stack.pop() {
+            let entry = &history[version as usize];
+            let next = std::mem::take(&mut next_versions[version as usize]);
+            for (idx, next_version) in next.iter().enumerate().rev() {
+                stack.push((*next_version, if idx == 0 { depth } else { depth + 1 }));
+            }
+            nodes.push(VersionNode {
+                info: VersionInfo::new(version, entry),
+                prior_version: entry.prior_version,
+                next_versions: next,
+                depth,
+                checkpoint: entry.checkpoint.clone(),
+            });
+        }
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, WorkpadMaster, WorkpadUpdate};
+
+    use super::*;
+
+    #[test]
+    fn version_tree() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(0, 0);
+        let mut update = |update| master.update(update).expect("Update should succeed");
+        let set_value = |value: &str| WorkpadUpdate::SheetSetCellValue {
+            sheet_id: sheet.id(),
+            row_id: cell.row().id(),
+            column_id: cell.column().id(),
+            value: value.to_owned(),
+        };
+
+        // 0 -> 1 -> 2 -> 3
+        //        -> 4 -> 5
+        //             -> 6
+        update(set_value("A"));
+        update(set_value("B"));
+        update(set_value("C"));
+        update(WorkpadUpdate::SetVersion { version: 1 });
+        update(WorkpadUpdate::SetActiveSheet {
+            sheet_id: sheet.id(),
+        });
+        update(set_value("D"));
+        update(WorkpadUpdate::SetVersion { version: 4 });
+        let pad = update(set_value("E"));
+
+        let tree: Vec<(Version, usize, Option<Version>, Vec<Version>)> = pad
+            .version_tree()
+            .iter()
+            .map(|node| {
+                (
+                    node.version(),
+                    node.depth(),
+                    node.prior_version(),
+                    node.next_versions().to_vec(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 0, None, vec![1]),
+                (1, 0, Some(0), vec![2, 4]),
+                (2, 0, Some(1), vec![3]),
+                (3, 0, Some(2), vec![]),
+                (4, 1, Some(1), vec![5, 6]),
+                (5, 1, Some(4), vec![]),
+                (6, 2, Some(4), vec![]),
+            ],
+            tree
+        );
+
+        let tree = pad.version_tree();
+        assert_eq!("New Workpad", tree[0].description());
+        assert_eq!("Set Active Sheet", tree[4].description());
+    }
+
+    #[test]
+    fn versions_record_when_and_by_whom() {
+        let before = SystemTime::now();
+        let mut master = WorkpadMaster::new_starter();
+        let pad = master
+            .update(WorkpadUpdate::WorkpadSetProperties {
+                new_name: String::from("Audited"),
+                new_author: String::from("Someone Else"),
+            })
+            .unwrap();
+        let after = SystemTime::now();
+
+        let info = pad.version();
+        assert_eq!(1, info.version());
+        assert!(before <= info.timestamp() && info.timestamp() <= after);
+        assert_eq!(whoami::realname(), info.author());
+
+        // The author of each version is who made the change not the author of the workpad
+        let prior = pad.backward_versions().next().unwrap();
+        assert_eq!(0, prior.version());
+        assert!(prior.timestamp() <= info.timestamp());
+        assert_eq!(whoami::realname(), prior.author());
+        assert_eq!(info, pad.version_tree()[1].info().clone());
+    }
+
+    #[test]
+    fn updates_by_others_record_their_author() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let pad = master
+            .update_by(
+                WorkpadUpdate::SheetSetProperties {
+                    sheet_id: sheet.id(),
+                    new_name: String::from("Shared"),
+                },
+                "A Collaborator",
+            )
+            .unwrap();
+
+        assert_eq!("A Collaborator", pad.version().author());
+        assert_eq!(
+            whoami::realname(),
+            pad.backward_versions().next().unwrap().author()
+        );
+    }
+
+    #[test]
+    fn cell_history() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let set = |master: &mut WorkpadMaster, row: usize, value: &str| {
+            let cell = sheet.cell(row, 1);
+            master
+                .update(WorkpadUpdate::SheetSetCellValue {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    value: value.to_owned(),
+                })
+                .unwrap()
+        };
+        let changes = |pad: &Workpad| -> Vec<(Version, String, String)> {
+            let cell = pad.active_sheet().unwrap().cell(3, 1);
+            cell.history()
+                .iter()
+                .map(|change| {
+                    assert_eq!(change.version(), change.info().version());
+                    (
+                        change.version(),
+                        change.old_value().to_owned(),
+                        change.new_value().to_owned(),
+                    )
+                })
+                .collect()
+        };
+        let change = |version, old: &str, new: &str| (version, old.to_owned(), new.to_owned());
+
+        // Never written
+        assert!(changes(&master.active_version()).is_empty());
+
+        set(&mut master, 3, "a"); // 1
+        set(&mut master, 4, "other"); // 2
+        set(&mut master, 3, "b"); // 3
+        set(&mut master, 3, "b"); // 4
+        let pad = set(&mut master, 3, ""); // 5
+        assert_eq!(
+            vec![change(5, "b", ""), change(3, "a", "b"), change(1, "", "a")],
+            changes(&pad)
+        );
+
+        // Changes on other branches are not included, including going back to the base
+        master
+            .update(WorkpadUpdate::SetVersion { version: 2 })
+            .unwrap();
+        let pad = set(&mut master, 3, "c"); // 6
+        assert_eq!(vec![change(6, "a", "c"), change(1, "", "a")], changes(&pad));
+        master
+            .update(WorkpadUpdate::SetVersion { version: 4 })
+            .unwrap();
+        let pad = set(&mut master, 4, "more"); // 7
+        assert_eq!(vec![change(3, "a", "b"), change(1, "", "a")], changes(&pad));
+
+        // Earlier versions see only their own past
+        master
+            .update(WorkpadUpdate::SetVersion { version: 2 })
+            .unwrap();
+        assert_eq!(vec![change(1, "", "a")], changes(&master.active_version()));
+        master
+            .update(WorkpadUpdate::SetVersion { version: 0 })
+            .unwrap();
+        assert!(changes(&master.active_version()).is_empty());
+    }
+
+    #[test]
+    fn checkpoints() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(0, 0);
+        let mut update = |update| master.update(update);
+        let set_value = |value: &str| WorkpadUpdate::SheetSetCellValue {
+            sheet_id: sheet.id(),
+            row_id: cell.row().id(),
+            column_id: cell.column().id(),
+            value: value.to_owned(),
+        };
+
+        update(set_value("A")).unwrap();
+        update(set_value("B")).unwrap();
+        let pad = update(WorkpadUpdate::SetCheckpoint {
+            version: 1,
+            label: String::from(" Sent to client "),
+            note: String::from("First draft"),
+        })
+        .unwrap();
+
+        // No new version is created
+        assert_eq!(2, pad.version().version());
+        assert!(pad.checkpoint().is_none());
+        assert_eq!(
+            vec![(1, Checkpoint::new("Sent to client", "First draft"))],
+            pad.checkpoints()
+        );
+        let pad = update(WorkpadUpdate::SetVersion { version: 1 }).unwrap();
+        assert_eq!("Sent to client", pad.checkpoint().unwrap().label());
+        assert_eq!(
+            "First draft",
+            pad.version_tree()[1].checkpoint().unwrap().note()
+        );
+
+        assert!(update(WorkpadUpdate::SetCheckpoint {
+            version: 3,
+            label: String::from("Q3 final"),
+            note: String::new(),
+        })
+        .is_err());
+        assert!(update(WorkpadUpdate::SetCheckpoint {
+            version: 2,
+            label: String::from(" "),
+            note: String::new(),
+        })
+        .is_err());
+
+        let pad = update(WorkpadUpdate::ClearCheckpoint { version: 1 }).unwrap();
+        assert!(pad.checkpoints().is_empty());
+
+        // Checkpoints are not changes to a version so cannot be made along with them
+        let err = update(WorkpadUpdate::Multi(vec![
+            set_value("C"),
+            WorkpadUpdate::SetCheckpoint {
+                version: 1,
+                label: String::from("Combined"),
+                note: String::new(),
+            },
+        ]))
+        .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::NotAllowedInMulti));
+        let pad = master.active_version();
+        assert_eq!(1, pad.version().version());
+        assert!(pad.checkpoints().is_empty());
+    }
+
+    #[test]
+    fn compact_history_keeps_checkpoints() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(0, 0);
+        let mut update = |update| master.update(update).expect("Update should succeed");
+        let set_value = |value: &str| WorkpadUpdate::SheetSetCellValue {
+            sheet_id: sheet.id(),
+            row_id: cell.row().id(),
+            column_id: cell.column().id(),
+            value: value.to_owned(),
+        };
+
+        // 0 -> 1 -> 2 -> 3 -> 4 -> 5
+        //             -> 6 -> 7
+        // with checkpoints on 3 (a dead branch) and 1 (older than those retained)
+        for value in ["A", "B", "C", "D", "E"] {
+            update(set_value(value));
+        }
+        update(WorkpadUpdate::SetVersion { version: 2 });
+        update(set_value("F"));
+        update(set_value("G"));
+        for (version, label) in [(3, "Dead"), (1, "Old")] {
+            update(WorkpadUpdate::SetCheckpoint {
+                version,
+                label: String::from(label),
+                note: String::new(),
+            });
+        }
+
+        let pad = master.compact_history(Some(1));
+        let labels: Vec<(Version, String)> = pad
+            .checkpoints()
+            .into_iter()
+            .map(|(version, checkpoint)| (version, checkpoint.label().to_owned()))
+            .collect();
+        assert_eq!(
+            vec![(0, String::from("Old")), (2, String::from("Dead"))],
+            labels
+        );
+
+        // Versions 1, 2, 3, 6 and 7 remain
+        assert_eq!(5, pad.version_tree().len());
+        assert_eq!(4, pad.version().version());
+        let pad = master
+            .update(WorkpadUpdate::SetVersion { version: 2 })
+            .unwrap();
+        assert_eq!("C", pad.active_sheet().unwrap().cell(0, 0).value());
+        let pad = master
+            .update(WorkpadUpdate::SetVersion { version: 0 })
+            .unwrap();
+        assert_eq!("A", pad.active_sheet().unwrap().cell(0, 0).value());
+    }
+
+    #[test]
+    fn forward_versions_from_any_branch() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(0, 0);
+        let mut set_value = |value: &str| {
+            master
+                .update(WorkpadUpdate::SheetSetCellValue {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    value: value.to_owned(),
+                })
+                .expect("Update should succeed")
+        };
+
+        // 0 -> 1 -> 2 -> 3
+        //        -> 4
+        set_value("A");
+        set_value("B");
+        let pad = set_value("C");
+        pad.master()
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .expect("Update should succeed");
+        let pad = set_value("D");
+
+        // Forward from a version on a dead branch follows that branch
+        let pad = pad
+            .master()
+            .update(WorkpadUpdate::SetVersion { version: 2 })
+            .expect("Update should succeed");
+        let forward: Vec<Version> = pad.forward_versions().map(|info| info.version()).collect();
+        assert_eq!(vec![3], forward);
+
+        // Forward from a branch point follows the most recent branch
+        let pad = pad
+            .master()
+            .update(WorkpadUpdate::SetVersion { version: 0 })
+            .expect("Update should succeed");
+        let forward: Vec<Version> = pad.forward_versions().map(|info| info.version()).collect();
+        assert_eq!(vec![1, 4], forward);
+    }
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Where a link held by a cell leads when it is followed
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hyperlink {
+    /// The name of a sheet of the same workpad, or a reference to a cell or range of cells
+    /// of it as resolved by [`crate::Workpad::resolve_reference`], e.g. `Sheet 2!B3`
+    Reference(String),
+    /// A web address or the path of a local file, opened by the desktop.  Only `http`,
+    /// `https` and `mailto` addresses and absolute paths are allowed.
+    External(String),
+}
+
+impl Hyperlink {
+    /// Returns the reference, address or path the link leads to
+    pub fn target(&self) -> &str {
+        match self {
+            Hyperlink::Reference(target) | Hyperlink::External(target) => target,
+        }
+    }
+
+    /// Returns true if the link may be held by a cell: one that leads somewhere and, if it
+    /// is external, to a web address or absolute path (see [`Hyperlink::External`]).
+    ///
+    /// Whether a file exists is not checked as links are shared with other machines, where
+    /// files differ, and files come and go; [`Hyperlink::is_openable`] checks that too.
+    pub fn is_allowed(&self) -> bool {
+        match self {
+            Hyperlink::Reference(target) => !target.trim().is_empty(),
+            Hyperlink::External(target) => {
+                let target = target.trim();
+                !target.chars().any(char::is_control)
+                    && (is_web_address(target) || Path::new(target).is_absolute())
+            }
+        }
+    }
+
+    /// Returns true if the link is allowed and, if it leads to a file, the file exists, so
+    /// that the link may be passed to the desktop to open
+    pub fn is_openable(&self) -> bool {
+        match self {
+            Hyperlink::External(target) if !is_web_address(target.trim()) => {
+                self.is_allowed() && Path::new(target.trim()).is_file()
+            }
+            _ => self.is_allowed(),
+        }
+    }
+}
+
+/// Returns true if the target is an address with a scheme the desktop may be asked to open
+fn is_web_address(target: &str) -> bool {
+    match target.split_once(':') {
+        Some((scheme, rest)) => {
+            let scheme = scheme.to_ascii_lowercase();
+            matches!(scheme.as_str(), "http" | "https" | "mailto") && !rest.is_empty()
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, WorkpadMaster, WorkpadUpdate};
+
+    #[test]
+    fn hyperlinks_are_set_and_cleared() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let (sheet_id, row_id, column_id) = (sheet.id(), sheet.row(2).id(), sheet.column(0).id());
+        let set = |hyperlink| WorkpadUpdate::SheetSetCellHyperlink {
+            sheet_id,
+            row_id,
+            column_id,
+            hyperlink,
+        };
+
+        let link = Hyperlink::Reference(String::from("'Sheet 2'!B3"));
+        let pad = master.update(set(Some(link.clone()))).unwrap();
+        let cell = pad.active_sheet().unwrap().cell(2, 0);
+        assert_eq!(Some(&link), cell.hyperlink());
+        let target = pad.resolve_reference(link.target()).unwrap();
+        assert_eq!("Sheet 2", target.sheet().name());
+
+        let err = master
+            .update(set(Some(Hyperlink::External(String::from("  ")))))
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::InvalidHyperlink));
+
+        let pad = master.update(set(None)).unwrap();
+        assert_eq!(None, pad.active_sheet().unwrap().cell(2, 0).hyperlink());
+    }
+
+    #[test]
+    fn external_links_are_limited_to_web_addresses_and_files() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let (sheet_id, row_id, column_id) = (sheet.id(), sheet.row(2).id(), sheet.column(0).id());
+        let set = |target: &str| WorkpadUpdate::SheetSetCellHyperlink {
+            sheet_id,
+            row_id,
+            column_id,
+            hyperlink: Some(Hyperlink::External(target.to_owned())),
+        };
+
+        let file = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        for target in [
+            "https://example.com/a b",
+            "HTTP://example.com",
+            "mailto:me@example.com",
+            file,
+        ] {
+            assert!(master.update(set(target)).is_ok(), "{target}");
+            assert!(
+                Hyperlink::External(target.to_owned()).is_openable(),
+                "{target}"
+            );
+        }
+
+        for target in [
+            "javascript:alert(1)",
+            "file:///etc/passwd",
+            "ms-settings:",
+            "https:",
+            "calc.exe",
+            "report.pdf",
+            "-e /bin/sh",
+            "--help",
+            "https://example.com\n/bin/sh",
+            "& del *",
+        ] {
+            let err = master.update(set(target)).unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::InvalidHyperlink), "{target}");
+            assert!(
+                !Hyperlink::External(target.to_owned()).is_openable(),
+                "{target}"
+            );
+        }
+
+        // Files may be linked to before they exist, but not opened
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/no such file");
+        assert!(master.update(set(missing)).is_ok());
+        assert!(!Hyperlink::External(missing.to_owned()).is_openable());
+    }
+}
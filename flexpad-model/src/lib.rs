@@ -1,23 +1,55 @@
 use std::{
     borrow::Borrow,
-    collections::{btree_map::Range, BTreeMap},
+    collections::{btree_map::Range, BTreeMap, BTreeSet, HashMap},
     error::Error,
     fmt,
-    ops::RangeBounds,
+    ops::{RangeBounds, RangeInclusive},
     sync::{
         atomic::{AtomicU32, Ordering},
-        Arc, RwLock,
+        Arc, Mutex, PoisonError, RwLock,
     },
+    time::SystemTime,
 };
 
 use internment::Intern;
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use rust_i18n::{i18n, t};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 //use crate::display_iter;
 
+mod comment;
+mod conditional;
+mod diff;
+mod format;
+mod history;
+mod hyperlink;
+mod number_format;
+mod observe;
+mod range;
+mod reference;
+mod search;
+mod validation;
+
+pub use comment::Comment;
+pub use conditional::{Comparison, Condition, ConditionalEvaluator, ConditionalFormat};
+pub use diff::{Change, DiffError, WorkpadDiff};
+pub use format::{
+    Border, BorderStyle, Borders, Color, Format, HorizontalAlignment, TextOverflow,
+    VerticalAlignment,
+};
+pub use history::{CellChange, Checkpoint, VersionInfo, VersionNode};
+pub use hyperlink::Hyperlink;
+pub use number_format::{NumberFormat, NumberFormatError};
+use observe::{Affected, Observers, PendingEvent};
+pub use observe::{Observation, VersionChange, WorkpadEvent};
+pub use range::SheetRange;
+pub use reference::{CellReference, ReferenceError};
+pub use search::{FindDirection, FindError, FindOptions, FindScope, Finder};
+pub use validation::{Validation, ValidationError, ValidationRule, Validator};
+
 i18n!("locales", fallback = "en");
 
 // Overview of the Workpad Model
@@ -72,7 +104,7 @@ macro_rules! workpad_id_type {
         $type_name:ident
     ) => {
         $(#[$outer])*
-        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
         pub struct $type_name(IdBase);
 
         impl From<IdBase> for $type_name {
@@ -125,9 +157,11 @@ impl WorkpadMaster {
 
         let master_data = WorkpadMasterData {
             id: Uuid::new_v4().simple().to_string(),
+            writer: Mutex::new(()),
             transaction: RwLock::new(None),
             history: Default::default(),
             active_version: RwLock::new(0),
+            observers: Default::default(),
             next_part_id: Default::default(),
             workpad_idx: Default::default(),
             sheets_idx: Default::default(),
@@ -150,12 +184,13 @@ impl WorkpadMaster {
 
         let workpad_data = WorkpadData {
             name: Intern::from("Unnamed"),
-            author: Intern::from(whoami::realname().as_ref()),
+            author: *AUTHOR,
             sheets,
             active_sheet,
+            styles: Arc::new(format::default_styles()),
         };
         master_data.write_workpad(Arc::new(workpad_data), 0);
-        master_data.tx_commit(&tx, update);
+        master_data.tx_commit(&tx, update, *AUTHOR, Affected::default());
 
         WorkpadMaster {
             data: Arc::new(master_data),
@@ -183,64 +218,179 @@ impl WorkpadMaster {
     /// Update the workpad by creating a new version with the supplied
     /// [`WorkpadUpdate`] applied.  The generated version becomes the
     /// active version of the workpad.
+    ///
+    /// Updates may be made from several threads at once (through clones of the
+    /// [`WorkpadMaster`]).  They are applied one at a time, each based on the version made
+    /// active by the one before, while readers of existing versions carry on unaffected.
     pub fn update(&mut self, update: WorkpadUpdate) -> UpdateResult {
-        if let WorkpadUpdate::SetVersion { version } = update {
-            let history = self.data.history.read().unwrap();
-            if (version as usize) < history.len() {
-                self.data.set_version(version);
-                Ok(self.active_version())
-            } else {
-                Err(UpdateError {
-                    kind: ErrorKind::MissingVersion(version),
-                    update: update.clone(),
-                    workpad_id: self.data.id.clone(),
-                    workpad_version: *self.data.active_version.read().unwrap(),
-                })
-            }
-        } else {
-            let tx = self.data.tx_begin();
+        self.internal_update(update, *AUTHOR)
+    }
+
+    /// Update the workpad as [`WorkpadMaster::update`] does, recording `author` as the
+    /// author of the new version rather than the user running this process.  Used to apply
+    /// updates made by others, such as collaborators editing the same workpad.
+    pub fn update_by(&mut self, update: WorkpadUpdate, author: &str) -> UpdateResult {
+        self.internal_update(update, Intern::from(author))
+    }
+
+    fn internal_update(&mut self, update: WorkpadUpdate, author: Intern<str>) -> UpdateResult {
+        // A writer that panicked leaves nothing for the lock to protect so it is taken over
+        let data = self.data.clone();
+        let writer = data.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let event = self.locked_update(update, author)?;
+        let pad = self.active_version();
+        drop(writer);
+
+        // Observers are called once other writers may proceed so that they can update too
+        if let Some(event) = event {
+            event.notify();
+        }
+        Ok(pad)
+    }
+
+    fn locked_update(
+        &mut self,
+        update: WorkpadUpdate,
+        author: Intern<str>,
+    ) -> Result<Option<PendingEvent>, UpdateError> {
+        let new_err = |kind| {
+            Err(UpdateError {
+                kind,
+                update: Box::new(update.clone()),
+                workpad_id: self.data.id.clone(),
+                workpad_version: *self.data.active_version.read().unwrap(),
+            })
+        };
 
-            match self.apply_update(&update, &tx) {
-                Ok(_) => {
-                    self.data.tx_commit(&tx, update);
-                    Ok(self.active_version())
+        // Changes to the active version and checkpoints do not create a new version
+        match update {
+            WorkpadUpdate::SetVersion { version } => {
+                if version as usize >= self.data.history.read().unwrap().len() {
+                    return new_err(ErrorKind::MissingVersion(version));
                 }
-                Err(err) => {
-                    self.data.tx_rollback(&tx);
-                    Err(err)
+                let prior_version = self.data.active_version();
+                self.data.set_version(version);
+                Ok(self.pending_event(|| {
+                    let history = self.data.history.read().unwrap();
+                    WorkpadEvent::VersionChanged(VersionChange::new(
+                        self.clone(),
+                        prior_version,
+                        version,
+                        affected_between(&history, prior_version, version),
+                    ))
+                }))
+            }
+            WorkpadUpdate::SetCheckpoint {
+                version,
+                ref label,
+                ref note,
+            } => {
+                if label.trim().is_empty() {
+                    return new_err(ErrorKind::InvalidName(label.clone()));
                 }
+                let mut history = self.data.history.write().unwrap();
+                let Some(entry) = history.get_mut(version as usize) else {
+                    return new_err(ErrorKind::MissingVersion(version));
+                };
+                entry.checkpoint = Some(Checkpoint::new(label.trim(), note));
+                Ok(None)
+            }
+            WorkpadUpdate::ClearCheckpoint { version } => {
+                let mut history = self.data.history.write().unwrap();
+                let Some(entry) = history.get_mut(version as usize) else {
+                    return new_err(ErrorKind::MissingVersion(version));
+                };
+                entry.checkpoint = None;
+                Ok(None)
+            }
+            _ => self.update_new_version(update, author),
+        }
+    }
+
+    fn update_new_version(
+        &mut self,
+        update: WorkpadUpdate,
+        author: Intern<str>,
+    ) -> Result<Option<PendingEvent>, UpdateError> {
+        let tx = self.data.tx_begin();
+
+        let mut affected = Affected::default();
+        match self.apply_update(&update, &tx, &mut affected) {
+            Ok(_) => {
+                self.data.tx_commit(&tx, update, author, affected.clone());
+                Ok(self.pending_event(|| {
+                    WorkpadEvent::Committed(VersionChange::new(
+                        self.clone(),
+                        tx.active_version,
+                        tx.new_version,
+                        affected,
+                    ))
+                }))
+            }
+            Err(err) => {
+                self.data.tx_rollback(&tx);
+                Err(err)
             }
         }
     }
 
+    /// Compacts the history of the workpad and returns the active version.
+    ///
+    /// Versions that can no longer be reached by stepping backwards or forwards from the
+    /// active version (see [`Workpad::backward_versions`] and [`Workpad::forward_versions`])
+    /// are removed.  If `retain` is given, at most that many versions are kept going back from
+    /// the latest version; the oldest of these becomes the baseline of the history, holding
+    /// all the changes made before it.  Versions marked with a [`Checkpoint`], and the
+    /// versions leading to them, are always kept.
+    ///
+    /// Versions are renumbered so any [`Workpad`] obtained before compaction should no
+    /// longer be used.
+    pub fn compact_history(&mut self, retain: Option<usize>) -> Workpad {
+        let data = self.data.clone();
+        let _writer = data.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        data.compact_history(retain);
+        self.active_version()
+    }
+
+    /// Applies an update as part of a transaction, recording the sheets and cells it changes
+    /// in `affected`
     fn apply_update(
         &mut self,
         update: &WorkpadUpdate,
         tx: &Transaction,
+        affected: &mut Affected,
     ) -> Result<(), UpdateError> {
         let new_err = |kind| {
             Err(UpdateError {
                 kind,
-                update: update.clone(),
+                update: Box::new(update.clone()),
                 workpad_id: self.data.id.clone(),
                 workpad_version: tx.active_version,
             })
         };
 
+        // Reads are made as at the new version so that each part of a Multi update sees the
+        // changes made by the parts before it.  (When the transaction began the new version
+        // was made to see the data of the active version.)
         let Transaction {
             id: _,
-            active_version,
+            active_version: _,
             new_version,
         } = *tx;
 
         match update {
             WorkpadUpdate::Multi(updates) => {
                 for update in updates {
-                    self.apply_update(update, tx)?;
+                    self.apply_update(update, tx, affected)?;
                 }
             }
             WorkpadUpdate::NewWorkpad => panic!("NewWorkpad not allowed for existing Workpad"),
-            WorkpadUpdate::SetVersion { .. } => unreachable!(),
+            // These do not create a new version so cannot be part of one (made by a Multi)
+            WorkpadUpdate::SetVersion { .. }
+            | WorkpadUpdate::SetCheckpoint { .. }
+            | WorkpadUpdate::ClearCheckpoint { .. } => {
+                return new_err(ErrorKind::NotAllowedInMulti);
+            }
             WorkpadUpdate::WorkpadSetProperties {
                 ref new_name,
                 ref new_author,
@@ -248,7 +398,7 @@ impl WorkpadMaster {
                 if new_name.is_empty() {
                     return new_err(ErrorKind::InvalidName(new_name.clone()));
                 }
-                let workpad_data = self.data.read_workpad(active_version);
+                let workpad_data = self.data.read_workpad(new_version);
                 let new_workpad_data = WorkpadData {
                     name: Intern::from(new_name.as_str()),
                     author: Intern::from(new_author.as_str()),
@@ -258,7 +408,7 @@ impl WorkpadMaster {
                     .write_workpad(Arc::new(new_workpad_data), new_version);
             }
             WorkpadUpdate::SetActiveSheet { sheet_id } => {
-                let workpad_data = self.data.read_workpad(active_version);
+                let workpad_data = self.data.read_workpad(new_version);
                 if !workpad_data.sheets.contains(sheet_id) {
                     return new_err(ErrorKind::MissingSheet(*sheet_id));
                 }
@@ -275,9 +425,9 @@ impl WorkpadMaster {
                     return new_err(ErrorKind::InvalidName(name.clone()));
                 }
 
-                let workpad_data = self.data.read_workpad(active_version);
+                let workpad_data = self.data.read_workpad(new_version);
                 for sheet_id in workpad_data.sheets.iter() {
-                    let sheet_data = self.data.read_sheet(*sheet_id, active_version);
+                    let sheet_data = self.data.read_sheet(*sheet_id, new_version);
                     let sheet_name: &str = &sheet_data.name;
                     if sheet_name == name {
                         return new_err(ErrorKind::DuplicateName(name.clone()));
@@ -285,6 +435,7 @@ impl WorkpadMaster {
                 }
 
                 let sheet_id = self.data.create_sheet(new_version, *kind, name);
+                affected.sheet(sheet_id);
                 let mut new_sheets = workpad_data.sheets.clone();
                 new_sheets.push(sheet_id);
                 let new_workpad_data = WorkpadData {
@@ -296,7 +447,7 @@ impl WorkpadMaster {
                     .write_workpad(Arc::new(new_workpad_data), new_version);
             }
             WorkpadUpdate::SheetDelete { sheet_id } => {
-                let workpad_data = self.data.read_workpad(active_version);
+                let workpad_data = self.data.read_workpad(new_version);
                 let new_sheets: Vec<SheetId> = workpad_data
                     .sheets
                     .iter()
@@ -331,6 +482,7 @@ impl WorkpadMaster {
                     ..(*workpad_data).clone()
                 };
                 self.data.delete_sheet(*sheet_id, new_version);
+                affected.sheet(*sheet_id);
                 self.data
                     .write_workpad(Arc::new(new_workpad_data), new_version);
             }
@@ -342,20 +494,21 @@ impl WorkpadMaster {
                     return new_err(ErrorKind::InvalidName(new_name.clone()));
                 }
 
-                let workpad_data = self.data.read_workpad(active_version);
+                let workpad_data = self.data.read_workpad(new_version);
                 for s_id in workpad_data.sheets.iter() {
-                    let sheet_data = self.data.read_sheet(*s_id, active_version);
+                    let sheet_data = self.data.read_sheet(*s_id, new_version);
                     let sheet_name: &str = &sheet_data.name;
                     if s_id != sheet_id && sheet_name == new_name {
                         return new_err(ErrorKind::DuplicateName(new_name.clone()));
                     }
                 }
 
-                let sheet_data = self.data.read_sheet(*sheet_id, active_version);
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
                 let new_sheet_data = SheetData {
                     name: Intern::from(new_name.as_str()),
                     ..(*sheet_data).clone()
                 };
+                affected.sheet(*sheet_id);
                 self.data
                     .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
             }
@@ -365,190 +518,1075 @@ impl WorkpadMaster {
                 column_id,
                 ref value,
             } => {
-                let workpad_data = self.data.read_workpad(active_version);
+                let workpad_data = self.data.read_workpad(new_version);
                 if !workpad_data.sheets.contains(sheet_id) {
                     return new_err(ErrorKind::MissingSheet(*sheet_id));
                 }
 
-                let sheet_data = self.data.read_sheet(*sheet_id, active_version);
-                if !sheet_data.rows.contains(row_id) {
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
                     return new_err(ErrorKind::MissingRow(*row_id));
-                }
-                if !sheet_data.columns.contains(column_id) {
+                };
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
                     return new_err(ErrorKind::MissingColumn(*column_id));
+                };
+
+                // Values breaking a rule that only warns are entered, to be flagged when shown
+                if !sheet_data.validations.is_empty() {
+                    let validator = Validator::new(sheet_data.validations.clone());
+                    let rejected = validator
+                        .broken_rules(row, column, value)
+                        .find(|(rule, _)| rule.reject);
+                    if let Some((_, err)) = rejected {
+                        return new_err(ErrorKind::InvalidValue(err));
+                    }
                 }
 
-                let cell_id =
+                let mut new_sheet_data = (*sheet_data).clone();
+                self.data.change_sheet_cell(
+                    *sheet_id,
+                    &mut new_sheet_data,
+                    (row, column),
+                    new_version,
+                    |cell_data| {
+                        cell_data.value = if value.is_empty() {
+                            Value::Empty
+                        } else {
+                            Value::String(Intern::from(value.as_str()))
+                        };
+                    },
+                );
+                affected.cell(*sheet_id, row, column);
+
+                // Keep track of the bounds of the non-empty cells of the sheet
+                new_sheet_data.used_range = match sheet_data.used_range {
+                    Some(used_range) if !value.is_empty() => {
+                        Some(used_range.including(row, column))
+                    }
+                    None if !value.is_empty() => Some(UsedRange::new(row, column)),
+                    Some(used_range) if used_range.is_on_edge(row, column) => self
+                        .data
+                        .compute_used_range(*sheet_id, &new_sheet_data, new_version),
+                    used_range => used_range,
+                };
+
+                if new_sheet_data.used_range != sheet_data.used_range
+                    || !Arc::ptr_eq(&new_sheet_data.rows, &sheet_data.rows)
+                    || !Arc::ptr_eq(&new_sheet_data.columns, &sheet_data.columns)
+                {
                     self.data
-                        .read_sheet_cell(*sheet_id, *row_id, *column_id, active_version);
-                let base = match cell_id {
-                    Some(id) => (*self.data.read_cell(id, active_version)).clone(),
-                    None => Default::default(),
+                        .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+                }
+            }
+            WorkpadUpdate::SheetSetActiveCell {
+                sheet_id,
+                row_id,
+                column_id,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
+                    return new_err(ErrorKind::MissingRow(*row_id));
                 };
-                let cell_data = CellData {
-                    value: Value::String(Intern::from(value.as_str())),
-                    ..base
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*column_id));
                 };
 
-                // If there is already a CellId that covers the new version we can use it and
-                // just write a new version for the CellData.  Otherwise allocate a new CellId.
-                let cell_id = self
-                    .data
-                    .read_sheet_cell(*sheet_id, *row_id, *column_id, new_version)
-                    .unwrap_or_else(|| {
-                        let new_id = self.data.next_part_id.fetch_add(1, Ordering::SeqCst).into();
-                        self.data.write_sheet_cell(
-                            *sheet_id,
-                            *row_id,
-                            *column_id,
-                            new_id,
-                            new_version,
-                        );
-                        new_id
-                    });
+                let new_sheet_data = SheetData {
+                    active_cell: Some((
+                        sheet_data.rows.id_at(row),
+                        sheet_data.columns.id_at(column),
+                    )),
+                    ..(*sheet_data).clone()
+                };
                 self.data
-                    .write_cell(cell_id, Arc::new(cell_data), new_version);
+                    .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
             }
-            WorkpadUpdate::SheetSetActiveCell {
+            WorkpadUpdate::SheetSetCellFormat {
+                sheet_id,
+                row_id,
+                column_id,
+                ref format,
+            }
+            | WorkpadUpdate::SheetApplyCellFormat {
                 sheet_id,
                 row_id,
                 column_id,
+                ref format,
             } => {
-                let workpad_data = self.data.read_workpad(active_version);
+                let workpad_data = self.data.read_workpad(new_version);
                 if !workpad_data.sheets.contains(sheet_id) {
                     return new_err(ErrorKind::MissingSheet(*sheet_id));
                 }
 
-                let sheet_data = self.data.read_sheet(*sheet_id, active_version);
-                if !sheet_data.rows.contains(row_id) {
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
                     return new_err(ErrorKind::MissingRow(*row_id));
-                }
-                if !sheet_data.columns.contains(column_id) {
+                };
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
                     return new_err(ErrorKind::MissingColumn(*column_id));
+                };
+
+                if let Some(Err(err)) = format.number_format.as_ref().map(|nf| nf.validate()) {
+                    return new_err(ErrorKind::InvalidNumberFormat(err));
+                }
+
+                let replace = matches!(update, WorkpadUpdate::SheetSetCellFormat { .. });
+                let mut new_sheet_data = (*sheet_data).clone();
+                self.data.change_sheet_cell(
+                    *sheet_id,
+                    &mut new_sheet_data,
+                    (row, column),
+                    new_version,
+                    |cell_data| {
+                        cell_data.format = if replace {
+                            format.clone()
+                        } else {
+                            cell_data.format.overlay(format)
+                        };
+                    },
+                );
+                affected.cell(*sheet_id, row, column);
+
+                if !Arc::ptr_eq(&new_sheet_data.rows, &sheet_data.rows)
+                    || !Arc::ptr_eq(&new_sheet_data.columns, &sheet_data.columns)
+                {
+                    self.data
+                        .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+                }
+            }
+            WorkpadUpdate::SetStyle {
+                ref name,
+                ref format,
+            } => {
+                if name.trim().is_empty() {
+                    return new_err(ErrorKind::InvalidName(name.clone()));
+                }
+                if let Some(Err(err)) = format.number_format.as_ref().map(|nf| nf.validate()) {
+                    return new_err(ErrorKind::InvalidNumberFormat(err));
+                }
+
+                let workpad_data = self.data.read_workpad(new_version);
+                let mut styles = (*workpad_data.styles).clone();
+                styles.insert(name.clone(), format.clone());
+                let new_workpad_data = WorkpadData {
+                    styles: Arc::new(styles),
+                    ..(*workpad_data).clone()
+                };
+                // Cells of any sheet may use the style
+                for sheet_id in workpad_data.sheets.iter() {
+                    affected.sheet(*sheet_id);
+                }
+                self.data
+                    .write_workpad(Arc::new(new_workpad_data), new_version);
+            }
+            WorkpadUpdate::RemoveStyle { ref name } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if let Err(kind) = workpad_data.check_style(&Some(name.clone())) {
+                    return new_err(kind);
+                }
+
+                let mut styles = (*workpad_data.styles).clone();
+                styles.remove(name);
+                let new_workpad_data = WorkpadData {
+                    styles: Arc::new(styles),
+                    ..(*workpad_data).clone()
+                };
+                // Cells of any sheet may use the style
+                for sheet_id in workpad_data.sheets.iter() {
+                    affected.sheet(*sheet_id);
+                }
+                self.data
+                    .write_workpad(Arc::new(new_workpad_data), new_version);
+            }
+            WorkpadUpdate::SheetSetStyle {
+                sheet_id,
+                ref style,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+                if let Err(kind) = workpad_data.check_style(style) {
+                    return new_err(kind);
                 }
 
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
                 let new_sheet_data = SheetData {
-                    active_cell: Some((*row_id, *column_id)),
+                    style: style.as_deref().map(Intern::from),
                     ..(*sheet_data).clone()
                 };
+                affected.sheet(*sheet_id);
                 self.data
                     .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
             }
-        }
-        Ok(())
-    }
-}
+            WorkpadUpdate::SheetSetRowStyle {
+                sheet_id,
+                row_id,
+                ref style,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+                if let Err(kind) = workpad_data.check_style(style) {
+                    return new_err(kind);
+                }
 
-impl Default for WorkpadMaster {
-    fn default() -> Self {
-        Self::new_blank()
-    }
-}
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
+                    return new_err(ErrorKind::MissingRow(*row_id));
+                };
 
-/// A change that can be applied to a workpad to create a new version.
-/// See [`WorkpadMaster::update(`)].
-#[derive(Debug, Clone)]
-pub enum WorkpadUpdate {
-    /// Used to apply multiple updates in one version.
-    Multi(Vec<WorkpadUpdate>),
-    /// Used to represent the creation of a workpad.  See [`WorkpadMaster::new`].
-    NewWorkpad,
-    /// Instruction to change the active version of the workpad
-    SetVersion { version: Version },
-    /// Instruction to change the name of the workpad
-    WorkpadSetProperties {
-        new_name: String,
-        new_author: String,
-    },
-    /// Instruction to change the active sheet of the workpad
-    SetActiveSheet { sheet_id: SheetId },
-    /// Instruction to add a sheet to the workpad
-    SheetAdd { kind: SheetKind, name: String },
-    /// Instruction to delete a specific sheet within a workpad.
-    SheetDelete { sheet_id: SheetId },
-    /// Instruction to change the name of a specific sheet within a workpad.
-    SheetSetProperties { sheet_id: SheetId, new_name: String },
-    /// Instruction to change the value of a cell at a row/column reference of a
-    /// specific sheet within a workpad.
-    SheetSetCellValue {
-        sheet_id: SheetId,
-        row_id: RowId,
-        column_id: ColumnId,
-        value: String,
-    },
-    /// Instruction to change the active cell (row/column reference) of a
-    /// specific sheet within a workpad.
-    SheetSetActiveCell {
-        sheet_id: SheetId,
-        row_id: RowId,
-        column_id: ColumnId,
-    },
-}
+                let mut new_sheet_data = (*sheet_data).clone();
+                let row_id = self
+                    .data
+                    .allocate_row(&mut new_sheet_data, row, new_version);
+                let row_data = self.data.read_row(row_id, new_version);
+                let new_row_data = RowData {
+                    style: style.as_deref().map(Intern::from),
+                    ..(*row_data).clone()
+                };
+                self.data
+                    .write_row(row_id, Arc::new(new_row_data), new_version);
+                affected.sheet(*sheet_id);
 
-impl std::fmt::Display for WorkpadUpdate {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let WorkpadUpdate::Multi(updates) = self {
-            let mut join = String::new();
-            for update in updates {
-                write!(f, "{}{}", join, update)?;
-                join = t!("WorkpadUpdate.Join");
+                if !Arc::ptr_eq(&new_sheet_data.rows, &sheet_data.rows) {
+                    self.data
+                        .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+                }
             }
-            Ok(())
-        } else {
-            type WU = WorkpadUpdate;
-            let variant = match self {
-                WU::Multi(_) => unreachable!(),
-                WU::NewWorkpad => "NewWorkpad",
-                WU::SetVersion { .. } => "SetVersion",
-                WU::WorkpadSetProperties { .. } => "WorkpadSetProperties",
-                WU::SetActiveSheet { .. } => "SetActiveSheet",
-                WU::SheetAdd { .. } => "SheetAdd",
-                WU::SheetDelete { .. } => "SheetDelete",
-                WU::SheetSetProperties { .. } => "SheetSetProperties",
-                WU::SheetSetCellValue { .. } => "SheetSetCellValue",
-                WU::SheetSetActiveCell { .. } => "SheetSetActiveCell",
-            };
-            let name = t!(&format!("WorkpadUpdate.{variant}"));
-            write!(f, "{name}")
-        }
-    }
-}
+            WorkpadUpdate::SheetSetColumnStyle {
+                sheet_id,
+                column_id,
+                ref style,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+                if let Err(kind) = workpad_data.check_style(style) {
+                    return new_err(kind);
+                }
 
-// TODO Flesh out error
-#[derive(Debug, Clone)]
-pub struct UpdateError {
-    kind: ErrorKind,
-    update: WorkpadUpdate,
-    workpad_id: String,
-    workpad_version: Version,
-}
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*column_id));
+                };
 
-impl std::fmt::Display for UpdateError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(
-            &t!("UpdateError.Display")
-                .replace("{kind}", &self.kind.to_string())
-                .replace("{update}", &self.update.to_string())
-                .replace("{workpad_id}", &self.workpad_id)
-                .replace("{workpad_versiojn}", &self.workpad_version.to_string()),
-        )
-    }
-}
+                let mut new_sheet_data = (*sheet_data).clone();
+                let column_id = self
+                    .data
+                    .allocate_column(&mut new_sheet_data, column, new_version);
+                let column_data = self.data.read_column(column_id, new_version);
+                let new_column_data = ColumnData {
+                    style: style.as_deref().map(Intern::from),
+                    ..(*column_data).clone()
+                };
+                self.data
+                    .write_column(column_id, Arc::new(new_column_data), new_version);
+                affected.sheet(*sheet_id);
 
-impl Error for UpdateError {}
+                if !Arc::ptr_eq(&new_sheet_data.columns, &sheet_data.columns) {
+                    self.data
+                        .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+                }
+            }
+            WorkpadUpdate::SheetSetCellStyle {
+                sheet_id,
+                row_id,
+                column_id,
+                ref style,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+                if let Err(kind) = workpad_data.check_style(style) {
+                    return new_err(kind);
+                }
 
-#[derive(Debug, Clone)]
-pub enum ErrorKind {
-    InvalidName(String),
-    MissingVersion(Version),
-    MissingSheet(SheetId),
-    MissingRow(RowId),
-    MissingColumn(ColumnId),
-    DuplicateName(String),
-}
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
+                    return new_err(ErrorKind::MissingRow(*row_id));
+                };
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*column_id));
+                };
 
-impl std::fmt::Display for ErrorKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut new_sheet_data = (*sheet_data).clone();
+                self.data.change_sheet_cell(
+                    *sheet_id,
+                    &mut new_sheet_data,
+                    (row, column),
+                    new_version,
+                    |cell_data| cell_data.style = style.as_deref().map(Intern::from),
+                );
+                affected.cell(*sheet_id, row, column);
+
+                if !Arc::ptr_eq(&new_sheet_data.rows, &sheet_data.rows)
+                    || !Arc::ptr_eq(&new_sheet_data.columns, &sheet_data.columns)
+                {
+                    self.data
+                        .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+                }
+            }
+            WorkpadUpdate::SheetSetConditionalFormats {
+                sheet_id,
+                ref conditional_formats,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+                for rule in conditional_formats {
+                    if let Some(Err(err)) =
+                        rule.format.number_format.as_ref().map(|nf| nf.validate())
+                    {
+                        return new_err(ErrorKind::InvalidNumberFormat(err));
+                    }
+                }
+
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let new_sheet_data = SheetData {
+                    conditional_formats: Arc::new(conditional_formats.clone()),
+                    ..(*sheet_data).clone()
+                };
+                affected.sheet(*sheet_id);
+                self.data
+                    .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+            }
+            WorkpadUpdate::SheetSetValidations {
+                sheet_id,
+                ref validations,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+                for rule in validations {
+                    if let Err(err) = rule.validate() {
+                        return new_err(ErrorKind::InvalidValidation(err));
+                    }
+                }
+
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let new_sheet_data = SheetData {
+                    validations: Arc::new(validations.clone()),
+                    ..(*sheet_data).clone()
+                };
+                affected.sheet(*sheet_id);
+                self.data
+                    .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+            }
+            WorkpadUpdate::SheetMergeCells {
+                sheet_id,
+                first_row_id,
+                first_column_id,
+                last_row_id,
+                last_column_id,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(first_row) = sheet_data.rows.index_of(*first_row_id) else {
+                    return new_err(ErrorKind::MissingRow(*first_row_id));
+                };
+                let Some(last_row) = sheet_data.rows.index_of(*last_row_id) else {
+                    return new_err(ErrorKind::MissingRow(*last_row_id));
+                };
+                let Some(first_column) = sheet_data.columns.index_of(*first_column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*first_column_id));
+                };
+                let Some(last_column) = sheet_data.columns.index_of(*last_column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*last_column_id));
+                };
+                let (top, bottom) = (first_row.min(last_row), first_row.max(last_row));
+                let (left, right) = (first_column.min(last_column), first_column.max(last_column));
+
+                let overlaps = sheet_data.merged_ranges().any(|(rows, columns)| {
+                    *rows.start() <= bottom
+                        && top <= *rows.end()
+                        && *columns.start() <= right
+                        && left <= *columns.end()
+                });
+                if (top == bottom && left == right) || overlaps {
+                    return new_err(ErrorKind::InvalidMerge);
+                }
+
+                let mut new_sheet_data = (*sheet_data).clone();
+                let merge = Merge {
+                    first_row: self
+                        .data
+                        .allocate_row(&mut new_sheet_data, top, new_version),
+                    first_column: self
+                        .data
+                        .allocate_column(&mut new_sheet_data, left, new_version),
+                    last_row: self
+                        .data
+                        .allocate_row(&mut new_sheet_data, bottom, new_version),
+                    last_column: self
+                        .data
+                        .allocate_column(&mut new_sheet_data, right, new_version),
+                };
+                let mut merges = (*sheet_data.merges).clone();
+                merges.push(merge);
+                new_sheet_data.merges = Arc::new(merges);
+                affected.sheet(*sheet_id);
+                self.data
+                    .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+            }
+            WorkpadUpdate::SheetUnmergeCells {
+                sheet_id,
+                row_id,
+                column_id,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
+                    return new_err(ErrorKind::MissingRow(*row_id));
+                };
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*column_id));
+                };
+
+                let merged = sheet_data.merges.iter().position(|merge| {
+                    sheet_data
+                        .merged_range(merge)
+                        .map_or(false, |(rows, columns)| {
+                            rows.contains(&row) && columns.contains(&column)
+                        })
+                });
+                let Some(index) = merged else {
+                    return new_err(ErrorKind::NotMerged);
+                };
+
+                let mut merges = (*sheet_data.merges).clone();
+                merges.remove(index);
+                affected.sheet(*sheet_id);
+                let new_sheet_data = SheetData {
+                    merges: Arc::new(merges),
+                    ..(*sheet_data).clone()
+                };
+                self.data
+                    .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+            }
+            WorkpadUpdate::SheetAddCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                ..
+            }
+            | WorkpadUpdate::SheetEditCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                ..
+            }
+            | WorkpadUpdate::SheetDeleteCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                ..
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
+                    return new_err(ErrorKind::MissingRow(*row_id));
+                };
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*column_id));
+                };
+
+                // Ids of allocated rows and columns replace those implied by their positions
+                let (row_id, column_id) =
+                    (sheet_data.rows.id_at(row), sheet_data.columns.id_at(column));
+                let comment_count = self
+                    .data
+                    .read_sheet_cell(*sheet_id, row_id, column_id, new_version)
+                    .map_or(0, |id| self.data.read_cell(id, new_version).comments.len());
+                if let WorkpadUpdate::SheetEditCellComment { index, .. }
+                | WorkpadUpdate::SheetDeleteCellComment { index, .. } = update
+                {
+                    if *index >= comment_count {
+                        return new_err(ErrorKind::MissingComment(*index));
+                    }
+                }
+
+                let mut new_sheet_data = (*sheet_data).clone();
+                self.data.change_sheet_cell(
+                    *sheet_id,
+                    &mut new_sheet_data,
+                    (row, column),
+                    new_version,
+                    |cell_data| {
+                        let comments = Arc::make_mut(&mut cell_data.comments);
+                        match update {
+                            WorkpadUpdate::SheetAddCellComment { comment, .. } => {
+                                comments.push(comment.clone())
+                            }
+                            WorkpadUpdate::SheetEditCellComment { index, text, .. } => {
+                                comments[*index].text = text.clone()
+                            }
+                            // Replies go with the comment they reply to
+                            WorkpadUpdate::SheetDeleteCellComment { index: 0, .. } => {
+                                comments.clear()
+                            }
+                            WorkpadUpdate::SheetDeleteCellComment { index, .. } => {
+                                comments.remove(*index);
+                            }
+                            _ => unreachable!(),
+                        }
+                    },
+                );
+                affected.cell(*sheet_id, row, column);
+
+                if !Arc::ptr_eq(&new_sheet_data.rows, &sheet_data.rows)
+                    || !Arc::ptr_eq(&new_sheet_data.columns, &sheet_data.columns)
+                {
+                    self.data
+                        .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+                }
+            }
+            WorkpadUpdate::SheetSetCellHyperlink {
+                sheet_id,
+                row_id,
+                column_id,
+                hyperlink,
+            } => {
+                let workpad_data = self.data.read_workpad(new_version);
+                if !workpad_data.sheets.contains(sheet_id) {
+                    return new_err(ErrorKind::MissingSheet(*sheet_id));
+                }
+                if let Some(hyperlink) = hyperlink {
+                    if !hyperlink.is_allowed() {
+                        return new_err(ErrorKind::InvalidHyperlink);
+                    }
+                }
+
+                let sheet_data = self.data.read_sheet(*sheet_id, new_version);
+                let Some(row) = sheet_data.rows.index_of(*row_id) else {
+                    return new_err(ErrorKind::MissingRow(*row_id));
+                };
+                let Some(column) = sheet_data.columns.index_of(*column_id) else {
+                    return new_err(ErrorKind::MissingColumn(*column_id));
+                };
+
+                let mut new_sheet_data = (*sheet_data).clone();
+                self.data.change_sheet_cell(
+                    *sheet_id,
+                    &mut new_sheet_data,
+                    (row, column),
+                    new_version,
+                    |cell_data| cell_data.hyperlink = hyperlink.clone(),
+                );
+                affected.cell(*sheet_id, row, column);
+
+                if !Arc::ptr_eq(&new_sheet_data.rows, &sheet_data.rows)
+                    || !Arc::ptr_eq(&new_sheet_data.columns, &sheet_data.columns)
+                {
+                    self.data
+                        .write_sheet(*sheet_id, Arc::new(new_sheet_data), new_version);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for WorkpadMaster {
+    fn default() -> Self {
+        Self::new_blank()
+    }
+}
+
+/// A change that can be applied to a workpad to create a new version.
+/// See [`WorkpadMaster::update(`)].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WorkpadUpdate {
+    /// Used to apply multiple updates in one version.
+    Multi(Vec<WorkpadUpdate>),
+    /// Used to represent the creation of a workpad.  See [`WorkpadMaster::new`].
+    NewWorkpad,
+    /// Instruction to change the active version of the workpad
+    SetVersion { version: Version },
+    /// Instruction to mark a version of the workpad with a [`Checkpoint`].  This does not
+    /// create a new version.
+    SetCheckpoint {
+        version: Version,
+        label: String,
+        note: String,
+    },
+    /// Instruction to remove the [`Checkpoint`] from a version of the workpad.  This does
+    /// not create a new version.
+    ClearCheckpoint { version: Version },
+    /// Instruction to change the name of the workpad
+    WorkpadSetProperties {
+        new_name: String,
+        new_author: String,
+    },
+    /// Instruction to change the active sheet of the workpad
+    SetActiveSheet { sheet_id: SheetId },
+    /// Instruction to add a sheet to the workpad
+    SheetAdd { kind: SheetKind, name: String },
+    /// Instruction to delete a specific sheet within a workpad.
+    SheetDelete { sheet_id: SheetId },
+    /// Instruction to change the name of a specific sheet within a workpad.
+    SheetSetProperties { sheet_id: SheetId, new_name: String },
+    /// Instruction to change the value of a cell at a row/column reference of a
+    /// specific sheet within a workpad.
+    SheetSetCellValue {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        value: String,
+    },
+    /// Instruction to change the active cell (row/column reference) of a
+    /// specific sheet within a workpad.
+    SheetSetActiveCell {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+    },
+    /// Instruction to replace the [`Format`] of a cell at a row/column reference of a
+    /// specific sheet within a workpad.
+    SheetSetCellFormat {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        format: Format,
+    },
+    /// Instruction to change the properties of the [`Format`] of a cell at a row/column
+    /// reference of a specific sheet within a workpad that are set in `format`, keeping the
+    /// others.  See [`Format::overlay`].
+    SheetApplyCellFormat {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        format: Format,
+    },
+    /// Instruction to define, or change the [`Format`] of, a named style of the workpad.
+    /// Cells using the style are displayed with the new format.
+    SetStyle { name: String, format: Format },
+    /// Instruction to remove a named style from the workpad.  Cells using the style are
+    /// displayed as if they had none.
+    RemoveStyle { name: String },
+    /// Instruction to change (or with `None` clear) the named style used by default for the
+    /// cells of a specific sheet within a workpad.
+    SheetSetStyle {
+        sheet_id: SheetId,
+        style: Option<String>,
+    },
+    /// Instruction to change (or with `None` clear) the named style used by default for the
+    /// cells of a row of a specific sheet within a workpad.
+    SheetSetRowStyle {
+        sheet_id: SheetId,
+        row_id: RowId,
+        style: Option<String>,
+    },
+    /// Instruction to change (or with `None` clear) the named style used by default for the
+    /// cells of a column of a specific sheet within a workpad.
+    SheetSetColumnStyle {
+        sheet_id: SheetId,
+        column_id: ColumnId,
+        style: Option<String>,
+    },
+    /// Instruction to change (or with `None` clear) the named style of a cell at a
+    /// row/column reference of a specific sheet within a workpad.
+    SheetSetCellStyle {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        style: Option<String>,
+    },
+    /// Instruction to replace the [`ConditionalFormat`] rules of a specific sheet within a
+    /// workpad.  Rules are held in order of precedence.
+    SheetSetConditionalFormats {
+        sheet_id: SheetId,
+        conditional_formats: Vec<ConditionalFormat>,
+    },
+    /// Instruction to replace the [`Validation`] rules of a specific sheet within a
+    /// workpad.  Values already entered are kept, even if they break the new rules.
+    SheetSetValidations {
+        sheet_id: SheetId,
+        validations: Vec<Validation>,
+    },
+    /// Instruction to merge the block of cells between two row/column references of a
+    /// specific sheet within a workpad into one cell.  The block must have more than one cell
+    /// and not overlap cells already merged.  The merged cell shows the value and formatting
+    /// of the top-left cell of the block; the others are kept but hidden until unmerged.
+    SheetMergeCells {
+        sheet_id: SheetId,
+        first_row_id: RowId,
+        first_column_id: ColumnId,
+        last_row_id: RowId,
+        last_column_id: ColumnId,
+    },
+    /// Instruction to split the merged cell containing a row/column reference of a specific
+    /// sheet within a workpad back into its cells.  Fails if the cell is not merged.
+    SheetUnmergeCells {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+    },
+    /// Instruction to add a [`Comment`] to a cell at a row/column reference of a specific
+    /// sheet within a workpad.  If the cell already has comments it is added as a reply.
+    SheetAddCellComment {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        comment: Comment,
+    },
+    /// Instruction to change the text of the comment at `index` in the thread of comments of
+    /// a cell at a row/column reference of a specific sheet within a workpad.
+    SheetEditCellComment {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        index: usize,
+        text: String,
+    },
+    /// Instruction to delete the comment at `index` in the thread of comments of a cell at a
+    /// row/column reference of a specific sheet within a workpad.  Deleting the first comment
+    /// deletes the whole thread, replies included.
+    SheetDeleteCellComment {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        index: usize,
+    },
+    /// Instruction to set, or with `None` remove, the [`Hyperlink`] of a cell at a row/column
+    /// reference of a specific sheet within a workpad.
+    SheetSetCellHyperlink {
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        hyperlink: Option<Hyperlink>,
+    },
+}
+
+impl std::fmt::Display for WorkpadUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let WorkpadUpdate::Multi(updates) = self {
+            let mut join = String::new();
+            for update in updates {
+                write!(f, "{}{}", join, update)?;
+                join = t!("WorkpadUpdate.Join");
+            }
+            Ok(())
+        } else {
+            type WU = WorkpadUpdate;
+            let variant = match self {
+                WU::Multi(_) => unreachable!(),
+                WU::NewWorkpad => "NewWorkpad",
+                WU::SetVersion { .. } => "SetVersion",
+                WU::SetCheckpoint { .. } => "SetCheckpoint",
+                WU::ClearCheckpoint { .. } => "ClearCheckpoint",
+                WU::WorkpadSetProperties { .. } => "WorkpadSetProperties",
+                WU::SetActiveSheet { .. } => "SetActiveSheet",
+                WU::SheetAdd { .. } => "SheetAdd",
+                WU::SheetDelete { .. } => "SheetDelete",
+                WU::SheetSetProperties { .. } => "SheetSetProperties",
+                WU::SheetSetCellValue { .. } => "SheetSetCellValue",
+                WU::SheetSetActiveCell { .. } => "SheetSetActiveCell",
+                WU::SheetSetCellFormat { .. } => "SheetSetCellFormat",
+                WU::SheetApplyCellFormat { .. } => "SheetApplyCellFormat",
+                WU::SetStyle { .. } => "SetStyle",
+                WU::RemoveStyle { .. } => "RemoveStyle",
+                WU::SheetSetStyle { .. } => "SheetSetStyle",
+                WU::SheetSetRowStyle { .. } => "SheetSetRowStyle",
+                WU::SheetSetColumnStyle { .. } => "SheetSetColumnStyle",
+                WU::SheetSetCellStyle { .. } => "SheetSetCellStyle",
+                WU::SheetSetConditionalFormats { .. } => "SheetSetConditionalFormats",
+                WU::SheetSetValidations { .. } => "SheetSetValidations",
+                WU::SheetMergeCells { .. } => "SheetMergeCells",
+                WU::SheetUnmergeCells { .. } => "SheetUnmergeCells",
+                WU::SheetAddCellComment { .. } => "SheetAddCellComment",
+                WU::SheetEditCellComment { .. } => "SheetEditCellComment",
+                WU::SheetDeleteCellComment { .. } => "SheetDeleteCellComment",
+                WU::SheetSetCellHyperlink { .. } => "SheetSetCellHyperlink",
+            };
+            let name = t!(&format!("WorkpadUpdate.{variant}"));
+            write!(f, "{name}")
+        }
+    }
+}
+
+impl WorkpadUpdate {
+    /// Returns this update with the rows and columns it refers to identified by their index
+    /// within their sheet of `pad` rather than by the ids allocated within `pad`.  The result
+    /// can be applied to any copy of the workpad with the same sheets, such as those of
+    /// collaborators that allocate ids independently.  References that `pad` cannot resolve
+    /// are left as they are.
+    pub fn portable(&self, pad: &Workpad) -> WorkpadUpdate {
+        let implied_row = |sheet_id: SheetId, row_id: RowId| {
+            let index = pad
+                .sheet_by_id(sheet_id)
+                .and_then(|sheet| sheet.data.rows.index_of(row_id));
+            match index {
+                Some(index) => RowId::from(IMPLIED_ID | index as IdBase),
+                None => row_id,
+            }
+        };
+        let implied_column = |sheet_id: SheetId, column_id: ColumnId| {
+            let index = pad
+                .sheet_by_id(sheet_id)
+                .and_then(|sheet| sheet.data.columns.index_of(column_id));
+            match index {
+                Some(index) => ColumnId::from(IMPLIED_ID | index as IdBase),
+                None => column_id,
+            }
+        };
+        let implied_ids = |sheet_id: SheetId, row_id: RowId, column_id: ColumnId| {
+            (
+                implied_row(sheet_id, row_id),
+                implied_column(sheet_id, column_id),
+            )
+        };
+
+        match self {
+            WorkpadUpdate::Multi(updates) => {
+                WorkpadUpdate::Multi(updates.iter().map(|u| u.portable(pad)).collect())
+            }
+            WorkpadUpdate::SheetSetCellValue {
+                sheet_id,
+                row_id,
+                column_id,
+                value,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetSetCellValue {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    value: value.clone(),
+                }
+            }
+            WorkpadUpdate::SheetSetActiveCell {
+                sheet_id,
+                row_id,
+                column_id,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetSetActiveCell {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                }
+            }
+            WorkpadUpdate::SheetSetCellFormat {
+                sheet_id,
+                row_id,
+                column_id,
+                format,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetSetCellFormat {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    format: format.clone(),
+                }
+            }
+            WorkpadUpdate::SheetApplyCellFormat {
+                sheet_id,
+                row_id,
+                column_id,
+                format,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetApplyCellFormat {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    format: format.clone(),
+                }
+            }
+            WorkpadUpdate::SheetSetRowStyle {
+                sheet_id,
+                row_id,
+                style,
+            } => WorkpadUpdate::SheetSetRowStyle {
+                sheet_id: *sheet_id,
+                row_id: implied_row(*sheet_id, *row_id),
+                style: style.clone(),
+            },
+            WorkpadUpdate::SheetSetColumnStyle {
+                sheet_id,
+                column_id,
+                style,
+            } => WorkpadUpdate::SheetSetColumnStyle {
+                sheet_id: *sheet_id,
+                column_id: implied_column(*sheet_id, *column_id),
+                style: style.clone(),
+            },
+            WorkpadUpdate::SheetSetCellStyle {
+                sheet_id,
+                row_id,
+                column_id,
+                style,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetSetCellStyle {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    style: style.clone(),
+                }
+            }
+            WorkpadUpdate::SheetMergeCells {
+                sheet_id,
+                first_row_id,
+                first_column_id,
+                last_row_id,
+                last_column_id,
+            } => {
+                let (first_row_id, first_column_id) =
+                    implied_ids(*sheet_id, *first_row_id, *first_column_id);
+                let (last_row_id, last_column_id) =
+                    implied_ids(*sheet_id, *last_row_id, *last_column_id);
+                WorkpadUpdate::SheetMergeCells {
+                    sheet_id: *sheet_id,
+                    first_row_id,
+                    first_column_id,
+                    last_row_id,
+                    last_column_id,
+                }
+            }
+            WorkpadUpdate::SheetUnmergeCells {
+                sheet_id,
+                row_id,
+                column_id,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetUnmergeCells {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                }
+            }
+            WorkpadUpdate::SheetAddCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                comment,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetAddCellComment {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    comment: comment.clone(),
+                }
+            }
+            WorkpadUpdate::SheetEditCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                index,
+                text,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetEditCellComment {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    index: *index,
+                    text: text.clone(),
+                }
+            }
+            WorkpadUpdate::SheetDeleteCellComment {
+                sheet_id,
+                row_id,
+                column_id,
+                index,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetDeleteCellComment {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    index: *index,
+                }
+            }
+            WorkpadUpdate::SheetSetCellHyperlink {
+                sheet_id,
+                row_id,
+                column_id,
+                hyperlink,
+            } => {
+                let (row_id, column_id) = implied_ids(*sheet_id, *row_id, *column_id);
+                WorkpadUpdate::SheetSetCellHyperlink {
+                    sheet_id: *sheet_id,
+                    row_id,
+                    column_id,
+                    hyperlink: hyperlink.clone(),
+                }
+            }
+            update => update.clone(),
+        }
+    }
+}
+
+// TODO Flesh out error
+#[derive(Debug, Clone)]
+pub struct UpdateError {
+    kind: ErrorKind,
+    update: Box<WorkpadUpdate>,
+    workpad_id: String,
+    workpad_version: Version,
+}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            &t!("UpdateError.Display")
+                .replace("{kind}", &self.kind.to_string())
+                .replace("{update}", &self.update.to_string())
+                .replace("{workpad_id}", &self.workpad_id)
+                .replace("{workpad_versiojn}", &self.workpad_version.to_string()),
+        )
+    }
+}
+
+impl Error for UpdateError {}
+
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+    InvalidName(String),
+    MissingVersion(Version),
+    MissingSheet(SheetId),
+    MissingRow(RowId),
+    MissingColumn(ColumnId),
+    DuplicateName(String),
+    InvalidNumberFormat(NumberFormatError),
+    MissingStyle(String),
+    InvalidMerge,
+    NotMerged,
+    MissingComment(usize),
+    InvalidHyperlink,
+    InvalidValue(ValidationError),
+    InvalidValidation(ValidationError),
+    NotAllowedInMulti,
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::InvalidName(name) => {
                 f.write_str(&t!("UpdateError.InvalidName").replace("{name}", name))
@@ -568,25 +1606,103 @@ impl std::fmt::Display for ErrorKind {
             Self::DuplicateName(name) => {
                 f.write_str(&t!("UpdateError.DuplicateName").replace("{name}", name))
             }
+            Self::MissingStyle(name) => {
+                f.write_str(&t!("UpdateError.MissingStyle").replace("{name}", name))
+            }
+            Self::InvalidNumberFormat(err) => f.write_str(
+                &t!("UpdateError.InvalidNumberFormat").replace("{error}", &err.to_string()),
+            ),
+            Self::InvalidMerge => f.write_str(&t!("UpdateError.InvalidMerge")),
+            Self::NotMerged => f.write_str(&t!("UpdateError.NotMerged")),
+            Self::MissingComment(index) => f.write_str(
+                &t!("UpdateError.MissingComment").replace("{index}", &(index + 1).to_string()),
+            ),
+            Self::InvalidHyperlink => f.write_str(&t!("UpdateError.InvalidHyperlink")),
+            Self::InvalidValue(err) => {
+                f.write_str(&t!("UpdateError.InvalidValue").replace("{error}", &err.to_string()))
+            }
+            Self::InvalidValidation(err) => err.fmt(f),
+            Self::NotAllowedInMulti => f.write_str(&t!("UpdateError.NotAllowedInMulti")),
         }
     }
 }
 
+/// The name of the user making changes, recorded as the author of new workpads and of each
+/// version in their history
+static AUTHOR: Lazy<Intern<str>> = Lazy::new(|| Intern::from(whoami::realname().as_ref()));
+
 /// Type to record the event history of a workpad
-#[allow(dead_code)] // TODO Persistence
 #[derive(Debug)]
 struct HistoryEntry {
     prior_version: Option<Version>,
     update: WorkpadUpdate,
+    checkpoint: Option<Checkpoint>,
+    timestamp: SystemTime,
+    author: Intern<str>,
+    // What the update changed, to report when the version is stepped to or from
+    affected: Affected,
+}
+
+/// Returns what was affected by the updates that created the versions between two versions:
+/// those undone going back from `from` to the version both are based on, latest first,
+/// then those redone going forward to `to`
+fn affected_between(history: &[HistoryEntry], from: Version, to: Version) -> Affected {
+    let path = |mut version: Version| {
+        let mut path = vec![version];
+        while let Some(prior) = history[version as usize].prior_version {
+            path.push(prior);
+            version = prior;
+        }
+        path
+    };
+    let (from_path, to_path) = (path(from), path(to));
+    let base = from_path.iter().find(|version| to_path.contains(version));
+    let undone = from_path
+        .iter()
+        .take_while(|version| Some(*version) != base);
+    let mut redone: Vec<_> = to_path
+        .iter()
+        .take_while(|version| Some(*version) != base)
+        .collect();
+    redone.reverse();
+
+    let mut affected = Affected::default();
+    for version in undone.chain(redone) {
+        affected.extend(&history[*version as usize].affected);
+    }
+    affected
+}
+
+/// Returns the most recent version that descends from a version, which is where stepping
+/// forwards from the version leads, or the version itself if no version descends from it
+fn latest_descendant(history: &[HistoryEntry], version: Version) -> Version {
+    // Prior versions always preceed the versions based on them so a single pass finds
+    // the most recent version that descends from this version
+    let mut descends = vec![false; history.len()];
+    descends[version as usize] = true;
+    let mut latest = version;
+    for (ver, entry) in history.iter().enumerate().skip(version as usize + 1) {
+        if entry
+            .prior_version
+            .map_or(false, |prior| descends[prior as usize])
+        {
+            descends[ver] = true;
+            latest = ver as Version;
+        }
+    }
+    latest
 }
 
 /// Data that backs a [`WorkpadMaster`]
 struct WorkpadMasterData {
     #[allow(dead_code)] // TODO Persistence
     id: String,
+    // Held for the whole of an update so that updates are applied one at a time
+    writer: Mutex<()>,
     transaction: RwLock<Option<Transaction>>,
     history: RwLock<Vec<HistoryEntry>>,
     active_version: RwLock<Version>,
+    observers: Observers,
     next_part_id: IdBaseAtomic,
     workpad_idx: VersionIndex<(), Arc<WorkpadData>>,
     sheets_idx: VersionIndex<SheetId, Arc<SheetData>>,
@@ -605,10 +1721,16 @@ struct Transaction {
 
 const NO_VER: &str = "Version not found";
 impl WorkpadMasterData {
-    /// Start a transaction.  Transactions cannot be concurrent so no existing transaction should exist.
+    /// Start a transaction.  Transactions are not concurrent as updates are serialized (see
+    /// [`WorkpadMaster::update`]) so any existing transaction belongs to an update that
+    /// panicked and is rolled back.
     fn tx_begin(&self) -> Transaction {
+        let stale_tx = *self.transaction.read().unwrap();
+        if let Some(stale_tx) = stale_tx {
+            self.tx_rollback(&stale_tx);
+        }
+
         let mut tx = self.transaction.write().unwrap();
-        assert!(tx.is_none(), "Concurrent transactions not supported");
 
         let new_tx = Transaction {
             id: self.next_part_id.fetch_add(1, Ordering::SeqCst),
@@ -624,11 +1746,29 @@ impl WorkpadMasterData {
         self.cells_idx.tx_begin();
         self.sheets_cells_idx.tx_begin();
 
+        // When the active version is not the latest the new version starts a new branch of
+        // the history and must not see the data of the (now dead) versions after the active one
+        if new_tx.new_version > 0 && new_tx.active_version != new_tx.new_version - 1 {
+            let (base, new) = (new_tx.active_version, new_tx.new_version);
+            self.workpad_idx.branch(base, new);
+            self.sheets_idx.branch(base, new);
+            self.columns_idx.branch(base, new);
+            self.rows_idx.branch(base, new);
+            self.cells_idx.branch(base, new);
+            self.sheets_cells_idx.branch(base, new);
+        }
+
         new_tx
     }
 
     /// Commit the current transaction.
-    fn tx_commit(&self, tx: &Transaction, update: WorkpadUpdate) {
+    fn tx_commit(
+        &self,
+        tx: &Transaction,
+        update: WorkpadUpdate,
+        author: Intern<str>,
+        affected: Affected,
+    ) {
         let mut self_tx = self.transaction.write().unwrap();
         match self_tx.as_ref() {
             Some(inner) => assert!(inner == tx, "Transaction is not in progress"),
@@ -652,6 +1792,10 @@ impl WorkpadMasterData {
         history.push(HistoryEntry {
             prior_version,
             update,
+            checkpoint: None,
+            timestamp: SystemTime::now(),
+            author,
+            affected,
         });
         self.set_version(tx.new_version);
         self_tx.take();
@@ -676,6 +1820,103 @@ impl WorkpadMasterData {
         self_tx.take();
     }
 
+    /// Removes the versions that cannot be reached from the active version, and those older
+    /// than the retained versions, renumbering the versions that remain.
+    fn compact_history(&self, retain: Option<usize>) {
+        // Holding the transaction lock prevents transactions starting during compaction
+        let tx = self.transaction.read().unwrap();
+        assert!(tx.is_none(), "Cannot compact history during a transaction");
+        let mut history = self.history.write().unwrap();
+        let mut active_version = self.active_version.write().unwrap();
+
+        let ancestors = |mut version: Version| {
+            let mut versions = vec![version];
+            while let Some(prior_version) = history[version as usize].prior_version {
+                versions.push(prior_version);
+                version = prior_version;
+            }
+            versions
+        };
+
+        // The versions leading to the latest version, those reachable by stepping backwards or
+        // forwards from the active version (all of which lead to its latest descendant), and
+        // those leading to checkpoints which are always kept
+        let latest = ancestors((history.len() - 1) as Version);
+        let forward = ancestors(latest_descendant(&history, *active_version));
+        let anchors: Vec<Vec<Version>> = std::iter::once(*active_version)
+            .chain(
+                history
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| entry.checkpoint.is_some())
+                    .map(|(version, _)| version as Version),
+            )
+            .map(ancestors)
+            .collect();
+        let mut kept: Vec<Version> = latest
+            .iter()
+            .chain(forward.iter())
+            .chain(anchors.iter().flatten())
+            .copied()
+            .collect();
+        kept.sort_unstable();
+        kept.dedup();
+
+        // The baseline must be shared by the latest version and all the anchors to keep the
+        // history a single tree
+        if let Some(retain) = retain {
+            let retain = retain.max(1);
+            if latest.len() > retain {
+                let baseline = anchors
+                    .iter()
+                    .map(|anchor| *latest.iter().find(|v| anchor.contains(v)).unwrap())
+                    .fold(latest[retain - 1], Version::min);
+                kept.retain(|v| *v >= baseline);
+            }
+        }
+
+        if kept.len() == history.len() {
+            return;
+        }
+
+        // Each entry covers a range of versions and is kept, covering the renumbered versions
+        // kept in that range, only if any of those versions are kept
+        let renumber = |version: Version| kept.binary_search(&version).ok().map(|v| v as Version);
+        let remap = |from: Version, to: Version| {
+            let first = kept.partition_point(|v| *v < from);
+            if first == kept.len() || kept[first] > to {
+                None
+            } else if to == Version::MAX {
+                Some((first as Version, to))
+            } else {
+                let last = kept.partition_point(|v| *v <= to) - 1;
+                Some((first as Version, last as Version))
+            }
+        };
+        self.workpad_idx.compact(remap);
+        self.sheets_idx.compact(remap);
+        self.columns_idx.compact(remap);
+        self.rows_idx.compact(remap);
+        self.cells_idx.compact(remap);
+        self.sheets_cells_idx.compact(remap);
+
+        let mut entries: Vec<Option<HistoryEntry>> = std::mem::take(&mut *history)
+            .into_iter()
+            .map(Some)
+            .collect();
+        *history = kept
+            .iter()
+            .map(|version| {
+                let entry = entries[*version as usize].take().unwrap();
+                HistoryEntry {
+                    prior_version: entry.prior_version.and_then(renumber),
+                    ..entry
+                }
+            })
+            .collect();
+        *active_version = renumber(*active_version).unwrap();
+    }
+
     fn set_version(&self, new_version: Version) {
         *self.active_version.write().unwrap() = new_version;
     }
@@ -714,31 +1955,10 @@ impl WorkpadMasterData {
     fn create_sheet(&self, version: Version, kind: SheetKind, name: &str) -> SheetId {
         let sheet_id = self.next_part_id.fetch_add(1, Ordering::SeqCst).into();
 
-        let column_data = Arc::new(ColumnData {
-            name: Name::Auto,
-            width: 100.0,
-        });
-        let columns: Vec<ColumnId> = (0..99)
-            .map(|_| {
-                let column_id = self.next_part_id.fetch_add(1, Ordering::SeqCst).into();
-                self.write_column(column_id, column_data.clone(), version);
-                column_id
-            })
-            .collect();
-
-        let row_data = Arc::new(RowData {
-            name: Name::Auto,
-            height: 20.0,
-        });
-        let rows: Vec<RowId> = (0..999)
-            .map(|_| {
-                let row_id = self.next_part_id.fetch_add(1, Ordering::SeqCst).into();
-                self.write_row(row_id, row_data.clone(), version);
-                row_id
-            })
-            .collect();
-
-        let active_cell = Some((rows[0], columns[0]));
+        // Rows and columns are only allocated ids when first written to
+        let columns = Arc::new(Axis::new(MAX_COLUMNS));
+        let rows = Arc::new(Axis::new(MAX_ROWS));
+        let active_cell = Some((rows.id_at(0), columns.id_at(0)));
 
         let data = SheetData {
             kind,
@@ -747,12 +1967,60 @@ impl WorkpadMasterData {
             row_header_width: 60.0,
             columns,
             rows,
+            default_column: Arc::new(ColumnData {
+                name: Name::Auto,
+                width: 100.0,
+                style: None,
+            }),
+            default_row: Arc::new(RowData {
+                name: Name::Auto,
+                height: 20.0,
+                style: None,
+            }),
             active_cell,
+            used_range: None,
+            style: None,
+            conditional_formats: Default::default(),
+            validations: Default::default(),
+            merges: Default::default(),
         };
         self.write_sheet(sheet_id, Arc::new(data), version);
         sheet_id
     }
 
+    /// Returns the id of the row at an index of a sheet, allocating a new id as at the given
+    /// version if the row has only an implied id
+    fn allocate_row(&self, sheet_data: &mut SheetData, index: usize, version: Version) -> RowId {
+        let id = sheet_data.rows.id_at(index);
+        if !is_implied_id(id.base()) {
+            return id;
+        }
+
+        let id = self.next_part_id.fetch_add(1, Ordering::SeqCst).into();
+        self.write_row(id, sheet_data.default_row.clone(), version);
+        Arc::make_mut(&mut sheet_data.rows).allocate(index, id);
+        id
+    }
+
+    /// Returns the id of the column at an index of a sheet, allocating a new id as at the given
+    /// version if the column has only an implied id
+    fn allocate_column(
+        &self,
+        sheet_data: &mut SheetData,
+        index: usize,
+        version: Version,
+    ) -> ColumnId {
+        let id = sheet_data.columns.id_at(index);
+        if !is_implied_id(id.base()) {
+            return id;
+        }
+
+        let id = self.next_part_id.fetch_add(1, Ordering::SeqCst).into();
+        self.write_column(id, sheet_data.default_column.clone(), version);
+        Arc::make_mut(&mut sheet_data.columns).allocate(index, id);
+        id
+    }
+
     /// Read column data for a specified version
     fn read_column(&self, id: ColumnId, version: Version) -> Arc<ColumnData> {
         self.columns_idx.read(id, version).expect(NO_VER)
@@ -783,6 +2051,37 @@ impl WorkpadMasterData {
         self.cells_idx.write(id, data, version);
     }
 
+    /// Changes the data of the cell at a (row, column) index of a sheet as at the given
+    /// version.  Cells are always held against allocated row and column ids so these are
+    /// allocated in `sheet_data` as needed, as is an id for the cell if it has none.
+    fn change_sheet_cell(
+        &self,
+        sheet_id: SheetId,
+        sheet_data: &mut SheetData,
+        (row, column): (usize, usize),
+        version: Version,
+        change: impl FnOnce(&mut CellData),
+    ) {
+        let row_id = self.allocate_row(sheet_data, row, version);
+        let column_id = self.allocate_column(sheet_data, column, version);
+
+        let cell_id = self.read_sheet_cell(sheet_id, row_id, column_id, version);
+        let mut cell_data = match cell_id {
+            Some(id) => (*self.read_cell(id, version)).clone(),
+            None => Default::default(),
+        };
+        change(&mut cell_data);
+
+        // If there is already a CellId that covers the version we can use it and just write a
+        // new version for the CellData.  Otherwise allocate a new CellId.
+        let cell_id = cell_id.unwrap_or_else(|| {
+            let new_id = self.next_part_id.fetch_add(1, Ordering::SeqCst).into();
+            self.write_sheet_cell(sheet_id, row_id, column_id, new_id, version);
+            new_id
+        });
+        self.write_cell(cell_id, Arc::new(cell_data), version);
+    }
+
     /// Read cell data for a specified version
     fn read_sheet_cell(
         &self,
@@ -795,6 +2094,81 @@ impl WorkpadMasterData {
             .read((sheet_id, row_id, column_id), version)
     }
 
+    /// Read the ids of all the cells of a sheet that exist in a specified version
+    fn read_sheet_cells(
+        &self,
+        sheet_id: SheetId,
+        version: Version,
+    ) -> Vec<((SheetId, RowId, ColumnId), CellId)> {
+        self.sheets_cells_idx.read_all(
+            (sheet_id, IdBase::MIN.into(), IdBase::MIN.into()),
+            (sheet_id, IdBase::MAX.into(), IdBase::MAX.into()),
+            version,
+        )
+    }
+
+    /// Compute the bounds of the non-empty cells of a sheet from scratch
+    fn compute_used_range(
+        &self,
+        sheet_id: SheetId,
+        sheet_data: &SheetData,
+        version: Version,
+    ) -> Option<UsedRange> {
+        self.read_sheet_cells(sheet_id, version)
+            .into_iter()
+            .filter(|(_, id)| !self.read_cell(*id, version).value.is_empty())
+            .map(|((_, row_id, column_id), _)| {
+                (
+                    sheet_data.rows.index_of(row_id).unwrap(),
+                    sheet_data.columns.index_of(column_id).unwrap(),
+                )
+            })
+            .fold(None, |used_range: Option<UsedRange>, (row, column)| {
+                Some(match used_range {
+                    Some(used_range) => used_range.including(row, column),
+                    None => UsedRange::new(row, column),
+                })
+            })
+    }
+
+    /// Read the value of the cell at a row/column reference of a sheet for a specified version
+    fn read_sheet_cell_value(
+        &self,
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+        version: Version,
+    ) -> String {
+        self.read_sheet_cell(sheet_id, row_id, column_id, version)
+            .map(|id| self.read_cell(id, version).value.as_str().to_owned())
+            .unwrap_or_default()
+    }
+
+    /// Returns the versions at which the value of the cell at a row/column reference of a
+    /// sheet may have changed, on any branch of the history.  These are the versions at which
+    /// an entry for the cell, or for which cell is at the reference, starts or ends.
+    fn sheet_cell_versions(
+        &self,
+        sheet_id: SheetId,
+        row_id: RowId,
+        column_id: ColumnId,
+    ) -> BTreeSet<Version> {
+        let mut versions = BTreeSet::new();
+        let mut add = |from: Version, to: Version| {
+            versions.insert(from);
+            if to != Version::MAX {
+                versions.insert(to + 1);
+            }
+        };
+        for (from, to, cell_id) in self.sheets_cells_idx.entries((sheet_id, row_id, column_id)) {
+            add(from, to);
+            for (from, to, _) in self.cells_idx.entries(cell_id) {
+                add(from, to);
+            }
+        }
+        versions
+    }
+
     /// Write cell data for a specified version
     fn write_sheet_cell(
         &self,
@@ -826,6 +2200,20 @@ struct WorkpadData {
     author: Intern<str>,
     sheets: Vec<SheetId>,
     active_sheet: Option<SheetId>,
+    // The named styles, shared by versions until one changes
+    styles: Arc<BTreeMap<String, Format>>,
+}
+
+impl WorkpadData {
+    /// Returns an error if a style is named that is not one of the named styles
+    fn check_style(&self, style: &Option<String>) -> Result<(), ErrorKind> {
+        match style {
+            Some(name) if !self.styles.contains_key(name) => {
+                Err(ErrorKind::MissingStyle(name.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 /// A version of a workpad.  See [`WorkpadMaster::active_version()`].
@@ -846,27 +2234,25 @@ impl Workpad {
         self.master.clone()
     }
 
-    /// Returns the id of this version and a description of the update that created it.
-    pub fn version(&self) -> (Version, String) {
+    /// Returns the id of this version, a description of the update that created it and
+    /// when and by whom that update was made.
+    pub fn version(&self) -> VersionInfo {
         let history = self.master.data.history.read().unwrap();
-        (
-            self.version,
-            history[self.version as usize].update.to_string(),
-        )
+        VersionInfo::new(self.version, &history[self.version as usize])
     }
 
     /// Returns the version information (see [`Workpad::version`]) for the versions which
     /// preceed this version.  Versions are returned from the immediate predecessor of
     /// this version backwards.
     #[allow(dead_code)]
-    pub fn backward_versions(&self) -> impl Iterator<Item = (Version, String)> {
+    pub fn backward_versions(&self) -> impl Iterator<Item = VersionInfo> {
         let history = self.master.data.history.read().unwrap();
         let mut entry = &history[self.version as usize];
 
         let mut versions = vec![];
         while let Some(prior_version) = entry.prior_version {
             entry = &history[prior_version as usize];
-            versions.push((prior_version, entry.update.to_string()))
+            versions.push(VersionInfo::new(prior_version, entry))
         }
 
         versions.into_iter()
@@ -874,17 +2260,17 @@ impl Workpad {
 
     /// Returns the version information (see [`Workpad::version`]) for the versions which
     /// succeed this version.  Versions are returned from the the immediate successor of
-    /// this version forwards.
+    /// this version forwards.  Where the history branches the most recent branch is followed
+    /// (see [`Workpad::version_tree`] for all the branches).
     #[allow(dead_code)]
-    pub fn forward_versions(&self) -> impl Iterator<Item = (Version, String)> {
+    pub fn forward_versions(&self) -> impl Iterator<Item = VersionInfo> {
         let history = self.master.data.history.read().unwrap();
-        // history cannot be empty otherwise this version could not exist
-        let mut ver = (history.len() - 1) as Version;
 
+        let mut ver = latest_descendant(&history, self.version);
         let mut versions = vec![];
         while ver != self.version {
             let entry = &history[ver as usize];
-            versions.push((ver as Version, entry.update.to_string()));
+            versions.push(VersionInfo::new(ver, entry));
             // Prior version must exist until we reach this version
             ver = entry.prior_version.unwrap();
         }
@@ -926,6 +2312,20 @@ impl Workpad {
     pub fn author(&self) -> &str {
         &self.data.author
     }
+
+    /// Returns the named styles of this [`Workpad`] and their [`Format`]s, by name
+    pub fn styles(&self) -> impl Iterator<Item = (&str, &Format)> {
+        self.data
+            .styles
+            .iter()
+            .map(|(name, format)| (name.as_str(), format))
+    }
+
+    /// Returns the [`Format`] of a named style of this [`Workpad`], or `None` if there is no
+    /// style with the name
+    pub fn style(&self, name: &str) -> Option<&Format> {
+        self.data.styles.get(name)
+    }
 }
 
 workpad_id_type!(
@@ -949,38 +2349,188 @@ impl std::fmt::Display for Workpad {
                 None => f.write_str("None")?,
             }
         }
-        f.write_str("}")
+        f.write_str("}")
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SheetKind {
+    #[default]
+    Worksheet,
+    Textsheet,
+}
+
+impl std::fmt::Display for SheetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SheetKind::Worksheet => write!(f, "Worksheet"),
+            SheetKind::Textsheet => write!(f, "Textsheet"),
+        }
+    }
+}
+
+/// Data structure to store information related to a sheet within a workpad.
+#[derive(Debug, Clone)]
+pub struct SheetData {
+    #[allow(dead_code)] // TODO Only Worksheet supported so far
+    kind: SheetKind,
+    name: Intern<str>,
+    column_header_height: f32,
+    row_header_width: f32,
+    columns: Arc<Axis<ColumnId>>,
+    rows: Arc<Axis<RowId>>,
+    // Data for the columns and rows that are yet to be allocated ids
+    default_column: Arc<ColumnData>,
+    default_row: Arc<RowData>,
+    // Active cell is (RowId, ColumnId) rather than CellId as the active cell may be empty
+    active_cell: Option<(RowId, ColumnId)>,
+    // The bounds of the non-empty cells (if any)
+    used_range: Option<UsedRange>,
+    // The named style of the cells whose rows, columns and selves have none
+    style: Option<Intern<str>>,
+    conditional_formats: Arc<Vec<ConditionalFormat>>,
+    validations: Arc<Vec<Validation>>,
+    merges: Arc<Vec<Merge>>,
+}
+
+impl SheetData {
+    /// Returns the (rows, columns) indices of the cells of a merge of the sheet
+    fn merged_range(
+        &self,
+        merge: &Merge,
+    ) -> Option<(RangeInclusive<usize>, RangeInclusive<usize>)> {
+        let rows = self.rows.index_of(merge.first_row)?..=self.rows.index_of(merge.last_row)?;
+        let columns = self.columns.index_of(merge.first_column)?
+            ..=self.columns.index_of(merge.last_column)?;
+        Some((rows, columns))
+    }
+
+    /// Returns the (rows, columns) indices of the cells of each merge of the sheet
+    fn merged_ranges(
+        &self,
+    ) -> impl Iterator<Item = (RangeInclusive<usize>, RangeInclusive<usize>)> + '_ {
+        self.merges
+            .iter()
+            .filter_map(|merge| self.merged_range(merge))
+    }
+}
+
+/// The number of rows in a new sheet
+const MAX_ROWS: usize = 1 << 20;
+/// The number of columns in a new sheet
+const MAX_COLUMNS: usize = 1 << 14;
+
+/// Flag that marks the id of a row or column that has not been allocated.  The rest of the
+/// id is the index of the row or column.
+const IMPLIED_ID: IdBase = 1 << (IdBase::BITS - 1);
+
+fn is_implied_id(id: IdBase) -> bool {
+    id & IMPLIED_ID != 0
+}
+
+/// Types of id used for the rows and columns of a sheet
+trait AxisId: Copy + Eq + std::hash::Hash + From<IdBase> {
+    fn base(self) -> IdBase;
+}
+
+/// The rows (or columns) of a sheet.
+///
+/// Ids are only allocated for the rows (or columns) that have been written to.  All the others
+/// are implied: they have an id derived from their index (see [`IMPLIED_ID`]) and use the
+/// default data of the sheet.  So the size of a sheet grows with its content rather than its
+/// dimensions.
+///
+/// Both directions of the mapping between indices and ids are held so that either can be
+/// resolved without a search.  An `Axis` is shared (via an `Arc`) by all the versions of a
+/// sheet with the same layout.
+#[derive(Debug, Clone)]
+struct Axis<Id> {
+    len: usize,
+    allocated: BTreeMap<usize, Id>,
+    indices: HashMap<Id, usize>,
+}
+
+impl<Id: AxisId> Axis<Id> {
+    fn new(len: usize) -> Self {
+        Self {
+            len,
+            allocated: Default::default(),
+            indices: Default::default(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the id at an index which is implied unless an id has been allocated
+    fn id_at(&self, index: usize) -> Id {
+        match self.allocated.get(&index) {
+            Some(id) => *id,
+            None => Id::from(IMPLIED_ID | index as IdBase),
+        }
+    }
+
+    /// Returns the index of an allocated or implied id, or `None` if it is not part of the axis
+    fn index_of(&self, id: Id) -> Option<usize> {
+        let base = id.base();
+        if is_implied_id(base) {
+            let index = (base & !IMPLIED_ID) as usize;
+            (index < self.len).then_some(index)
+        } else {
+            self.indices.get(&id).copied()
+        }
+    }
+
+    fn allocate(&mut self, index: usize, id: Id) {
+        self.allocated.insert(index, id);
+        self.indices.insert(id, index);
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub enum SheetKind {
-    #[default]
-    Worksheet,
-    Textsheet,
+/// A block of cells of a sheet merged into one, identified by the rows and columns at its
+/// edges so that it keeps its cells if rows or columns move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Merge {
+    first_row: RowId,
+    first_column: ColumnId,
+    last_row: RowId,
+    last_column: ColumnId,
 }
 
-impl std::fmt::Display for SheetKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            SheetKind::Worksheet => write!(f, "Worksheet"),
-            SheetKind::Textsheet => write!(f, "Textsheet"),
+/// The bounds (as row and column indices) of the non-empty cells of a sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct UsedRange {
+    top: usize,
+    left: usize,
+    bottom: usize,
+    right: usize,
+}
+
+impl UsedRange {
+    fn new(row: usize, column: usize) -> Self {
+        Self {
+            top: row,
+            left: column,
+            bottom: row,
+            right: column,
         }
     }
-}
 
-/// Data structure to store information related to a sheet within a workpad.
-#[derive(Debug, Clone)]
-pub struct SheetData {
-    #[allow(dead_code)] // TODO Only Worksheet supported so far
-    kind: SheetKind,
-    name: Intern<str>,
-    column_header_height: f32,
-    row_header_width: f32,
-    columns: Vec<ColumnId>,
-    rows: Vec<RowId>,
-    // Active cell is (RowId, ColumnId) rather than CellId as the active cell may be empty
-    active_cell: Option<(RowId, ColumnId)>,
+    /// Returns a [`UsedRange`] extended (if necessary) to include the given cell
+    fn including(self, row: usize, column: usize) -> Self {
+        Self {
+            top: self.top.min(row),
+            left: self.left.min(column),
+            bottom: self.bottom.max(row),
+            right: self.right.max(column),
+        }
+    }
+
+    /// Determines if emptying the given cell might shrink the range
+    fn is_on_edge(&self, row: usize, column: usize) -> bool {
+        row == self.top || row == self.bottom || column == self.left || column == self.right
+    }
 }
 
 /// A sheet within a specific version of [`Workpad`].
@@ -1014,6 +2564,53 @@ impl Sheet {
         &self.data.name
     }
 
+    /// Returns the number of columns in this [`Sheet`]
+    pub fn column_count(&self) -> usize {
+        self.data.columns.len()
+    }
+
+    /// Returns the number of rows in this [`Sheet`]
+    pub fn row_count(&self) -> usize {
+        self.data.rows.len()
+    }
+
+    /// Returns the width of the columns of this [`Sheet`] whose width has not been set
+    pub fn default_column_width(&self) -> f32 {
+        self.data.default_column.width
+    }
+
+    /// Returns the height of the rows of this [`Sheet`] whose height has not been set
+    pub fn default_row_height(&self) -> f32 {
+        self.data.default_row.height
+    }
+
+    /// Returns the name of the default style of the cells of this [`Sheet`], if it has one
+    pub fn style(&self) -> Option<&str> {
+        self.data.style.as_deref()
+    }
+
+    /// Returns the [`ConditionalFormat`] rules of this [`Sheet`], in order of precedence
+    pub fn conditional_formats(&self) -> &[ConditionalFormat] {
+        &self.data.conditional_formats
+    }
+
+    /// Returns an evaluator of the [`ConditionalFormat`] rules of this [`Sheet`] against its
+    /// cells.  Create one per version rendered rather than per cell.
+    pub fn conditional_evaluator(&self) -> ConditionalEvaluator {
+        ConditionalEvaluator::new(self, self.data.conditional_formats.clone())
+    }
+
+    /// Returns the [`Validation`] rules of this [`Sheet`], in order
+    pub fn validations(&self) -> &[Validation] {
+        &self.data.validations
+    }
+
+    /// Returns a checker of values against the [`Validation`] rules of this [`Sheet`].
+    /// Create one per version rendered rather than per cell.
+    pub fn validator(&self) -> Validator {
+        Validator::new(self.data.validations.clone())
+    }
+
     /// Return an iterator to the [`Column`]s held by this [`Sheet`]
     pub fn columns(&self) -> impl ExactSizeIterator<Item = Column> + '_ {
         (0..(self.data.columns.len())).map(|idx| self.column(idx))
@@ -1021,9 +2618,12 @@ impl Sheet {
 
     /// Return a [`Column`] held by this [`Sheet`] given its index
     pub fn column(&self, index: usize) -> Column {
-        let id = self.data.columns[index];
-        let master_data = &self.workpad.master.data;
-        let data = master_data.read_column(id, self.version);
+        let id = self.data.columns.id_at(index);
+        let data = if is_implied_id(id.base()) {
+            self.data.default_column.clone()
+        } else {
+            self.workpad.master.data.read_column(id, self.version)
+        };
         Column {
             sheet: self.clone(),
             data,
@@ -1032,6 +2632,28 @@ impl Sheet {
         }
     }
 
+    /// Returns the [`Column`]s of this [`Sheet`] that have a style, in order.  Other columns
+    /// are not visited.
+    pub fn styled_columns(&self) -> impl Iterator<Item = Column> + '_ {
+        self.data
+            .columns
+            .allocated
+            .keys()
+            .map(|index| self.column(*index))
+            .filter(|column| column.style().is_some())
+    }
+
+    /// Returns the [`Row`]s of this [`Sheet`] that have a style, in order.  Other rows are not
+    /// visited.
+    pub fn styled_rows(&self) -> impl Iterator<Item = Row> + '_ {
+        self.data
+            .rows
+            .allocated
+            .keys()
+            .map(|index| self.row(*index))
+            .filter(|row| row.style().is_some())
+    }
+
     /// Return an iterator to the [`Rows`]s held by this [`Sheet`]
     pub fn rows(&self) -> impl ExactSizeIterator<Item = Row> + '_ {
         (0..(self.data.rows.len())).map(|idx| self.row(idx))
@@ -1040,9 +2662,12 @@ impl Sheet {
     /// Return a [`Row`] held by this [`Sheet`] given its index
     #[allow(dead_code)]
     pub fn row(&self, index: usize) -> Row {
-        let id = self.data.rows[index];
-        let master_data = &self.workpad.master.data;
-        let data = master_data.read_row(id, self.version);
+        let id = self.data.rows.id_at(index);
+        let data = if is_implied_id(id.base()) {
+            self.data.default_row.clone()
+        } else {
+            self.workpad.master.data.read_row(id, self.version)
+        };
         Row {
             sheet: self.clone(),
             data,
@@ -1066,45 +2691,91 @@ impl Sheet {
     /// Returns the active [`Cell`] of this ['Sheet'].
     /// If there are no cells in this sheet it will return `None`
     pub fn active_cell(&self) -> Option<Cell> {
+        // The ids held may be implied ones for rows or columns allocated since
         self.data.active_cell.map(|(row_id, column_id)| {
-            self.internal_cell(
+            self.cell(
                 self.internal_row_index(row_id),
-                row_id,
                 self.internal_column_index(column_id),
-                column_id,
             )
         })
     }
 
-    #[allow(dead_code)]
+    /// Returns the [`Cell`]s of this [`Sheet`] that have ever been written to, row by row.
+    /// All other cells are empty and so are not visited.
     pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
-        // TODO use a range
-        let rows = self.data.rows.len();
-        let cols = self.data.columns.len();
-        let mut rw = 0;
-        let mut cl = 0;
-        std::iter::from_fn(move || {
-            if rw >= rows {
-                None
-            } else {
-                let cell = self.cell(rw, cl);
-                cl += 1;
-                if cl >= cols {
-                    cl = 0;
-                    rw += 1;
-                }
-                Some(cell)
-            }
+        let master_data = &self.workpad.master.data;
+        let mut cells: Vec<Cell> = master_data
+            .read_sheet_cells(self.id, self.version)
+            .into_iter()
+            .map(|((_, row_id, column_id), id)| Cell {
+                sheet: self.clone(),
+                row: self.row(self.internal_row_index(row_id)),
+                column: self.column(self.internal_column_index(column_id)),
+                id: Some(id),
+                data: Some(master_data.read_cell(id, self.version)),
+            })
+            .collect();
+        cells.sort_by_key(|cell| (cell.row.index, cell.column.index));
+        cells.into_iter()
+    }
+
+    /// Returns the smallest range that contains all the non-empty cells of this [`Sheet`] or
+    /// `None` if all its cells are empty.
+    pub fn used_range(&self) -> Option<CellReference> {
+        self.data.used_range.map(|used_range| {
+            CellReference::new(
+                self.clone(),
+                used_range.top..=used_range.bottom,
+                used_range.left..=used_range.right,
+            )
+        })
+    }
+
+    /// Returns the blocks of cells of this [`Sheet`] that are merged into one cell, in the
+    /// order they were merged
+    pub fn merged_ranges(&self) -> impl Iterator<Item = CellReference> + '_ {
+        self.data
+            .merged_ranges()
+            .map(|(rows, columns)| CellReference::new(self.clone(), rows, columns))
+    }
+
+    /// Returns the block of cells merged into one that contains the cell at a row and column
+    /// index, if it is merged
+    pub fn merged_range(&self, row: usize, column: usize) -> Option<CellReference> {
+        self.merged_ranges()
+            .find(|merged| merged.rows().contains(&row) && merged.columns().contains(&column))
+    }
+
+    /// Returns the non-empty [`Cell`]s of this [`Sheet`] row by row.  Unlike [`Sheet::cells`]
+    /// cells that have been cleared are not visited.
+    pub fn non_empty_cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.cells().filter(|cell| {
+            cell.data
+                .as_ref()
+                .map_or(false, |data| !data.value.is_empty())
         })
     }
 
+    /// Returns the cells of this [`Sheet`] that have comments, row by row
+    pub fn commented_cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.cells().filter(|cell| !cell.comments().is_empty())
+    }
+
     /// Return a [`Row`] held by this [`Sheet`] given its row and column indices.
     pub fn cell(&self, row: usize, column: usize) -> Cell {
-        let row_id = self.data.rows[row];
-        let column_id = self.data.columns[column];
+        let row_id = self.data.rows.id_at(row);
+        let column_id = self.data.columns.id_at(column);
         self.internal_cell(row, row_id, column, column_id)
     }
 
+    /// Returns the [`Cell`] at the row and column with the given ids, or `None` if either is
+    /// not part of this sheet
+    pub fn cell_by_ids(&self, row_id: RowId, column_id: ColumnId) -> Option<Cell> {
+        let row = self.data.rows.index_of(row_id)?;
+        let column = self.data.columns.index_of(column_id)?;
+        Some(self.cell(row, column))
+    }
+
     pub fn internal_cell(
         &self,
         row: usize,
@@ -1125,15 +2796,11 @@ impl Sheet {
     }
 
     fn internal_row_index(&self, row_id: RowId) -> usize {
-        self.data.rows.iter().position(|id| *id == row_id).unwrap()
+        self.data.rows.index_of(row_id).unwrap()
     }
 
     fn internal_column_index(&self, column_id: ColumnId) -> usize {
-        self.data
-            .columns
-            .iter()
-            .position(|id| *id == column_id)
-            .unwrap()
+        self.data.columns.index_of(column_id).unwrap()
     }
 }
 
@@ -1154,11 +2821,12 @@ impl std::fmt::Display for Sheet {
         self.workpad.id().fmt(f)?;
         f.write_str(", version:")?;
         self.workpad.version.fmt(f)?;
-        f.write_str(", rows:[")?;
-        f.write_fmt(format_args!("{}", self.data.rows.iter().format(", ")))?;
-        f.write_str("], columns:[")?;
-        f.write_fmt(format_args!("{}", self.data.columns.iter().format(", ")))?;
-        write!(f, "]}}",)
+        write!(
+            f,
+            ", rows:{}, columns:{}}}",
+            self.row_count(),
+            self.column_count()
+        )
     }
 }
 
@@ -1167,11 +2835,18 @@ workpad_id_type!(
     ColumnId
 );
 
+impl AxisId for ColumnId {
+    fn base(self) -> IdBase {
+        self.0
+    }
+}
+
 /// Data structure to store information related to a column of a sheet within a workpad.
 #[derive(Debug, Clone)]
 struct ColumnData {
     name: Name,
     width: f32,
+    style: Option<Intern<str>>,
 }
 
 /// A column within a specific version of a [`Sheet`] in a [`Workpad`].
@@ -1212,6 +2887,11 @@ impl Column {
     pub fn width(&self) -> f32 {
         self.data.width
     }
+
+    /// Return the name of the style of this [`Column`], if it has one
+    pub fn style(&self) -> Option<&str> {
+        self.data.style.as_deref()
+    }
 }
 
 workpad_id_type!(
@@ -1219,11 +2899,18 @@ workpad_id_type!(
     RowId
 );
 
+impl AxisId for RowId {
+    fn base(self) -> IdBase {
+        self.0
+    }
+}
+
 /// Data structure to store information related to a row of a sheet within a workpad.
 #[derive(Debug, Clone)]
 struct RowData {
     name: Name,
     height: f32,
+    style: Option<Intern<str>>,
 }
 
 /// A row within a specific version of a [`Sheet`] in a [`Workpad`].
@@ -1265,6 +2952,11 @@ impl Row {
     pub fn height(&self) -> f32 {
         self.data.height
     }
+
+    /// Return the name of the style of this [`Row`], if it has one.
+    pub fn style(&self) -> Option<&str> {
+        self.data.style.as_deref()
+    }
 }
 
 workpad_id_type!(
@@ -1273,15 +2965,20 @@ workpad_id_type!(
 );
 
 /// Data structure to store information related to a cell within a workpad.
-// TODO value types, borders, alignment, etc.
+// TODO value types
 #[derive(Debug, Default, Clone)]
 struct CellData {
     name: Name,
     value: Value,
+    style: Option<Intern<str>>,
+    format: Format,
+    // The thread of comments on the cell, oldest first
+    comments: Arc<Vec<Comment>>,
+    hyperlink: Option<Hyperlink>,
 }
 
 /// A cell within a specific version of a [`Workpad`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Cell {
     sheet: Sheet,
     row: Row,
@@ -1320,16 +3017,20 @@ impl Cell {
 
     /// Returns the name of this [`Cell`]
     pub fn name(&self) -> &str {
-        let custom = match &self.data {
+        match self.custom_name() {
+            Some(n) => n,
+            None => Intern::new(format!("{}{}", self.column.name(), self.row.name())).as_ref(),
+        }
+    }
+
+    /// Returns the name of this [`Cell`] if it has been explicitly set
+    fn custom_name(&self) -> Option<&str> {
+        match &self.data {
             Some(data) => match &data.name {
                 Name::Auto => None,
                 Name::Custom(n) => Some(n),
             },
             None => None,
-        };
-        match custom {
-            Some(n) => n,
-            None => Intern::new(format!("{}{}", self.column.name(), self.row.name())).as_ref(),
         }
     }
 
@@ -1337,13 +3038,66 @@ impl Cell {
     // TODO How do we want to expose values?
     pub fn value(&self) -> &str {
         match &self.data {
-            Some(data) => match &data.value {
-                Value::Empty => "",
-                Value::String(s) => s,
-            },
+            Some(data) => data.value.as_str(),
             None => "",
         }
     }
+
+    /// Returns the name of the style of this [`Cell`], if it has one
+    pub fn style(&self) -> Option<&str> {
+        self.data.as_ref().and_then(|data| data.style.as_deref())
+    }
+
+    /// Returns the [`Format`] set on this [`Cell`] itself.  See [`Cell::resolved_format`]
+    /// for the format it is displayed with.
+    pub fn format(&self) -> &Format {
+        static NO_FORMAT: Format = Format::NONE;
+        match &self.data {
+            Some(data) => &data.format,
+            None => &NO_FORMAT,
+        }
+    }
+
+    /// Returns the thread of [`Comment`]s on this [`Cell`]: the comment that started it
+    /// followed by the replies to it, oldest first.  Empty if the cell has no comments.
+    pub fn comments(&self) -> &[Comment] {
+        match &self.data {
+            Some(data) => &data.comments,
+            None => &[],
+        }
+    }
+
+    /// Returns the [`Hyperlink`] of this [`Cell`], if it has one
+    pub fn hyperlink(&self) -> Option<&Hyperlink> {
+        self.data.as_ref().and_then(|data| data.hyperlink.as_ref())
+    }
+
+    /// Returns the [`Format`] this [`Cell`] is displayed with: the styles of its sheet, its
+    /// column, its row and itself overlaid in turn, then the format set on the cell itself.
+    /// Styles that are not defined are ignored.
+    pub fn resolved_format(&self) -> Format {
+        let styles = &self.sheet.workpad.data.styles;
+        [
+            self.sheet.style(),
+            self.column.style(),
+            self.row.style(),
+            self.style(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|name| styles.get(name))
+        .chain([self.format()])
+        .fold(Format::NONE, |format, other| format.overlay(other))
+    }
+
+    /// Returns the value of this [`Cell`] as displayed with its [`NumberFormat`], if it has
+    /// one
+    pub fn display_value(&self) -> std::borrow::Cow<'_, str> {
+        match self.resolved_format().number_format {
+            Some(number_format) => number_format.apply(self.value()),
+            None => std::borrow::Cow::Borrowed(self.value()),
+        }
+    }
 }
 
 /// A value of a cell
@@ -1354,6 +3108,22 @@ pub enum Value {
     String(Intern<str>),
 }
 
+impl Value {
+    fn is_empty(&self) -> bool {
+        match self {
+            Value::Empty => true,
+            Value::String(s) => s.is_empty(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Value::Empty => "",
+            Value::String(s) => s,
+        }
+    }
+}
+
 /// A name
 #[allow(dead_code)]
 #[derive(Debug, Default, Clone)]
@@ -1394,6 +3164,34 @@ fn create_column_name(column: usize) -> Intern<str> {
     }
 }
 
+/// The inverse of [`create_column_name`].  Letters may be of either case.  Returns `None`
+/// if the name is not a column name or is too long for its index to be represented.
+fn parse_column_name(name: &str) -> Option<usize> {
+    if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    // Skip the indices used by all the shorter names then treat the name as base 26
+    let offset = (1..name.len()).try_fold(0_usize, |acc, len| {
+        acc.checked_add(26_usize.checked_pow(len.try_into().ok()?)?)
+    })?;
+    let value = name.bytes().try_fold(0_usize, |acc, b| {
+        acc.checked_mul(26)?
+            .checked_add((b.to_ascii_uppercase() - b'A') as usize)
+    })?;
+    offset.checked_add(value)
+}
+
+/// The inverse of the automatic row names (see [`intern_usize`]) which count from one.
+/// Returns `None` if the name is not a row name or is too long for its index to be
+/// represented.
+fn parse_row_name(name: &str) -> Option<usize> {
+    if name.starts_with('0') || !name.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    name.parse::<usize>().ok()?.checked_sub(1)
+}
+
 fn intern_base_26(i: usize, min_len: usize) -> Intern<str> {
     let mut buffer = [b'A'; 20];
     let mut rem = i;
@@ -1486,6 +3284,16 @@ where
         self.index.range(range)
     }
 
+    /// Replaces the key of every entry, removing those for which `f` returns `None`.  This
+    /// cannot be undone so is not allowed during a transaction.
+    fn rekey(&mut self, f: impl Fn(Key) -> Option<Key>) {
+        assert!(self.undos.is_empty(), "Transaction in progress");
+        self.index = std::mem::take(&mut self.index)
+            .into_iter()
+            .filter_map(|(key, value)| f(key).map(|key| (key, value)))
+            .collect();
+    }
+
     fn insert(&mut self, key: Key, value: Value) {
         match self.index.insert(key, value) {
             Some(prior) => self.undos.push(UndoAction::Insert(key, prior)),
@@ -1520,6 +3328,9 @@ where
     Data: Clone,
 {
     index: RwLock<UndoableIndex<(Id, Version, Version), Data>>,
+    // The ids whose entries were changed by each version, so that the entries affected by
+    // branching can be found without visiting the whole index
+    changes: RwLock<UndoableIndex<(Version, Id), ()>>,
 }
 
 impl<Id, Data> VersionIndex<Id, Data>
@@ -1529,23 +3340,87 @@ where
 {
     fn tx_begin(&self) {
         self.index.read().unwrap().tx_begin();
+        self.changes.read().unwrap().tx_begin();
     }
 
     fn tx_commit(&self) {
         self.index.write().unwrap().tx_commit();
+        self.changes.write().unwrap().tx_commit();
     }
 
     fn tx_rollback(&self) {
         self.index.write().unwrap().tx_rollback();
+        self.changes.write().unwrap().tx_rollback();
     }
 
     /// Reads data as at the given version:
     fn read(&self, id: Id, version: Version) -> Option<Data> {
+        let index = self.index.read().unwrap();
+        Self::entry_at(&index, id, version).map(|(_, v)| v)
+    }
+
+    /// Reads the data, as at the given version, for all the ids in the range `from..=to`
+    fn read_all(&self, from: Id, to: Id, version: Version) -> Vec<(Id, Data)> {
+        let index = self.index.read().unwrap();
+        index
+            .range((from, 0, 0)..=(to, Version::MAX, Version::MAX))
+            .filter(|(&(_, from, to), _)| from <= version && version <= to)
+            .map(|(&(id, _, _), v)| (id, (*v).clone()))
+            .collect()
+    }
+
+    /// Returns the version range of every entry for an id, whichever branch of the history it
+    /// is on, with its data
+    fn entries(&self, id: Id) -> Vec<(Version, Version, Data)> {
         let index = self.index.read().unwrap();
         index
-            .range(Self::all_versions(id))
-            .find(|(&(_, from, to), _)| from <= version && version <= to)
-            .map(|(_, v)| (*v).clone())
+            .range((id, 0, 0)..=(id, Version::MAX, Version::MAX))
+            .map(|(&(_, from, to), v)| (from, to, (*v).clone()))
+            .collect()
+    }
+
+    /// Prepares the index for a new version that is based on an earlier version rather than
+    /// the latest, so that the data seen as at the new version is that of the base version:
+    ///
+    /// * entries made after the base version are closed at `new_version - 1`
+    /// * entries seen at the base version but closed since are reopened at `new_version`
+    ///
+    /// Both kinds of entry are for ids changed by a version after the base version so only
+    /// the entries of those ids are visited.
+    fn branch(&self, base_version: Version, new_version: Version) {
+        let mut index = self.index.write().unwrap();
+        let mut changes = self.changes.write().unwrap();
+
+        let ids: BTreeSet<Id> = changes
+            .range::<(Version, Id), _>(..)
+            .rev()
+            .take_while(|(&(version, _), _)| version > base_version)
+            .map(|(&(_, id), _)| id)
+            .collect();
+
+        let mut closes = vec![];
+        let mut reopens = vec![];
+        for id in ids {
+            for (&(id, from, to), data) in
+                index.range((id, 0, 0)..=(id, Version::MAX, Version::MAX))
+            {
+                if from > base_version && to == Version::MAX {
+                    closes.push(((id, from, to), data.clone()));
+                } else if from <= base_version && base_version <= to && to != Version::MAX {
+                    reopens.push((id, data.clone()));
+                }
+            }
+        }
+
+        for ((id, from, to), data) in closes {
+            index.insert((id, from, new_version - 1), data);
+            index.remove(&(id, from, to));
+            changes.insert((new_version, id), ());
+        }
+        for (id, data) in reopens {
+            index.insert((id, new_version, Version::MAX), data);
+            changes.insert((new_version, id), ());
+        }
     }
 
     /// Writes new data as at the given version:
@@ -1560,19 +3435,17 @@ where
     /// or if no existing_data, only the new data entry is made
     fn write(&self, id: Id, data: Data, version: Version) {
         let mut index = self.index.write().unwrap();
+        self.changes.write().unwrap().insert((version, id), ());
 
-        let existing = index
-            .range(Self::all_versions(id))
-            .find(|(&(_, from, to), _)| from <= version && version <= to)
-            .map(|(k, v)| (k, (*v).clone()));
+        let existing = Self::entry_at(&index, id, version);
 
         match existing {
-            Some((&(_, from, to), existing)) if from < version => {
+            Some(((_, from, to), existing)) if from < version => {
                 index.insert((id, from, version - 1), existing);
                 index.insert((id, version, to), data);
                 index.remove(&(id, from, to));
             }
-            Some((&(_, from, to), _)) => {
+            Some(((_, from, to), _)) => {
                 index.insert((id, from, to), data);
             }
             None => {
@@ -1590,37 +3463,41 @@ where
     /// * (from, version-1, Id) -> data
     fn delete(&self, id: Id, version: Version) {
         let mut index = self.index.write().unwrap();
+        self.changes.write().unwrap().insert((version, id), ());
 
-        let existing = index
-            .range(Self::all_versions(id))
-            .find(|(&(_, from, to), _)| from <= version && version <= to)
-            .map(|(k, v)| (k, (*v).clone()));
-        if let Some((&(_, from, to), existing)) = existing {
+        let existing = Self::entry_at(&index, id, version);
+        if let Some(((_, from, to), existing)) = existing {
             index.insert((id, from, version - 1), existing);
             index.remove(&(id, from, to));
         }
     }
 
-    fn all_versions(id: Id) -> impl RangeBounds<(Id, Version, Version)> {
-        struct AllVersions<Id> {
-            start: (Id, Version, Version),
-            end: (Id, Version, Version),
-        }
-
-        impl<Id> RangeBounds<(Id, Version, Version)> for AllVersions<Id> {
-            fn start_bound(&self) -> std::ops::Bound<&(Id, Version, Version)> {
-                std::ops::Bound::Included(&(self.start))
-            }
-
-            fn end_bound(&self) -> std::ops::Bound<&(Id, Version, Version)> {
-                std::ops::Bound::Included(&(self.end))
-            }
-        }
+    /// Replaces the version range of every entry using `remap`, removing those for which it
+    /// returns `None`
+    fn compact(&self, remap: impl Fn(Version, Version) -> Option<(Version, Version)>) {
+        let mut index = self.index.write().unwrap();
+        index.rekey(|(id, from, to)| remap(from, to).map(|(from, to)| (id, from, to)));
+        // A change made by a removed version now takes effect at the next version kept
+        let mut changes = self.changes.write().unwrap();
+        changes
+            .rekey(|(version, id)| remap(version, Version::MAX).map(|(version, _)| (version, id)));
+    }
 
-        AllVersions {
-            start: (id, 0, 0),
-            end: (id, Version::MAX, Version::MAX),
-        }
+    /// Finds the entry for an id that covers the given version.
+    ///
+    /// The version ranges of the entries for an id never overlap so the only candidate is the
+    /// last entry that starts at or before the version, which is found without visiting the
+    /// rest of the history of the id.
+    fn entry_at(
+        index: &UndoableIndex<(Id, Version, Version), Data>,
+        id: Id,
+        version: Version,
+    ) -> Option<((Id, Version, Version), Data)> {
+        index
+            .range((id, 0, 0)..=(id, version, Version::MAX))
+            .next_back()
+            .filter(|(&(_, _, to), _)| version <= to)
+            .map(|(k, v)| (*k, (*v).clone()))
     }
 }
 
@@ -1632,6 +3509,7 @@ where
     fn default() -> Self {
         Self {
             index: Default::default(),
+            changes: Default::default(),
         }
     }
 }
@@ -1676,6 +3554,36 @@ mod tests {
         assert_eq!("ZZZZZ", &create_column_name(AAAAAA - 1) as &str);
     }
 
+    #[test]
+    fn column_name_parsing() {
+        for column in [
+            0, 1, 25, 26, 27, 701, 702, 18277, 18278, 475253, 475254, 12356629,
+        ] {
+            assert_eq!(
+                Some(column),
+                parse_column_name(&create_column_name(column)),
+                "Column {column}"
+            );
+        }
+        assert_eq!(Some(27), parse_column_name("ab"));
+        assert_eq!(None, parse_column_name(""));
+        assert_eq!(None, parse_column_name("A1"));
+        assert_eq!(Some(12356630), parse_column_name("AAAAAA"));
+        assert_eq!(None, parse_column_name(&"Z".repeat(20)));
+    }
+
+    #[test]
+    fn row_name_parsing() {
+        assert_eq!(Some(0), parse_row_name("1"));
+        assert_eq!(Some(1199), parse_row_name("1200"));
+        assert_eq!(Some(1199), parse_row_name(&intern_usize(1200)));
+        assert_eq!(None, parse_row_name("0"));
+        assert_eq!(None, parse_row_name("01"));
+        assert_eq!(None, parse_row_name(""));
+        assert_eq!(None, parse_row_name("1A"));
+        assert_eq!(None, parse_row_name("99999999999999999999999"));
+    }
+
     #[test]
     fn new_blank() {
         let master = WorkpadMaster::new_blank();
@@ -2288,11 +4196,238 @@ mod tests {
             value: String::from("123"),
         });
 
-        assert!(result.is_err());
-        assert_eq!(
-            "ColumnId(4294967295) not found (during update: Set Sheet Cell Value)",
-            result.err().unwrap().to_string()
-        );
+        assert!(result.is_err());
+        assert_eq!(
+            "ColumnId(4294967295) not found (during update: Set Sheet Cell Value)",
+            result.err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn portable_updates_apply_to_other_copies() {
+        let mut master = WorkpadMaster::new_starter();
+        let mut copy = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let sheet_id = sheet.id();
+        assert_eq!(sheet_id, copy.active_version().active_sheet().unwrap().id());
+
+        // Rows and columns are allocated ids in a different order in each copy
+        set_cell_values(&mut master, &sheet, &[(1, 1, "a"), (2, 2, "b")]);
+        let copy_sheet = copy.active_version().active_sheet().unwrap();
+        set_cell_values(&mut copy, &copy_sheet, &[(2, 2, "b"), (1, 1, "a")]);
+
+        let pad = master.active_version();
+        let cell = pad.active_sheet().unwrap().cell(1, 2);
+        let update = WorkpadUpdate::SheetSetCellValue {
+            sheet_id,
+            row_id: cell.row().id(),
+            column_id: cell.column().id(),
+            value: String::from("x"),
+        };
+        let portable = WorkpadUpdate::Multi(vec![update]).portable(&pad);
+
+        let pad = master.update(portable.clone()).unwrap();
+        assert_eq!("x", pad.active_sheet().unwrap().cell(1, 2).value());
+        let pad = copy.update(portable.clone()).unwrap();
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("x", sheet.cell(1, 2).value());
+        assert_eq!(3, sheet.non_empty_cells().count());
+
+        let WorkpadUpdate::Multi(updates) = portable else {
+            unreachable!()
+        };
+        let WorkpadUpdate::SheetSetCellValue {
+            row_id, column_id, ..
+        } = updates[0]
+        else {
+            unreachable!()
+        };
+        assert_eq!("C2", sheet.cell_by_ids(row_id, column_id).unwrap().name());
+    }
+
+    #[test]
+    fn cell_formats_are_versioned() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(1, 1);
+        let format_update = |replace: bool, format: Format| {
+            let (sheet_id, row_id, column_id) = (sheet.id(), cell.row().id(), cell.column().id());
+            if replace {
+                WorkpadUpdate::SheetSetCellFormat {
+                    sheet_id,
+                    row_id,
+                    column_id,
+                    format,
+                }
+            } else {
+                WorkpadUpdate::SheetApplyCellFormat {
+                    sheet_id,
+                    row_id,
+                    column_id,
+                    format,
+                }
+            }
+        };
+        assert!(cell.format().is_empty());
+
+        let bold = Format {
+            bold: Some(true),
+            horizontal_alignment: Some(HorizontalAlignment::Right),
+            ..Default::default()
+        };
+        let pad_1 = master.update(format_update(true, bold.clone())).unwrap();
+        let italic = Format {
+            italic: Some(true),
+            ..Default::default()
+        };
+        let pad_2 = master.update(format_update(false, italic.clone())).unwrap();
+        let pad_3 = set_cell_values(&mut master, &pad_2.active_sheet().unwrap(), &[(1, 1, "x")]);
+        let pad_4 = master.update(format_update(true, italic.clone())).unwrap();
+
+        let format_of = |pad: &Workpad| pad.active_sheet().unwrap().cell(1, 1).format().clone();
+        assert_eq!(bold, format_of(&pad_1));
+        assert_eq!(bold.overlay(&italic), format_of(&pad_2));
+        // Setting the value keeps the format
+        assert_eq!(bold.overlay(&italic), format_of(&pad_3));
+        assert_eq!("x", pad_3.active_sheet().unwrap().cell(1, 1).value());
+        assert_eq!(italic, format_of(&pad_4));
+
+        // Number formats change how values are displayed, not the values
+        let percentage = Format {
+            number_format: Some(NumberFormat::Percentage { decimals: 1 }),
+            ..Default::default()
+        };
+        master.update(format_update(true, percentage)).unwrap();
+        let pad_5 = set_cell_values(
+            &mut master,
+            &pad_4.active_sheet().unwrap(),
+            &[(1, 1, "0.25")],
+        );
+        let cell_5 = pad_5.active_sheet().unwrap().cell(1, 1);
+        assert_eq!("0.25", cell_5.value());
+        assert_eq!("25.0%", cell_5.display_value());
+
+        let invalid = Format {
+            number_format: Some(NumberFormat::Custom("0\"".to_owned())),
+            ..Default::default()
+        };
+        let err = master.update(format_update(true, invalid)).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::InvalidNumberFormat(NumberFormatError::UnterminatedQuote)
+        ));
+
+        // Formatting alone does not make a cell non-empty
+        assert!(pad_2.active_sheet().unwrap().used_range().is_none());
+        assert_eq!(1, pad_2.active_sheet().unwrap().cells().count());
+    }
+
+    #[test]
+    fn named_styles_and_sheet_defaults() {
+        let mut master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+        assert!(pad.style("Heading").is_some());
+        let sheet = pad.active_sheet().unwrap();
+        let sheet_id = sheet.id();
+        let (row_id, column_id) = (sheet.row(1).id(), sheet.column(2).id());
+
+        let style_of = |name: &str, fill: u8| WorkpadUpdate::SetStyle {
+            name: name.to_owned(),
+            format: Format {
+                fill_color: Some(Color::rgb(fill, fill, fill)),
+                bold: (name == "Sheet").then_some(true),
+                ..Default::default()
+            },
+        };
+        master
+            .update(WorkpadUpdate::Multi(vec![
+                style_of("Sheet", 1),
+                style_of("Column", 2),
+                style_of("Row", 3),
+                style_of("Cell", 4),
+            ]))
+            .unwrap();
+
+        let fill_of = |pad: &Workpad, row: usize, column: usize| {
+            let cell = pad.active_sheet().unwrap().cell(row, column);
+            let format = cell.resolved_format();
+            (format.fill_color.map(|color| color.r), format.bold)
+        };
+        let style = |name: &str| Some(name.to_owned());
+        master
+            .update(WorkpadUpdate::SheetSetStyle {
+                sheet_id,
+                style: style("Sheet"),
+            })
+            .unwrap();
+        master
+            .update(WorkpadUpdate::SheetSetColumnStyle {
+                sheet_id,
+                column_id,
+                style: style("Column"),
+            })
+            .unwrap();
+        master
+            .update(WorkpadUpdate::SheetSetRowStyle {
+                sheet_id,
+                row_id,
+                style: style("Row"),
+            })
+            .unwrap();
+        let pad = master
+            .update(WorkpadUpdate::SheetSetCellStyle {
+                sheet_id,
+                row_id,
+                column_id,
+                style: style("Cell"),
+            })
+            .unwrap();
+
+        // Rows override columns which override the sheet, and cells override all three
+        assert_eq!((Some(1), Some(true)), fill_of(&pad, 0, 0));
+        assert_eq!((Some(2), Some(true)), fill_of(&pad, 0, 2));
+        assert_eq!((Some(3), Some(true)), fill_of(&pad, 1, 0));
+        assert_eq!((Some(4), Some(true)), fill_of(&pad, 1, 2));
+        assert_eq!(Some("Cell"), pad.active_sheet().unwrap().cell(1, 2).style());
+
+        // The format set on a cell overrides its styles
+        let pad_with_format = master
+            .update(WorkpadUpdate::SheetApplyCellFormat {
+                sheet_id,
+                row_id,
+                column_id,
+                format: Format {
+                    bold: Some(false),
+                    ..Default::default()
+                },
+            })
+            .unwrap();
+        assert_eq!((Some(4), Some(false)), fill_of(&pad_with_format, 1, 2));
+
+        // Changing a style changes every cell using it, but not earlier versions
+        let pad_changed = master.update(style_of("Row", 30)).unwrap();
+        assert_eq!((Some(30), Some(true)), fill_of(&pad_changed, 1, 0));
+        assert_eq!((Some(3), Some(true)), fill_of(&pad, 1, 0));
+
+        // Cells using a removed style fall back to the defaults of their sheet
+        let pad_removed = master
+            .update(WorkpadUpdate::RemoveStyle {
+                name: "Row".to_owned(),
+            })
+            .unwrap();
+        assert_eq!((Some(1), Some(true)), fill_of(&pad_removed, 1, 0));
+
+        let err = master
+            .update(WorkpadUpdate::SheetSetCellStyle {
+                sheet_id,
+                row_id,
+                column_id,
+                style: style("Row"),
+            })
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::MissingStyle(ref name) if name == "Row"));
+        let err = master.update(style_of(" ", 0)).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::InvalidName(_)));
     }
 
     #[test]
@@ -2530,8 +4665,529 @@ mod tests {
         assert!(forth_vers.next().is_none());
     }
 
+    #[test]
+    fn new_version_after_switching_back_ignores_dead_versions() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        set_cell_values(&mut master, &sheet, &[(0, 0, "A")]);
+        set_cell_values(&mut master, &sheet, &[(1, 1, "B")]);
+
+        master
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .expect("Update should succeed");
+        let pad = set_cell_values(&mut master, &sheet, &[(2, 2, "C")]);
+
+        // Version 3 is based on version 1 so B2 (set in version 2) must not be seen
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("A", sheet.cell(0, 0).value());
+        assert_eq!("", sheet.cell(1, 1).value());
+        assert_eq!("C", sheet.cell(2, 2).value());
+
+        // Version 2 is unaffected
+        master
+            .update(WorkpadUpdate::SetVersion { version: 2 })
+            .expect("Update should succeed");
+        let sheet = master.active_version().active_sheet().unwrap();
+        assert_eq!("B", sheet.cell(1, 1).value());
+        assert_eq!("", sheet.cell(2, 2).value());
+    }
+
+    #[test]
+    fn multi_sees_earlier_parts() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(3, 4);
+
+        let pad = master
+            .update(WorkpadUpdate::Multi(vec![
+                WorkpadUpdate::SheetSetCellValue {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    value: String::from("X"),
+                },
+                WorkpadUpdate::SheetSetActiveCell {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                },
+                WorkpadUpdate::SheetSetProperties {
+                    sheet_id: sheet.id(),
+                    new_name: String::from("Renamed"),
+                },
+            ]))
+            .expect("Update should succeed");
+
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("Renamed", sheet.name());
+        assert_eq!("E4", sheet.active_cell().unwrap().name());
+        assert_eq!("E4", sheet.used_range().unwrap().first_cell().name());
+    }
+
+    #[test]
+    fn used_range() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        assert!(sheet.used_range().is_none());
+        assert_eq!(0, sheet.non_empty_cells().count());
+
+        let used = |pad: &Workpad| {
+            let used_range = pad.active_sheet().unwrap().used_range()?;
+            Some((
+                used_range.first_cell().name().to_owned(),
+                used_range.last_cell().name().to_owned(),
+            ))
+        };
+        let some = |first: &str, last: &str| Some((String::from(first), String::from(last)));
+
+        let pad = set_cell_values(&mut master, &sheet, &[(4, 2, "x")]);
+        assert_eq!(some("C5", "C5"), used(&pad));
+
+        let pad = set_cell_values(&mut master, &sheet, &[(1, 6, "y"), (9, 1, "z")]);
+        assert_eq!(some("B2", "G10"), used(&pad));
+
+        // Emptying an inner cell leaves the range unchanged
+        let pad = set_cell_values(&mut master, &sheet, &[(5, 5, "w")]);
+        let pad2 = set_cell_values(&mut master, &sheet, &[(5, 5, "")]);
+        assert_eq!(used(&pad), used(&pad2));
+
+        // Emptying an edge cell shrinks the range
+        let pad = set_cell_values(&mut master, &sheet, &[(9, 1, "")]);
+        assert_eq!(some("C2", "G5"), used(&pad));
+
+        let pad = set_cell_values(&mut master, &sheet, &[(4, 2, ""), (1, 6, "")]);
+        assert_eq!(None, used(&pad));
+
+        // Earlier versions keep their own range
+        let pad = master
+            .update(WorkpadUpdate::SetVersion { version: 2 })
+            .expect("Update should succeed");
+        assert_eq!(some("B2", "G10"), used(&pad));
+    }
+
+    #[test]
+    fn non_empty_cells() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        set_cell_values(
+            &mut master,
+            &sheet,
+            &[(7, 1, "a"), (2, 8, "b"), (2, 3, "c")],
+        );
+        let pad = set_cell_values(&mut master, &sheet, &[(2, 8, ""), (0, 0, "d")]);
+
+        let sheet = pad.active_sheet().unwrap();
+        let cells: Vec<(String, String)> = sheet
+            .non_empty_cells()
+            .map(|cell| (cell.name().to_owned(), cell.value().to_owned()))
+            .collect();
+        assert_eq!(
+            vec![
+                (String::from("A1"), String::from("d")),
+                (String::from("D3"), String::from("c")),
+                (String::from("B8"), String::from("a")),
+            ],
+            cells
+        );
+    }
+
+    #[test]
+    fn rows_and_columns_allocated_when_written() {
+        let mut master = WorkpadMaster::new_blank();
+        let pad = master
+            .update(WorkpadUpdate::SheetAdd {
+                kind: SheetKind::Worksheet,
+                name: String::from("Big"),
+            })
+            .expect("Update should succeed");
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!(1_048_576, sheet.row_count());
+        assert_eq!(16_384, sheet.column_count());
+        assert_eq!(0, sheet.data.rows.allocated.len());
+        assert_eq!(0, sheet.data.columns.allocated.len());
+        assert!(master.data.rows_idx.index.read().unwrap().index.is_empty());
+        assert!(master
+            .data
+            .columns_idx
+            .index
+            .read()
+            .unwrap()
+            .index
+            .is_empty());
+
+        let last = sheet.cell(1_048_575, 16_383);
+        assert_eq!("XFD1048576", last.name());
+        assert_eq!(sheet.default_row_height(), last.row().height());
+
+        let pad = set_cell_values(&mut master, &sheet, &[(1_048_575, 16_383, "z")]);
+        let allocated = pad.active_sheet().unwrap();
+        assert_eq!(1, allocated.data.rows.allocated.len());
+        assert_eq!(1, allocated.data.columns.allocated.len());
+        assert_eq!("z", allocated.cell(1_048_575, 16_383).value());
+        assert_eq!(1, allocated.cells().count());
+
+        // Ids of rows and columns taken from before they were allocated remain usable
+        let pad = set_cell_values(&mut master, &sheet, &[(1_048_575, 0, "y")]);
+        let allocated = pad.active_sheet().unwrap();
+        assert_eq!(1, allocated.data.rows.allocated.len());
+        assert_eq!(2, allocated.data.columns.allocated.len());
+        assert_eq!(
+            vec![String::from("y"), String::from("z")],
+            allocated
+                .cells()
+                .map(|cell| cell.value().to_owned())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn version_index_reads_long_histories() {
+        let index: VersionIndex<u32, Version> = Default::default();
+        for version in 1..1000 {
+            index.write(1, version, version);
+            if version % 100 == 0 {
+                index.delete(2, version);
+            } else {
+                index.write(2, version * 10, version);
+            }
+        }
+        index.tx_commit();
+
+        assert_eq!(None, index.read(1, 0));
+        assert_eq!(None, index.read(3, 500));
+        for version in 1..1000 {
+            assert_eq!(Some(version), index.read(1, version));
+            let expected = (version % 100 != 0).then_some(version * 10);
+            assert_eq!(expected, index.read(2, version));
+        }
+        assert_eq!(Some(999), index.read(1, Version::MAX));
+    }
+
+    #[test]
+    fn row_and_column_indices_shared_by_versions() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let pad = set_cell_values(&mut master, &sheet, &[(500_000, 20, "a"), (7, 9, "b")]);
+        let allocated = pad.active_sheet().unwrap();
+        let cell = allocated.cell(500_000, 20);
+        assert_eq!(Some(500_000), allocated.data.rows.index_of(cell.row().id()));
+        assert_eq!(
+            Some(20),
+            allocated.data.columns.index_of(cell.column().id())
+        );
+        assert_eq!(Some(7), allocated.data.rows.index_of(sheet.row(7).id()));
+
+        // Changes that do not allocate rows or columns share the same indices
+        let pad = master
+            .update(WorkpadUpdate::SheetSetActiveCell {
+                sheet_id: sheet.id(),
+                row_id: cell.row().id(),
+                column_id: cell.column().id(),
+            })
+            .expect("Update should succeed");
+        let moved = pad.active_sheet().unwrap();
+        assert!(Arc::ptr_eq(&allocated.data.rows, &moved.data.rows));
+        assert!(Arc::ptr_eq(&allocated.data.columns, &moved.data.columns));
+        assert_eq!("a", moved.active_cell().unwrap().value());
+    }
+
+    #[test]
+    fn merged_cells() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let sheet_id = sheet.id();
+        let merge = |first: (usize, usize), last: (usize, usize)| {
+            let (first, last) = (sheet.cell(first.0, first.1), sheet.cell(last.0, last.1));
+            WorkpadUpdate::SheetMergeCells {
+                sheet_id,
+                first_row_id: first.row().id(),
+                first_column_id: first.column().id(),
+                last_row_id: last.row().id(),
+                last_column_id: last.column().id(),
+            }
+        };
+
+        // Corners may be given in any order
+        let pad = master.update(merge((3, 2), (1, 1))).unwrap();
+        let sheet = pad.active_sheet().unwrap();
+        let merged = sheet.merged_range(2, 2).unwrap();
+        assert_eq!((1..=3, 1..=2), (merged.rows(), merged.columns()));
+        assert!(sheet.merged_range(0, 1).is_none());
+        assert_eq!(1, sheet.merged_ranges().count());
+
+        // Single cells and overlapping blocks cannot be merged
+        for update in [merge((5, 5), (5, 5)), merge((3, 2), (4, 4))] {
+            let err = master.update(update).unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::InvalidMerge));
+        }
+        master.update(merge((4, 0), (4, 4))).unwrap();
+
+        // Unmerging any cell of a block unmerges the whole block
+        let pad = master
+            .update(WorkpadUpdate::SheetUnmergeCells {
+                sheet_id,
+                row_id: sheet.row(2).id(),
+                column_id: sheet.column(1).id(),
+            })
+            .unwrap();
+        let sheet = pad.active_sheet().unwrap();
+        assert!(sheet.merged_range(2, 2).is_none());
+        let merged = sheet.merged_range(4, 3).unwrap();
+        assert_eq!((4..=4, 0..=4), (merged.rows(), merged.columns()));
+
+        // Unmerging a cell that is not merged fails without creating a version
+        let version = pad.version().version();
+        let err = master
+            .update(WorkpadUpdate::SheetUnmergeCells {
+                sheet_id,
+                row_id: sheet.row(2).id(),
+                column_id: sheet.column(1).id(),
+            })
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::NotMerged));
+        assert_eq!(version, master.active_version().version().version());
+    }
+
+    #[test]
+    fn compact_history_prunes_dead_versions() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+
+        // Versions 1 to 3, then 4 and 5 based on 1 (making 2 and 3 dead) and step back to 4
+        set_cell_values(&mut master, &sheet, &[(0, 0, "A")]);
+        set_cell_values(&mut master, &sheet, &[(0, 0, "B"), (5, 5, "dead")]);
+        set_cell_values(&mut master, &sheet, &[(0, 0, "C")]);
+        master
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .expect("Update should succeed");
+        set_cell_values(&mut master, &sheet, &[(0, 0, "D")]);
+        set_cell_values(&mut master, &sheet, &[(0, 0, "E")]);
+        master
+            .update(WorkpadUpdate::SetVersion { version: 4 })
+            .expect("Update should succeed");
+        let cells_before = master.data.cells_idx.index.read().unwrap().index.len();
+
+        let pad = master.compact_history(None);
+        assert_eq!(4, master.data.history.read().unwrap().len());
+        assert!(master.data.cells_idx.index.read().unwrap().index.len() < cells_before);
+
+        // Versions 0, 1, 4 and 5 are now 0, 1, 2 and 3
+        assert!(ver_is(pad.version(), 2, "Set Sheet Cell Value"));
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("D", sheet.cell(0, 0).value());
+        assert!(sheet.cell(5, 5).value().is_empty());
+        assert_eq!(1, sheet.non_empty_cells().count());
+
+        let mut back_vers = pad.backward_versions();
+        assert_next_ver(&mut back_vers, 1, "Set Sheet Cell Value");
+        assert_next_ver(&mut back_vers, 0, "New Workpad");
+        assert!(back_vers.next().is_none());
+        let mut forth_vers = pad.forward_versions();
+        assert_next_ver(&mut forth_vers, 3, "Set Sheet Cell Value");
+        assert!(forth_vers.next().is_none());
+
+        let pad = master
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .expect("Update should succeed");
+        assert_eq!("A", pad.active_sheet().unwrap().cell(0, 0).value());
+        let pad = master
+            .update(WorkpadUpdate::SetVersion { version: 3 })
+            .expect("Update should succeed");
+        assert_eq!("E", pad.active_sheet().unwrap().cell(0, 0).value());
+
+        // Nothing more to remove
+        master.compact_history(None);
+        assert_eq!(4, master.data.history.read().unwrap().len());
+
+        // Branching again sees only the data of the base version
+        master
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .expect("Update should succeed");
+        let pad = set_cell_values(&mut master, &sheet, &[(1, 1, "F")]);
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("A", sheet.cell(0, 0).value());
+        assert_eq!(2, sheet.non_empty_cells().count());
+    }
+
+    #[test]
+    fn compact_history_keeps_redo_versions() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+
+        // Versions 1 to 3, then 4 based on 1 and step back to 2 (from which Redo leads to 3)
+        set_cell_values(&mut master, &sheet, &[(0, 0, "A")]);
+        set_cell_values(&mut master, &sheet, &[(0, 0, "B")]);
+        set_cell_values(&mut master, &sheet, &[(0, 0, "C")]);
+        master
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .expect("Update should succeed");
+        set_cell_values(&mut master, &sheet, &[(0, 0, "D")]);
+        master
+            .update(WorkpadUpdate::SetVersion { version: 2 })
+            .expect("Update should succeed");
+
+        let pad = master.compact_history(None);
+        assert_eq!(5, master.data.history.read().unwrap().len());
+        assert!(ver_is(pad.version(), 2, "Set Sheet Cell Value"));
+        let mut forth_vers = pad.forward_versions();
+        assert_next_ver(&mut forth_vers, 3, "Set Sheet Cell Value");
+        assert!(forth_vers.next().is_none());
+
+        let pad = master
+            .update(WorkpadUpdate::SetVersion { version: 3 })
+            .expect("Update should succeed");
+        assert_eq!("C", pad.active_sheet().unwrap().cell(0, 0).value());
+    }
+
+    #[test]
+    fn compact_history_squashes_old_versions() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        for value in ["A", "B", "C", "D", "E"] {
+            set_cell_values(&mut master, &sheet, &[(0, 0, value)]);
+        }
+        set_cell_values(&mut master, &sheet, &[(1, 0, "x")]);
+        set_cell_values(&mut master, &sheet, &[(1, 0, "")]);
+
+        let pad = master.compact_history(Some(3));
+        assert_eq!(3, master.data.history.read().unwrap().len());
+        assert!(ver_is(pad.version(), 2, "Set Sheet Cell Value"));
+        let mut back_vers = pad.backward_versions();
+        assert_next_ver(&mut back_vers, 1, "Set Sheet Cell Value");
+        assert_next_ver(&mut back_vers, 0, "Set Sheet Cell Value");
+        assert!(back_vers.next().is_none());
+
+        // The baseline holds the changes made before it
+        let pad = master
+            .update(WorkpadUpdate::SetVersion { version: 0 })
+            .expect("Update should succeed");
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("E", sheet.cell(0, 0).value());
+        assert!(sheet.cell(1, 0).value().is_empty());
+        assert_eq!(3, pad.sheets().count());
+        assert!(pad.backward_versions().next().is_none());
+
+        // New versions can be added after compaction
+        let pad = set_cell_values(&mut master, &sheet, &[(2, 0, "F")]);
+        assert!(ver_is(pad.version(), 3, "Set Sheet Cell Value"));
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("E", sheet.cell(0, 0).value());
+        assert_eq!("F", sheet.cell(2, 0).value());
+        let mut back_vers = pad.backward_versions();
+        assert_next_ver(&mut back_vers, 0, "Set Sheet Cell Value");
+        assert!(back_vers.next().is_none());
+    }
+
+    #[test]
+    fn concurrent_writers_are_serialized() {
+        const THREADS: usize = 8;
+        const UPDATES: usize = 50;
+
+        let master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+
+        let writers: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let mut master = master.clone();
+                let sheet = sheet.clone();
+                std::thread::spawn(move || {
+                    for update in 0..UPDATES {
+                        let value = format!("{thread}-{update}");
+                        set_cell_values(&mut master, &sheet, &[(thread, update, &value)]);
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().expect("Writer should not panic");
+        }
+
+        // Each update is based on the one before so the latest version has every value
+        let pad = master.active_version();
+        assert_eq!(THREADS * UPDATES, pad.version().version() as usize);
+        assert_eq!(THREADS * UPDATES, pad.backward_versions().count());
+        let sheet = pad.active_sheet().unwrap();
+        for thread in 0..THREADS {
+            for update in 0..UPDATES {
+                let value = format!("{thread}-{update}");
+                assert_eq!(value, sheet.cell(thread, update).value());
+            }
+        }
+    }
+
+    #[test]
+    fn readers_see_stable_versions_during_writes() {
+        const CELLS: usize = 10;
+
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        fn set_all(master: &mut WorkpadMaster, sheet: &Sheet, value: &str) -> Workpad {
+            let values: Vec<_> = (0..CELLS).map(|cl| (0, cl, value)).collect();
+            set_cell_values(master, sheet, &values)
+        }
+        let snapshot = set_all(&mut master, &sheet, "start");
+
+        let writers: Vec<_> = (0..4)
+            .map(|thread| {
+                let mut master = master.clone();
+                let sheet = sheet.clone();
+                std::thread::spawn(move || {
+                    for update in 0..50 {
+                        set_all(&mut master, &sheet, &format!("{thread}-{update}"));
+                    }
+                })
+            })
+            .collect();
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let master = master.clone();
+                let snapshot = snapshot.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..200 {
+                        // An existing version never changes
+                        let sheet = snapshot.active_sheet().unwrap();
+                        assert!((0..CELLS).all(|cl| sheet.cell(0, cl).value() == "start"));
+
+                        // All the changes of an update are seen together
+                        let sheet = master.active_version().active_sheet().unwrap();
+                        let first = sheet.cell(0, 0).value().to_owned();
+                        assert!((0..CELLS).all(|cl| sheet.cell(0, cl).value() == first));
+                    }
+                })
+            })
+            .collect();
+
+        for thread in writers.into_iter().chain(readers) {
+            thread.join().expect("Thread should not panic");
+        }
+        assert_eq!(201, master.active_version().version().version());
+    }
+
+    /// Sets the values of cells of a sheet, given by (row, column) index, in one update
+    pub(crate) fn set_cell_values(
+        master: &mut WorkpadMaster,
+        sheet: &Sheet,
+        values: &[(usize, usize, &str)],
+    ) -> Workpad {
+        let updates = values
+            .iter()
+            .map(|(rw, cl, value)| {
+                let cell = sheet.cell(*rw, *cl);
+                WorkpadUpdate::SheetSetCellValue {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    value: String::from(*value),
+                }
+            })
+            .collect();
+        master
+            .update(WorkpadUpdate::Multi(updates))
+            .expect("Update should succeed")
+    }
+
     fn assert_next_ver(
-        iter: &mut impl Iterator<Item = (Version, String)>,
+        iter: &mut impl Iterator<Item = VersionInfo>,
         expected_version: Version,
         expected_desc: &str,
     ) {
@@ -2541,8 +5197,8 @@ mod tests {
         };
     }
 
-    fn ver_is(ver: (Version, String), expected_version: Version, expected_desc: &str) -> bool {
-        ver.0 == expected_version && ver.1 == expected_desc
+    fn ver_is(ver: VersionInfo, expected_version: Version, expected_desc: &str) -> bool {
+        ver.version() == expected_version && ver.description() == expected_desc
     }
 
     fn assert_next_sheet(iter: &mut impl Iterator<Item = Sheet>, expected_name: &str) {
@@ -0,0 +1,1007 @@
+use std::{borrow::Cow, fmt};
+
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+/// How the value of a cell is displayed when it is a number, date or time.  The value itself
+/// is unchanged.  Values that a format does not apply to are displayed as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NumberFormat {
+    /// A number with a fixed number of decimal places, optionally grouping thousands
+    Number { decimals: u8, thousands: bool },
+    /// An amount of money with a currency symbol before it, grouping thousands
+    Currency { symbol: String, decimals: u8 },
+    /// A number multiplied by 100 and shown with a percent sign
+    Percentage { decimals: u8 },
+    /// A number in scientific notation, such as `1.23E+04`
+    Scientific { decimals: u8 },
+    /// A date and/or time shown using a pattern such as `yyyy-mm-dd hh:mm`.  Dates are
+    /// values written as `2024-01-31`, `13:45:30` or `2024-01-31 13:45`, or numbers counting
+    /// days (and fractions of a day) since 30 December 1899 as in other spreadsheets.
+    DateTime(String),
+    /// A format string in the usual spreadsheet style, such as `#,##0.00;(#,##0.00)`
+    Custom(String),
+}
+
+impl NumberFormat {
+    /// Returns the format string equivalent to this format
+    pub fn code(&self) -> Cow<'_, str> {
+        let decimals = |decimals: u8| match decimals {
+            0 => String::new(),
+            n => format!(".{}", "0".repeat(n as usize)),
+        };
+        match self {
+            NumberFormat::Number {
+                decimals: n,
+                thousands,
+            } => {
+                let integer = if *thousands { "#,##0" } else { "0" };
+                Cow::Owned(format!("{integer}{}", decimals(*n)))
+            }
+            NumberFormat::Currency {
+                symbol,
+                decimals: n,
+            } => {
+                // Escaped so that no part of the symbol is taken as a placeholder
+                let symbol: String = symbol.chars().flat_map(|c| ['\\', c]).collect();
+                Cow::Owned(format!("{symbol}#,##0{}", decimals(*n)))
+            }
+            NumberFormat::Percentage { decimals: n } => Cow::Owned(format!("0{}%", decimals(*n))),
+            NumberFormat::Scientific { decimals: n } => {
+                Cow::Owned(format!("0{}E+00", decimals(*n)))
+            }
+            NumberFormat::DateTime(pattern) => Cow::Borrowed(pattern),
+            NumberFormat::Custom(code) => Cow::Borrowed(code),
+        }
+    }
+
+    /// Returns an error describing why this format cannot be used, if it cannot
+    pub fn validate(&self) -> Result<(), NumberFormatError> {
+        FormatCode::parse(&self.code()).map(|_| ())
+    }
+
+    /// Returns a value as displayed with this format.  Values that the format does not apply
+    /// to, and all values if the format is not valid, are returned unchanged.
+    pub fn apply<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        match FormatCode::parse(&self.code()) {
+            Ok(code) => code.apply(value).map_or(Cow::Borrowed(value), Cow::Owned),
+            Err(_) => Cow::Borrowed(value),
+        }
+    }
+}
+
+/// The reason a [`NumberFormat`] cannot be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberFormatError {
+    Empty,
+    TooManySections,
+    UnterminatedQuote,
+    UnterminatedBracket,
+    MissingCharacter(char),
+}
+
+impl fmt::Display for NumberFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str(&t!("NumberFormatError.Empty")),
+            Self::TooManySections => f.write_str(&t!("NumberFormatError.TooManySections")),
+            Self::UnterminatedQuote => f.write_str(&t!("NumberFormatError.UnterminatedQuote")),
+            Self::UnterminatedBracket => f.write_str(&t!("NumberFormatError.UnterminatedBracket")),
+            Self::MissingCharacter(c) => f.write_str(
+                &t!("NumberFormatError.MissingCharacter").replace("{char}", &c.to_string()),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NumberFormatError {}
+
+/// The most sections a format string can have: for positive numbers, negative numbers, zero
+/// and text
+const MAX_SECTIONS: usize = 4;
+
+/// A parsed format string
+#[derive(Debug)]
+struct FormatCode {
+    sections: Vec<Section>,
+}
+
+/// The part of a format string used for some values
+#[derive(Debug, Default)]
+struct Section {
+    tokens: Vec<Token>,
+    /// Group the thousands of the integer part
+    thousands: bool,
+    /// The number of times the number is divided by 1000 (by commas after the digits)
+    scale: i32,
+    /// The number of times the number is multiplied by 100 (by percent signs)
+    percent: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    /// A place for a digit: `0` shows zeros, `#` shows nothing and `?` shows a space
+    Digit(char),
+    Point,
+    /// Separates thousands when between digits, otherwise scales the number.  Resolved once
+    /// the whole section is known.
+    Comma,
+    /// The exponent of scientific notation, always showing its sign if `plus`
+    Exponent {
+        plus: bool,
+    },
+    /// The text of the value (`@`)
+    Text,
+    /// The value shown as it is (`General`)
+    General,
+    Date(DatePart),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DatePart {
+    Year(usize),
+    /// A month or, after hours or before seconds, minutes.  Resolved once the whole section
+    /// is known.
+    MonthOrMinute(usize),
+    Month(usize),
+    Day(usize),
+    Hour(usize),
+    Minute(usize),
+    Second(usize),
+    /// `AM/PM` or, if short, `A/P`, in the case given
+    AmPm {
+        short: bool,
+        lower: bool,
+    },
+}
+
+impl FormatCode {
+    fn parse(code: &str) -> Result<Self, NumberFormatError> {
+        if code.is_empty() {
+            return Err(NumberFormatError::Empty);
+        }
+
+        let mut sections = vec![Section::default()];
+        let mut chars = code.chars().peekable();
+        while let Some(c) = chars.next() {
+            let section = sections.last_mut().unwrap();
+            let tokens = &mut section.tokens;
+            match c {
+                ';' => {
+                    if sections.len() == MAX_SECTIONS {
+                        return Err(NumberFormatError::TooManySections);
+                    }
+                    sections.push(Section::default());
+                }
+                '"' => {
+                    let mut literal = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => literal.push(c),
+                            None => return Err(NumberFormatError::UnterminatedQuote),
+                        }
+                    }
+                    tokens.push(Token::Literal(literal));
+                }
+                '\\' => match chars.next() {
+                    Some(c) => tokens.push(Token::Literal(c.to_string())),
+                    None => return Err(NumberFormatError::MissingCharacter(c)),
+                },
+                // Space the width of the next character, and repeat the next character to fill
+                // the cell (which is not supported, so it is dropped)
+                '_' | '*' => match chars.next() {
+                    Some(_) if c == '_' => tokens.push(Token::Literal(" ".to_owned())),
+                    Some(_) => (),
+                    None => return Err(NumberFormatError::MissingCharacter(c)),
+                },
+                '[' => {
+                    let mut content = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => content.push(c),
+                            None => return Err(NumberFormatError::UnterminatedBracket),
+                        }
+                    }
+                    // Only currency symbols (as in `[$€-407]`) are shown; colours and
+                    // conditions are not supported
+                    if let Some(currency) = content.strip_prefix('$') {
+                        let symbol = currency.split('-').next().unwrap_or_default();
+                        tokens.push(Token::Literal(symbol.to_owned()));
+                    }
+                }
+                '0' | '#' | '?' => tokens.push(Token::Digit(c)),
+                '.' => tokens.push(Token::Point),
+                ',' => tokens.push(Token::Comma),
+                '%' => {
+                    section.percent += 1;
+                    tokens.push(Token::Literal("%".to_owned()));
+                }
+                'E' | 'e' if matches!(chars.peek(), Some('+' | '-')) => {
+                    let plus = chars.next() == Some('+');
+                    tokens.push(Token::Exponent { plus });
+                }
+                '@' => tokens.push(Token::Text),
+                'G' | 'g' if starts_with_ignore_case(&chars, "eneral") => {
+                    chars.nth("eneral".len() - 1);
+                    tokens.push(Token::General);
+                }
+                'A' | 'a' if starts_with_ignore_case(&chars, "m/pm") => {
+                    chars.nth("m/pm".len() - 1);
+                    let lower = c == 'a';
+                    tokens.push(Token::Date(DatePart::AmPm {
+                        short: false,
+                        lower,
+                    }));
+                }
+                'A' | 'a' if starts_with_ignore_case(&chars, "/p") => {
+                    chars.nth("/p".len() - 1);
+                    let lower = c == 'a';
+                    tokens.push(Token::Date(DatePart::AmPm { short: true, lower }));
+                }
+                'Y' | 'y' => tokens.push(Token::Date(DatePart::Year(run(&mut chars, c)))),
+                'M' | 'm' => tokens.push(Token::Date(DatePart::MonthOrMinute(run(&mut chars, c)))),
+                'D' | 'd' => tokens.push(Token::Date(DatePart::Day(run(&mut chars, c)))),
+                'H' | 'h' => tokens.push(Token::Date(DatePart::Hour(run(&mut chars, c)))),
+                'S' | 's' => tokens.push(Token::Date(DatePart::Second(run(&mut chars, c)))),
+                c => tokens.push(Token::Literal(c.to_string())),
+            }
+        }
+
+        for section in &mut sections {
+            section.resolve();
+        }
+        Ok(Self { sections })
+    }
+
+    /// Returns a value as displayed with this format, or `None` if the format does not apply
+    fn apply(&self, value: &str) -> Option<String> {
+        // A last section for text (with `@`) is used only for text
+        let (numbers, text) = match self.sections.split_last() {
+            Some((last, rest)) if self.sections.len() == MAX_SECTIONS || last.is_text() => {
+                (rest, Some(last))
+            }
+            _ => (&self.sections[..], None),
+        };
+
+        let trimmed = value.trim();
+        match trimmed.parse::<f64>().ok().filter(|n| n.is_finite()) {
+            Some(number) => {
+                let (section, minus) = match numbers {
+                    [] => return None,
+                    [only] => (only, number < 0.0),
+                    [positive, negative] => {
+                        if number < 0.0 {
+                            (negative, false)
+                        } else {
+                            (positive, false)
+                        }
+                    }
+                    [positive, negative, zero, ..] => {
+                        if number > 0.0 {
+                            (positive, false)
+                        } else if number < 0.0 {
+                            (negative, false)
+                        } else {
+                            (zero, false)
+                        }
+                    }
+                };
+                if section.is_date() {
+                    DateTime::from_serial(number).map(|date_time| section.format_date(date_time))
+                } else {
+                    Some(section.format_number(number, minus, trimmed))
+                }
+            }
+            None => {
+                // Dates written as text are shown with the first section for dates
+                let date_section = numbers.iter().find(|section| section.is_date());
+                if let (Some(section), Some(date_time)) = (date_section, DateTime::parse(trimmed)) {
+                    Some(section.format_date(date_time))
+                } else {
+                    text.map(|section| section.format_text(value))
+                }
+            }
+        }
+    }
+}
+
+/// Returns the number of times a character is repeated, ignoring case, consuming the
+/// repetitions after the first
+fn run(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, c: char) -> usize {
+    let mut count = 1;
+    while chars
+        .next_if(|next| next.eq_ignore_ascii_case(&c))
+        .is_some()
+    {
+        count += 1;
+    }
+    count
+}
+
+/// Returns true if the characters to come start with `text`, ignoring case
+fn starts_with_ignore_case(chars: &std::iter::Peekable<std::str::Chars<'_>>, text: &str) -> bool {
+    let mut chars = chars.clone();
+    text.chars()
+        .all(|t| chars.next().map_or(false, |c| c.eq_ignore_ascii_case(&t)))
+}
+
+impl Section {
+    /// Resolves the tokens whose meaning depends on those around them
+    fn resolve(&mut self) {
+        if self.is_date() {
+            // `m` after hours or before seconds means minutes
+            let parts: Vec<(usize, DatePart)> = self
+                .tokens
+                .iter()
+                .enumerate()
+                .filter_map(|(index, token)| match token {
+                    Token::Date(part) => Some((index, *part)),
+                    _ => None,
+                })
+                .collect();
+            for (i, (index, part)) in parts.iter().enumerate() {
+                if let DatePart::MonthOrMinute(count) = part {
+                    let after_hour = i > 0 && matches!(parts[i - 1].1, DatePart::Hour(_));
+                    let before_second = matches!(parts.get(i + 1), Some((_, DatePart::Second(_))));
+                    self.tokens[*index] = if *count <= 2 && (after_hour || before_second) {
+                        Token::Date(DatePart::Minute(*count))
+                    } else {
+                        Token::Date(DatePart::Month(*count))
+                    };
+                }
+            }
+            // Digits and points are not supported in dates so are shown as they are
+            for token in &mut self.tokens {
+                *token = match token {
+                    Token::Digit(c) => Token::Literal(c.to_string()),
+                    Token::Point => Token::Literal(".".to_owned()),
+                    Token::Comma => Token::Literal(",".to_owned()),
+                    _ => continue,
+                };
+            }
+            return;
+        }
+
+        // Only the first point is a decimal point
+        let mut seen_point = false;
+        for token in &mut self.tokens {
+            if *token == Token::Point {
+                if seen_point {
+                    *token = Token::Literal(".".to_owned());
+                }
+                seen_point = true;
+            }
+        }
+
+        // Commas between digits separate thousands, commas just after the digits of the
+        // integer part scale the number and others are shown as they are
+        let is_digit = |token: Option<&Token>| matches!(token, Some(Token::Digit(_)));
+        for index in 0..self.tokens.len() {
+            if self.tokens[index] != Token::Comma {
+                continue;
+            }
+            let before = (0..index)
+                .rev()
+                .map(|i| &self.tokens[i])
+                .find(|token| **token != Token::Comma);
+            let after = self.tokens[index + 1..]
+                .iter()
+                .find(|token| **token != Token::Comma);
+            let in_integer = !self.tokens[..index]
+                .iter()
+                .any(|token| matches!(token, Token::Point | Token::Exponent { .. }));
+            self.tokens[index] = if is_digit(before) && is_digit(after) {
+                self.thousands = true;
+                Token::Literal(String::new())
+            } else if is_digit(before) && in_integer {
+                self.scale += 1;
+                Token::Literal(String::new())
+            } else {
+                Token::Literal(",".to_owned())
+            };
+        }
+        self.tokens
+            .retain(|token| !matches!(token, Token::Literal(l) if l.is_empty()));
+    }
+
+    fn is_date(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, Token::Date(_)))
+    }
+
+    fn is_text(&self) -> bool {
+        self.tokens.contains(&Token::Text)
+            && !self
+                .tokens
+                .iter()
+                .any(|token| matches!(token, Token::Digit(_) | Token::Date(_)))
+    }
+
+    fn format_text(&self, value: &str) -> String {
+        let mut result = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(literal) => result.push_str(literal),
+                Token::Text | Token::General => result.push_str(value),
+                _ => (),
+            }
+        }
+        result
+    }
+
+    /// Formats a number, preceded by a minus sign if `minus` and it is not shown as zero.
+    /// The number as written is used for `General`.
+    fn format_number(&self, number: f64, minus: bool, written: &str) -> String {
+        let tokens = &self.tokens;
+        if tokens.contains(&Token::General) {
+            let sign = if minus { "-" } else { "" };
+            return format!(
+                "{sign}{}",
+                self.format_text(written.trim_start_matches('-'))
+            );
+        }
+
+        let point = tokens.iter().position(|token| *token == Token::Point);
+        let exponent = tokens
+            .iter()
+            .position(|token| matches!(token, Token::Exponent { .. }));
+        let integer_end = point.or(exponent).unwrap_or(tokens.len());
+        let fraction_end = exponent.unwrap_or(tokens.len());
+        let places = |tokens: &[Token]| -> Vec<char> {
+            tokens
+                .iter()
+                .filter_map(|token| match token {
+                    Token::Digit(c) => Some(*c),
+                    _ => None,
+                })
+                .collect()
+        };
+        let integer_places = places(&tokens[..integer_end]);
+        let fraction_places = match point {
+            Some(point) => places(&tokens[point + 1..fraction_end]),
+            None => vec![],
+        };
+
+        let number = number.abs() * 100f64.powi(self.percent) / 1000f64.powi(self.scale);
+        let precision = fraction_places.len();
+        let (digits, exponent_value) = if exponent.is_some() {
+            let integer_digits = integer_places.len().max(1) as i32;
+            let mut exponent_value = if number == 0.0 {
+                0
+            } else {
+                number.log10().floor() as i32 - (integer_digits - 1)
+            };
+            let mut digits = fixed(number / 10f64.powi(exponent_value), precision);
+            // Rounding may carry into another digit
+            if digits.split('.').next().unwrap().len() > integer_digits as usize {
+                exponent_value += 1;
+                digits = fixed(number / 10f64.powi(exponent_value), precision);
+            }
+            (digits, Some(exponent_value))
+        } else {
+            (fixed(number, precision), None)
+        };
+        let (integer_digits, fraction_digits) = digits.split_once('.').unwrap_or((&digits, ""));
+        let is_zero = digits.chars().all(|c| c == '0' || c == '.');
+        let integer_digits = if integer_digits == "0" {
+            ""
+        } else {
+            integer_digits
+        };
+
+        let mut result = String::new();
+        if minus && !is_zero {
+            result.push('-');
+        }
+        result.push_str(&self.format_integer(&tokens[..integer_end], integer_digits));
+        if let Some(point) = point {
+            result.push('.');
+            result.push_str(&format_fraction(
+                &tokens[point + 1..fraction_end],
+                fraction_digits,
+            ));
+        }
+        if let (Some(exponent), Some(exponent_value)) = (exponent, exponent_value) {
+            let Token::Exponent { plus } = tokens[exponent] else {
+                unreachable!()
+            };
+            result.push('E');
+            if exponent_value < 0 {
+                result.push('-');
+            } else if plus {
+                result.push('+');
+            }
+            let exponent_tokens = &tokens[exponent + 1..];
+            let width = places(exponent_tokens).len().max(1);
+            result.push_str(&format!("{:0width$}", exponent_value.abs()));
+            for token in exponent_tokens {
+                if let Token::Literal(literal) = token {
+                    result.push_str(literal);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Formats the integer part of a number: its digits in the places for digits with the
+    /// literals between them
+    fn format_integer(&self, tokens: &[Token], digits: &str) -> String {
+        let places = tokens
+            .iter()
+            .filter(|token| matches!(token, Token::Digit(_)))
+            .count();
+        if places == 0 {
+            let mut result = literals(tokens);
+            result.push_str(digits);
+            return result;
+        }
+
+        if self.thousands {
+            // All the digits are shown together, where the first place is
+            let first_zero = tokens
+                .iter()
+                .filter_map(|token| match token {
+                    Token::Digit(c) => Some(*c),
+                    _ => None,
+                })
+                .position(|c| c == '0');
+            let width = first_zero.map_or(0, |first_zero| places - first_zero);
+            let digits = format!("{digits:0>width$}");
+            let mut result = String::new();
+            let mut placed = false;
+            for token in tokens {
+                match token {
+                    Token::Digit(_) if !placed => {
+                        result.push_str(&group_thousands(&digits));
+                        placed = true;
+                    }
+                    Token::Literal(literal) => result.push_str(literal),
+                    _ => (),
+                }
+            }
+            return result;
+        }
+
+        // Digits fill the places from the right with any digits left over in the first place
+        let mut digits: Vec<char> = digits.chars().collect();
+        let mut parts: Vec<String> = vec![];
+        let mut remaining = places;
+        for token in tokens.iter().rev() {
+            match token {
+                Token::Digit(c) => {
+                    remaining -= 1;
+                    let part = if remaining == 0 {
+                        let rest: String = digits.drain(..).collect();
+                        match (rest.is_empty(), c) {
+                            (false, _) => rest,
+                            (true, '0') => "0".to_owned(),
+                            (true, '?') => " ".to_owned(),
+                            (true, _) => String::new(),
+                        }
+                    } else {
+                        match (digits.pop(), c) {
+                            (Some(digit), _) => digit.to_string(),
+                            (None, '0') => "0".to_owned(),
+                            (None, '?') => " ".to_owned(),
+                            (None, _) => String::new(),
+                        }
+                    };
+                    parts.push(part);
+                }
+                Token::Literal(literal) => parts.push(literal.clone()),
+                _ => (),
+            }
+        }
+        parts.into_iter().rev().collect()
+    }
+
+    fn format_date(&self, date_time: DateTime) -> String {
+        let twelve_hour = self
+            .tokens
+            .iter()
+            .any(|token| matches!(token, Token::Date(DatePart::AmPm { .. })));
+        let (year, month, day) = date_time.date();
+        let (hour, minute, second) = date_time.time();
+        let hour_shown = if twelve_hour {
+            match hour % 12 {
+                0 => 12,
+                hour => hour,
+            }
+        } else {
+            hour
+        };
+
+        let mut result = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(literal) => result.push_str(literal),
+                Token::Date(part) => {
+                    let text = match *part {
+                        DatePart::Year(count) if count <= 2 => format!("{:02}", year % 100),
+                        DatePart::Year(_) => format!("{year:04}"),
+                        DatePart::Month(1) => month.to_string(),
+                        DatePart::Month(2) => format!("{month:02}"),
+                        DatePart::Month(3) => MONTHS[month as usize - 1][..3].to_owned(),
+                        DatePart::Month(4) => MONTHS[month as usize - 1].to_owned(),
+                        DatePart::Month(_) => MONTHS[month as usize - 1][..1].to_owned(),
+                        DatePart::Day(1) => day.to_string(),
+                        DatePart::Day(2) => format!("{day:02}"),
+                        DatePart::Day(3) => WEEKDAYS[date_time.weekday()][..3].to_owned(),
+                        DatePart::Day(_) => WEEKDAYS[date_time.weekday()].to_owned(),
+                        DatePart::Hour(1) => hour_shown.to_string(),
+                        DatePart::Hour(_) => format!("{hour_shown:02}"),
+                        DatePart::Minute(1) => minute.to_string(),
+                        DatePart::Minute(_) => format!("{minute:02}"),
+                        DatePart::Second(1) => second.to_string(),
+                        DatePart::Second(_) => format!("{second:02}"),
+                        DatePart::AmPm { short, lower } => {
+                            let text = match (hour < 12, short) {
+                                (true, false) => "AM",
+                                (false, false) => "PM",
+                                (true, true) => "A",
+                                (false, true) => "P",
+                            };
+                            if lower {
+                                text.to_lowercase()
+                            } else {
+                                text.to_owned()
+                            }
+                        }
+                        DatePart::MonthOrMinute(_) => unreachable!("Resolved when parsed"),
+                    };
+                    result.push_str(&text);
+                }
+                Token::Text | Token::General => (),
+                _ => (),
+            }
+        }
+        result
+    }
+}
+
+/// Formats the fraction part of a number: its digits in the places for digits from the
+/// left, with trailing zeros hidden in `#` places and replaced by spaces in `?` places
+fn format_fraction(tokens: &[Token], digits: &str) -> String {
+    let mut digits = digits.chars();
+    let mut parts: Vec<(Option<char>, String)> = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Digit(c) => Some((Some(*c), digits.next().unwrap_or('0').to_string())),
+            Token::Literal(literal) => Some((None, literal.clone())),
+            _ => None,
+        })
+        .collect();
+
+    for (place, part) in parts.iter_mut().rev() {
+        match place {
+            None => continue,
+            Some(c) if part.as_str() != "0" || *c == '0' => break,
+            Some('?') => *part = " ".to_owned(),
+            Some(_) => part.clear(),
+        }
+    }
+    parts.into_iter().map(|(_, part)| part).collect()
+}
+
+/// Returns the literals amongst some tokens
+fn literals(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Literal(literal) => Some(literal.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Separates the thousands of the digits of a number with commas
+fn group_thousands(digits: &str) -> String {
+    let first = match digits.len() % 3 {
+        0 => 3.min(digits.len()),
+        n => n,
+    };
+    let (first, rest) = digits.split_at(first);
+    let mut result = first.to_owned();
+    for group in rest.as_bytes().chunks(3) {
+        result.push(',');
+        result.push_str(std::str::from_utf8(group).unwrap());
+    }
+    result
+}
+
+/// Formats a number with a number of decimal places, rounding halves away from zero as
+/// spreadsheets do (rather than to even)
+fn fixed(number: f64, decimals: usize) -> String {
+    let factor = 10f64.powi(decimals as i32);
+    let rounded = (number * factor).round() / factor;
+    let rounded = if rounded.is_finite() { rounded } else { number };
+    format!("{rounded:.decimals$}")
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A date and time as the days since 1 January 1970 and the seconds since midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct DateTime {
+    days: i64,
+    seconds: i64,
+}
+
+impl DateTime {
+    /// Returns the date and time of a serial number: the days (and fraction of a day) since
+    /// 30 December 1899.  Negative numbers are not dates.
+    pub(crate) fn from_serial(serial: f64) -> Option<Self> {
+        if !(0.0..3_000_000.0).contains(&serial) {
+            return None;
+        }
+        let seconds = (serial * SECONDS_PER_DAY as f64).round() as i64;
+        Some(Self {
+            days: days_from_civil(1899, 12, 30) + seconds / SECONDS_PER_DAY,
+            seconds: seconds % SECONDS_PER_DAY,
+        })
+    }
+
+    /// Parses a date (`2024-01-31`), a time (`13:45` or `13:45:30`) or both separated by a
+    /// space or a `T`.  Times alone are on 30 December 1899, as with serial numbers.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let (date, time) = match text.split_once([' ', 'T']) {
+            Some((date, time)) => (Some(date), Some(time)),
+            None if text.contains(':') => (None, Some(text)),
+            None => (Some(text), None),
+        };
+
+        let days = match date {
+            Some(date) => {
+                let mut parts = date.splitn(3, '-');
+                let year: i64 = parse_part(parts.next(), 4)?;
+                let month: u32 = parse_part(parts.next(), 2)?;
+                let day: u32 = parse_part(parts.next(), 2)?;
+                if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+                    return None;
+                }
+                days_from_civil(year, month, day)
+            }
+            None => days_from_civil(1899, 12, 30),
+        };
+
+        let seconds = match time {
+            Some(time) => {
+                let mut parts = time.splitn(3, ':');
+                let hour: i64 = parse_part(parts.next(), 2)?;
+                let minute: i64 = parse_part(parts.next(), 2)?;
+                let second: i64 = match parts.next() {
+                    Some(second) => parse_part(Some(second), 2)?,
+                    None => 0,
+                };
+                if hour > 23 || minute > 59 || second > 59 {
+                    return None;
+                }
+                (hour * 60 + minute) * 60 + second
+            }
+            None => 0,
+        };
+        Some(Self { days, seconds })
+    }
+
+    /// Returns the (year, month, day) of the date
+    fn date(&self) -> (i64, u32, u32) {
+        civil_from_days(self.days)
+    }
+
+    /// Returns the (hour, minute, second) of the time
+    fn time(&self) -> (i64, i64, i64) {
+        (
+            self.seconds / 3600,
+            self.seconds / 60 % 60,
+            self.seconds % 60,
+        )
+    }
+
+    /// Returns the day of the week where Sunday is 0
+    fn weekday(&self) -> usize {
+        // 1 January 1970 was a Thursday
+        (self.days + 4).rem_euclid(7) as usize
+    }
+}
+
+/// Parses a part of a date or time that is all digits, up to `max_len` of them
+fn parse_part<T: std::str::FromStr>(part: Option<&str>, max_len: usize) -> Option<T> {
+    let part = part?;
+    if part.is_empty() || part.len() > max_len || !part.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    part.parse().ok()
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the days since 1 January 1970 of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Years are counted from March so that leap days fall at the end
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the (year, month, day) of a number of days since 1 January 1970.  The inverse of
+/// [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(code: &str, value: &str) -> String {
+        NumberFormat::Custom(code.to_owned())
+            .apply(value)
+            .into_owned()
+    }
+
+    #[test]
+    fn presets() {
+        let number = NumberFormat::Number {
+            decimals: 2,
+            thousands: true,
+        };
+        assert_eq!("#,##0.00", number.code());
+        assert_eq!("1,234,567.89", number.apply("1234567.891"));
+        assert_eq!("-1,234.50", number.apply("-1234.5"));
+        assert_eq!("0.00", number.apply("0"));
+        assert_eq!("text", number.apply("text"));
+
+        let number = NumberFormat::Number {
+            decimals: 0,
+            thousands: false,
+        };
+        assert_eq!("1235", number.apply("1234.5"));
+
+        let currency = NumberFormat::Currency {
+            symbol: "$".to_owned(),
+            decimals: 2,
+        };
+        assert_eq!("$1,234.50", currency.apply("1234.5"));
+        assert_eq!("-$0.75", currency.apply("-0.75"));
+
+        let percentage = NumberFormat::Percentage { decimals: 1 };
+        assert_eq!("12.5%", percentage.apply("0.125"));
+
+        let scientific = NumberFormat::Scientific { decimals: 2 };
+        assert_eq!("1.23E+04", scientific.apply("12345"));
+        assert_eq!("1.00E-03", scientific.apply("0.001"));
+        assert_eq!("1.00E+01", scientific.apply("9.999"));
+        assert_eq!("0.00E+00", scientific.apply("0"));
+    }
+
+    #[test]
+    fn custom_codes() {
+        assert_eq!("1,234.57", custom("#,##0.00", "1234.567"));
+        assert_eq!(".5", custom("#.##", "0.5"));
+        assert_eq!("5.", custom("#.##", "5"));
+        assert_eq!("0.5", custom("0.0#", "0.5"));
+        assert_eq!("0.12", custom("0.0#", "0.123"));
+        assert_eq!("007", custom("000", "7"));
+        assert_eq!("555-1234", custom("000-0000", "5551234"));
+        assert_eq!("1,235", custom("#,##0,", "1234567"));
+        assert_eq!("(1,234)", custom("#,##0;(#,##0)", "-1234"));
+        assert_eq!("1,234", custom("#,##0;(#,##0)", "1234"));
+        assert_eq!("zero", custom("0;-0;\"zero\"", "0"));
+        assert_eq!("Total: 5 units", custom("\"Total: \"0\" units\"", "5"));
+        assert_eq!("€12.00", custom("[$€-407]0.00", "12"));
+        assert_eq!("5 ", custom("0_)", "5"));
+        assert_eq!("Name: abc", custom("0;-0;0;\"Name: \"@", "abc"));
+        assert_eq!("abc", custom("0.00", "abc"));
+        assert_eq!("1.5", custom("General", "1.5"));
+        assert_eq!("(1.5)", custom("General;(General)", "-1.5"));
+    }
+
+    #[test]
+    fn dates_and_times() {
+        let date_time = |pattern: &str, value: &str| {
+            NumberFormat::DateTime(pattern.to_owned())
+                .apply(value)
+                .into_owned()
+        };
+        assert_eq!("2024-01-31", date_time("yyyy-mm-dd", "45322"));
+        assert_eq!("31/01/24", date_time("dd/mm/yy", "2024-01-31"));
+        assert_eq!("Wed 31 Jan 2024", date_time("ddd d mmm yyyy", "2024-01-31"));
+        assert_eq!(
+            "Thursday, 29 February 2024",
+            date_time("dddd, d mmmm yyyy", "2024-02-29")
+        );
+        assert_eq!("13:45:30", date_time("hh:mm:ss", "45322.573263889"));
+        assert_eq!("1:45 PM", date_time("h:mm AM/PM", "2024-01-31 13:45"));
+        assert_eq!("12:05 am", date_time("h:mm am/pm", "00:05"));
+        assert_eq!("1899-12-30 06:00", date_time("yyyy-mm-dd hh:mm", "0.25"));
+        assert_eq!("2024-02-30", date_time("yyyy-mm-dd", "2024-02-30"));
+        assert_eq!("-1", date_time("yyyy-mm-dd", "-1"));
+
+        for days in [-800_000, -1, 0, 59, 60, 45322, 1_000_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days, days_from_civil(year, month, day));
+        }
+        assert_eq!((1970, 1, 1), civil_from_days(0));
+        assert_eq!((2000, 2, 29), civil_from_days(11_016));
+    }
+
+    #[test]
+    fn invalid_codes() {
+        let validate = |code: &str| NumberFormat::Custom(code.to_owned()).validate();
+        assert_eq!(Err(NumberFormatError::Empty), validate(""));
+        assert_eq!(
+            Err(NumberFormatError::UnterminatedQuote),
+            validate("0\"units")
+        );
+        assert_eq!(
+            Err(NumberFormatError::UnterminatedBracket),
+            validate("[Red0")
+        );
+        assert_eq!(
+            Err(NumberFormatError::TooManySections),
+            validate("0;0;0;@;0")
+        );
+        assert_eq!(
+            Err(NumberFormatError::MissingCharacter('\\')),
+            validate("0\\")
+        );
+        assert_eq!(Ok(()), validate("[Red]#,##0.00;[Blue]-#,##0.00"));
+
+        // Invalid formats leave values unchanged
+        assert_eq!("12", custom("0\"", "12"));
+    }
+}
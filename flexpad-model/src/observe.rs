@@ -0,0 +1,332 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, RwLock, Weak,
+    },
+};
+
+use once_cell::sync::OnceCell;
+
+use crate::{DiffError, SheetId, Version, WorkpadDiff, WorkpadMaster, WorkpadMasterData};
+
+/// A change to a workpad reported to observers.  See [`WorkpadMaster::observe`].
+#[derive(Debug, Clone)]
+pub enum WorkpadEvent {
+    /// A new version was created by an update, based on the previously active version
+    Committed(VersionChange),
+    /// The active version changed (see [`crate::WorkpadUpdate::SetVersion`])
+    VersionChanged(VersionChange),
+}
+
+impl WorkpadEvent {
+    /// Returns the version that was active before
+    pub fn prior_version(&self) -> Version {
+        self.change().from
+    }
+
+    /// Returns the version that is now active
+    pub fn version(&self) -> Version {
+        self.change().to
+    }
+
+    /// Returns the differences between the previously active version and the now active one.
+    /// See [`VersionChange::diff`].
+    pub fn diff(&self) -> Result<&WorkpadDiff, DiffError> {
+        self.change().diff()
+    }
+
+    /// Returns the ids of the sheets affected, in the order they were first affected.  These
+    /// are recorded as each update is applied so include sheets whose formatting, styles,
+    /// comments, hyperlinks, merged cells or rules alone changed.  Changing a named style
+    /// affects every sheet, while changing which sheet or cell is active affects none.
+    pub fn affected_sheets(&self) -> &[SheetId] {
+        &self.change().affected.sheets
+    }
+
+    /// Returns the cells affected as (sheet id, row index, column index), in the order they
+    /// were first affected.  Only updates to single cells are recorded here; updates to
+    /// rows, columns, ranges or whole sheets are only found in [`Self::affected_sheets`].
+    pub fn affected_cells(&self) -> &[(SheetId, usize, usize)] {
+        &self.change().affected.cells
+    }
+
+    fn change(&self) -> &VersionChange {
+        match self {
+            Self::Committed(change) | Self::VersionChanged(change) => change,
+        }
+    }
+}
+
+/// The sheets and cells changed by updates, recorded as they are applied
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Affected {
+    sheets: Vec<SheetId>,
+    cells: Vec<(SheetId, usize, usize)>,
+}
+
+impl Affected {
+    pub(crate) fn sheet(&mut self, sheet_id: SheetId) {
+        if !self.sheets.contains(&sheet_id) {
+            self.sheets.push(sheet_id);
+        }
+    }
+
+    pub(crate) fn cell(&mut self, sheet_id: SheetId, row: usize, column: usize) {
+        self.sheet(sheet_id);
+        if !self.cells.contains(&(sheet_id, row, column)) {
+            self.cells.push((sheet_id, row, column));
+        }
+    }
+
+    pub(crate) fn extend(&mut self, other: &Affected) {
+        for sheet_id in &other.sheets {
+            self.sheet(*sheet_id);
+        }
+        for (sheet_id, row, column) in &other.cells {
+            self.cell(*sheet_id, *row, *column);
+        }
+    }
+}
+
+/// The versions a [`WorkpadEvent`] moved between.
+///
+/// The sheets and cells affected are recorded as the updates are applied.  Comparing
+/// versions means visiting every cell that differs, so the differences are only worked out
+/// when first asked for and then shared by all the clones of the event.
+#[derive(Clone)]
+pub struct VersionChange {
+    master: WorkpadMaster,
+    from: Version,
+    to: Version,
+    affected: Affected,
+    diff: Arc<OnceCell<Result<WorkpadDiff, DiffError>>>,
+}
+
+impl VersionChange {
+    pub(crate) fn new(
+        master: WorkpadMaster,
+        from: Version,
+        to: Version,
+        affected: Affected,
+    ) -> Self {
+        Self {
+            master,
+            from,
+            to,
+            affected,
+            diff: Default::default(),
+        }
+    }
+
+    /// Returns the differences between the versions (see [`WorkpadMaster::diff`]).  Like any
+    /// use of a version this is only valid until the history is compacted, after which the
+    /// versions may no longer exist.
+    pub fn diff(&self) -> Result<&WorkpadDiff, DiffError> {
+        self.diff
+            .get_or_init(|| self.master.diff(self.from, self.to))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+}
+
+impl fmt::Debug for VersionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VersionChange")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish()
+    }
+}
+
+type Observer = Arc<dyn Fn(&WorkpadEvent) + Send + Sync>;
+
+/// The observers of a workpad
+#[derive(Default)]
+pub(crate) struct Observers {
+    next_id: AtomicU32,
+    observers: RwLock<Vec<(u32, Observer)>>,
+}
+
+impl Observers {
+    fn add(&self, observer: Observer) -> u32 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.observers.write().unwrap().push((id, observer));
+        id
+    }
+
+    fn remove(&self, id: u32) {
+        self.observers.write().unwrap().retain(|(i, _)| *i != id);
+    }
+}
+
+/// Keeps an observer of a workpad registered.  The observer is removed when this is dropped.
+/// See [`WorkpadMaster::observe`].
+#[must_use = "the observer is removed as soon as the Observation is dropped"]
+pub struct Observation {
+    master: Weak<WorkpadMasterData>,
+    id: u32,
+}
+
+impl Drop for Observation {
+    fn drop(&mut self) {
+        if let Some(master) = self.master.upgrade() {
+            master.observers.remove(self.id);
+        }
+    }
+}
+
+impl fmt::Debug for Observation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observation").field("id", &self.id).finish()
+    }
+}
+
+impl WorkpadMaster {
+    /// Registers an observer to be called with a [`WorkpadEvent`] whenever an update creates
+    /// a new version or changes the active version.  The observer is called on the thread
+    /// that made the update, after the update is complete, and stays registered until the
+    /// returned [`Observation`] is dropped.  When updates are made from several threads at
+    /// once events may be reported out of order; see [`WorkpadEvent::version`].
+    ///
+    /// Compacting the history is not reported as it changes no data.
+    pub fn observe(&self, observer: impl Fn(&WorkpadEvent) + Send + Sync + 'static) -> Observation {
+        let id = self.data.observers.add(Arc::new(observer));
+        Observation {
+            master: Arc::downgrade(&self.data),
+            id,
+        }
+    }
+
+    /// Returns an event to report to the observers, created only if there are any
+    pub(crate) fn pending_event(
+        &self,
+        event: impl FnOnce() -> WorkpadEvent,
+    ) -> Option<PendingEvent> {
+        let observers: Vec<Observer> = self
+            .data
+            .observers
+            .observers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(_, observer)| observer.clone())
+            .collect();
+        (!observers.is_empty()).then(|| PendingEvent {
+            event: event(),
+            observers,
+        })
+    }
+}
+
+/// An event created while an update holds the workpad, to be reported once it is released
+pub(crate) struct PendingEvent {
+    event: WorkpadEvent,
+    observers: Vec<Observer>,
+}
+
+impl PendingEvent {
+    pub(crate) fn notify(self) {
+        for observer in self.observers {
+            observer(&self.event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::{Change, WorkpadUpdate};
+
+    #[test]
+    fn observers_see_commits_and_version_changes() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let cell = sheet.cell(1, 2);
+        let (sender, receiver) = mpsc::channel();
+        let sender = std::sync::Mutex::new(sender);
+        let observation = master.observe(move |event| {
+            sender.lock().unwrap().send(event.clone()).unwrap();
+        });
+
+        master
+            .update(WorkpadUpdate::SheetSetCellValue {
+                sheet_id: sheet.id(),
+                row_id: cell.row().id(),
+                column_id: cell.column().id(),
+                value: String::from("x"),
+            })
+            .unwrap();
+        let event = receiver.try_recv().unwrap();
+        assert!(matches!(event, WorkpadEvent::Committed(_)));
+        assert_eq!((0, 1), (event.prior_version(), event.version()));
+        assert_eq!(&[sheet.id()], event.affected_sheets());
+        assert_eq!(&[(sheet.id(), 1, 2)], event.affected_cells());
+        assert_eq!(
+            vec![(1, 2)],
+            event
+                .diff()
+                .unwrap()
+                .changed_cells(sheet.id())
+                .collect::<Vec<_>>()
+        );
+
+        master
+            .update(WorkpadUpdate::SetVersion { version: 0 })
+            .unwrap();
+        let event = receiver.try_recv().unwrap();
+        assert!(matches!(event, WorkpadEvent::VersionChanged(_)));
+        assert_eq!((1, 0), (event.prior_version(), event.version()));
+        assert!(matches!(
+            event.diff().unwrap().changes(),
+            [Change::CellValue { new_value, .. }] if new_value.is_empty()
+        ));
+        assert_eq!(&[(sheet.id(), 1, 2)], event.affected_cells());
+
+        // Sheets are affected by changes the differences between versions do not cover
+        master
+            .update(WorkpadUpdate::SheetSetConditionalFormats {
+                sheet_id: sheet.id(),
+                conditional_formats: vec![],
+            })
+            .unwrap();
+        let event = receiver.try_recv().unwrap();
+        assert!(event.diff().unwrap().is_empty());
+        assert_eq!(&[sheet.id()], event.affected_sheets());
+        assert!(event.affected_cells().is_empty());
+
+        // Stepping across branches affects what was changed on both
+        master
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .unwrap();
+        let event = receiver.try_recv().unwrap();
+        assert_eq!((2, 1), (event.prior_version(), event.version()));
+        assert_eq!(&[sheet.id()], event.affected_sheets());
+        assert_eq!(&[(sheet.id(), 1, 2)], event.affected_cells());
+        master
+            .update(WorkpadUpdate::SetVersion { version: 0 })
+            .unwrap();
+        receiver.try_recv().unwrap();
+
+        // Failed updates and checkpoints create no version so are not reported
+        assert!(master
+            .update(WorkpadUpdate::SetVersion { version: 99 })
+            .is_err());
+        master
+            .update(WorkpadUpdate::SetCheckpoint {
+                version: 0,
+                label: String::from("Start"),
+                note: String::new(),
+            })
+            .unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        drop(observation);
+        master
+            .update(WorkpadUpdate::SetVersion { version: 1 })
+            .unwrap();
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Sheet;
+
+/// A rectangular range of the cells of a sheet, such as those a rule applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SheetRange {
+    /// The indices of the rows of the range
+    pub rows: RangeInclusive<usize>,
+    /// The indices of the columns of the range
+    pub columns: RangeInclusive<usize>,
+}
+
+impl SheetRange {
+    pub fn new(rows: RangeInclusive<usize>, columns: RangeInclusive<usize>) -> Self {
+        Self { rows, columns }
+    }
+
+    /// Returns the range of just the cell at a (row, column) index
+    pub fn single(row: usize, column: usize) -> Self {
+        Self::new(row..=row, column..=column)
+    }
+
+    /// Returns true if the cell at a (row, column) index is in this range
+    pub fn contains(&self, row: usize, column: usize) -> bool {
+        self.rows.contains(&row) && self.columns.contains(&column)
+    }
+
+    /// Returns true if the range holds only one cell
+    pub fn is_single_cell(&self) -> bool {
+        self.rows.start() == self.rows.end() && self.columns.start() == self.columns.end()
+    }
+}
+
+impl Sheet {
+    /// Returns the name of a range of this [`Sheet`], such as `A1:C3`, from the names of
+    /// its corner cells
+    pub fn range_name(&self, range: &SheetRange) -> String {
+        let first = self.cell(*range.rows.start(), *range.columns.start());
+        if range.is_single_cell() {
+            return first.name().to_owned();
+        }
+        let last = self.cell(*range.rows.end(), *range.columns.end());
+        format!("{}:{}", first.name(), last.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WorkpadMaster;
+
+    use super::*;
+
+    #[test]
+    fn range_names() {
+        let sheet = WorkpadMaster::new_starter()
+            .active_version()
+            .active_sheet()
+            .unwrap();
+        assert_eq!("B3", sheet.range_name(&SheetRange::single(2, 1)));
+        assert_eq!("A1:C3", sheet.range_name(&SheetRange::new(0..=2, 0..=2)));
+    }
+}
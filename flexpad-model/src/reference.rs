@@ -0,0 +1,382 @@
+use std::{fmt, ops::RangeInclusive};
+
+use rust_i18n::t;
+
+use crate::{parse_column_name, parse_row_name, Cell, Sheet, SheetRange, Workpad};
+
+/// A reference to a single cell, or a rectangular range of cells, on a [`Sheet`].
+///
+/// References are written using the same names as shown for rows and columns, e.g. `AB1200`
+/// or `A1:C3`, optionally qualified by a sheet name, e.g. `Sheet 2!B4` or `'My Sheet'!B4`.
+/// A custom name given to a cell may be used in place of its row and column names.
+#[derive(Debug, Clone)]
+pub struct CellReference {
+    sheet: Sheet,
+    range: SheetRange,
+}
+
+impl CellReference {
+    pub(crate) fn new(
+        sheet: Sheet,
+        rows: RangeInclusive<usize>,
+        columns: RangeInclusive<usize>,
+    ) -> Self {
+        Self {
+            sheet,
+            range: SheetRange::new(rows, columns),
+        }
+    }
+
+    /// Returns the [`Sheet`] referred to
+    pub fn sheet(&self) -> Sheet {
+        self.sheet.clone()
+    }
+
+    /// Returns the indices of the rows referred to
+    pub fn rows(&self) -> RangeInclusive<usize> {
+        self.range.rows.clone()
+    }
+
+    /// Returns the indices of the columns referred to
+    pub fn columns(&self) -> RangeInclusive<usize> {
+        self.range.columns.clone()
+    }
+
+    /// Returns the range of the cells referred to on their sheet
+    pub fn range(&self) -> SheetRange {
+        self.range.clone()
+    }
+
+    /// Returns true if only one cell is referred to
+    pub fn is_single_cell(&self) -> bool {
+        self.range.is_single_cell()
+    }
+
+    /// Returns the top-left [`Cell`] referred to
+    pub fn first_cell(&self) -> Cell {
+        self.sheet
+            .cell(*self.range.rows.start(), *self.range.columns.start())
+    }
+
+    /// Returns the bottom-right [`Cell`] referred to
+    pub fn last_cell(&self) -> Cell {
+        self.sheet
+            .cell(*self.range.rows.end(), *self.range.columns.end())
+    }
+}
+
+/// The error returned when a reference cannot be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceError {
+    Empty,
+    UnknownSheet(String),
+    UnknownName(String),
+    OutOfRange(String),
+}
+
+impl fmt::Display for ReferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str(&t!("ReferenceError.Empty")),
+            Self::UnknownSheet(name) => {
+                f.write_str(&t!("ReferenceError.UnknownSheet").replace("{name}", name))
+            }
+            Self::UnknownName(name) => {
+                f.write_str(&t!("ReferenceError.UnknownName").replace("{name}", name))
+            }
+            Self::OutOfRange(name) => {
+                f.write_str(&t!("ReferenceError.OutOfRange").replace("{name}", name))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReferenceError {}
+
+impl Workpad {
+    /// Resolves a textual reference (see [`CellReference`]) against this version of the
+    /// workpad.  Unqualified references are to the active sheet, except that custom names
+    /// not found on the active sheet are looked for on the other sheets.
+    pub fn resolve_reference(&self, reference: &str) -> Result<CellReference, ReferenceError> {
+        let reference = reference.trim();
+        if reference.is_empty() {
+            return Err(ReferenceError::Empty);
+        }
+
+        let (sheet, target) = match split_sheet_name(reference) {
+            Some((name, target)) => {
+                let sheet = self
+                    .sheets()
+                    .find(|sheet| sheet.name() == name)
+                    .or_else(|| {
+                        self.sheets()
+                            .find(|sheet| sheet.name().eq_ignore_ascii_case(&name))
+                    })
+                    .ok_or(ReferenceError::UnknownSheet(name))?;
+                (Some(sheet), target.trim())
+            }
+            None => (None, reference),
+        };
+
+        let (from, to) = match target.split_once(':') {
+            Some((from, to)) => (from.trim(), to.trim()),
+            None => (target, target),
+        };
+
+        let (sheet, from) = self.resolve_cell(sheet, from)?;
+        let (sheet, to) = self.resolve_cell(Some(sheet), to)?;
+
+        Ok(CellReference::new(
+            sheet,
+            from.0.min(to.0)..=from.0.max(to.0),
+            from.1.min(to.1)..=from.1.max(to.1),
+        ))
+    }
+
+    /// Resolves one end of a reference to its (row, column) indices
+    fn resolve_cell(
+        &self,
+        sheet: Option<Sheet>,
+        name: &str,
+    ) -> Result<(Sheet, (usize, usize)), ReferenceError> {
+        if name.is_empty() {
+            return Err(ReferenceError::Empty);
+        }
+
+        let unknown = || ReferenceError::UnknownName(name.to_owned());
+        let active_sheet = sheet.clone().or_else(|| self.active_sheet());
+        let Some(active_sheet) = active_sheet else {
+            return Err(unknown());
+        };
+
+        if let Some((column, row)) = split_row_column(name) {
+            // Names too long to be parsed are as much outside the sheet as those that are
+            return match (parse_row_name(row), parse_column_name(column)) {
+                (Some(row), Some(column))
+                    if row < active_sheet.row_count() && column < active_sheet.column_count() =>
+                {
+                    Ok((active_sheet, (row, column)))
+                }
+                _ => Err(ReferenceError::OutOfRange(name.to_owned())),
+            };
+        }
+
+        let candidates: Vec<Sheet> = match sheet {
+            Some(sheet) => vec![sheet],
+            None => std::iter::once(active_sheet.clone())
+                .chain(self.sheets().filter(|sheet| *sheet != active_sheet))
+                .collect(),
+        };
+        candidates
+            .into_iter()
+            .find_map(|sheet| {
+                let cell = sheet
+                    .cells()
+                    .find(|cell| cell.custom_name() == Some(name))?;
+                Some((sheet, (cell.row().index(), cell.column().index())))
+            })
+            .ok_or_else(unknown)
+    }
+}
+
+/// Splits a sheet-qualified reference into the sheet name and the remainder.  Sheet names
+/// may be quoted (`'Sheet 1'!A1`) with embedded quotes doubled.
+fn split_sheet_name(reference: &str) -> Option<(String, &str)> {
+    if let Some(quoted) = reference.strip_prefix('\'') {
+        let mut name = String::new();
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((idx, c)) = chars.next() {
+            if c == '\'' {
+                if let Some((_, '\'')) = chars.peek() {
+                    chars.next();
+                    name.push('\'');
+                } else {
+                    return quoted[idx + 1..]
+                        .strip_prefix('!')
+                        .map(|target| (name, target));
+                }
+            } else {
+                name.push(c);
+            }
+        }
+        None
+    } else {
+        reference
+            .rsplit_once('!')
+            .map(|(name, target)| (name.trim().to_owned(), target))
+    }
+}
+
+/// Splits an automatic cell name (e.g. `AB1200`) into its column and row names
+fn split_row_column(name: &str) -> Option<(&str, &str)> {
+    let split = name.find(|c: char| c.is_ascii_digit())?;
+    let (column, row) = name.split_at(split);
+    let is_column = !column.is_empty() && column.bytes().all(|b| b.is_ascii_alphabetic());
+    let is_row = !row.starts_with('0') && row.bytes().all(|b| b.is_ascii_digit());
+    (is_column && is_row).then_some((column, row))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use internment::Intern;
+
+    use super::*;
+    use crate::{Name, WorkpadMaster};
+
+    fn resolve(pad: &Workpad, reference: &str) -> (String, (usize, usize), (usize, usize)) {
+        let reference = pad.resolve_reference(reference).unwrap();
+        let first = reference.first_cell();
+        let last = reference.last_cell();
+        (
+            reference.sheet().name().to_owned(),
+            (first.row().index(), first.column().index()),
+            (last.row().index(), last.column().index()),
+        )
+    }
+
+    #[test]
+    fn cell_references() {
+        let master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+
+        assert_eq!(
+            (String::from("Sheet 1"), (0, 0), (0, 0)),
+            resolve(&pad, "A1")
+        );
+        assert_eq!(
+            (String::from("Sheet 1"), (2, 27), (2, 27)),
+            resolve(&pad, " ab3 ")
+        );
+        assert_eq!(
+            (String::from("Sheet 2"), (3, 1), (3, 1)),
+            resolve(&pad, "Sheet 2!B4")
+        );
+        assert_eq!(
+            (String::from("Sheet 3"), (3, 1), (3, 1)),
+            resolve(&pad, "'Sheet 3'!B4")
+        );
+        assert!(pad.resolve_reference("B2").unwrap().is_single_cell());
+    }
+
+    #[test]
+    fn range_references() {
+        let master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+
+        assert_eq!(
+            (String::from("Sheet 1"), (0, 0), (2, 2)),
+            resolve(&pad, "A1:C3")
+        );
+        assert_eq!(
+            (String::from("Sheet 2"), (0, 0), (2, 2)),
+            resolve(&pad, "Sheet 2!C3:A1")
+        );
+
+        let reference = pad.resolve_reference("B2:D3").unwrap();
+        assert!(!reference.is_single_cell());
+        assert_eq!(1..=2, reference.rows());
+        assert_eq!(1..=3, reference.columns());
+    }
+
+    #[test]
+    fn reference_errors() {
+        let master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+
+        let err = |reference| pad.resolve_reference(reference).unwrap_err();
+        assert_eq!(ReferenceError::Empty, err("  "));
+        assert_eq!(ReferenceError::Empty, err("A1:"));
+        assert_eq!(
+            ReferenceError::UnknownSheet(String::from("Sheet 9")),
+            err("Sheet 9!A1")
+        );
+        assert_eq!(
+            ReferenceError::UnknownName(String::from("'Sheet 1!A1")),
+            err("'Sheet 1!A1")
+        );
+        assert_eq!(
+            ReferenceError::OutOfRange(String::from("A1048577")),
+            err("A1048577")
+        );
+        assert_eq!(
+            ReferenceError::OutOfRange(String::from("XFE1")),
+            err("XFE1")
+        );
+        assert_eq!(
+            ReferenceError::OutOfRange(String::from("A99999999999999999999999")),
+            err("A99999999999999999999999")
+        );
+        assert_eq!(
+            ReferenceError::OutOfRange(String::from("ZZZZZZZZZZZZZZZZ1")),
+            err("ZZZZZZZZZZZZZZZZ1")
+        );
+        assert_eq!(
+            ReferenceError::UnknownName(String::from("Total")),
+            err("Total")
+        );
+        assert_eq!(
+            ReferenceError::UnknownName(String::from("Total")),
+            err("Sheet 2!Total")
+        );
+
+        let master = WorkpadMaster::new_blank();
+        assert_eq!(
+            ReferenceError::UnknownName(String::from("A1")),
+            master.active_version().resolve_reference("A1").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn named_cell_references() {
+        let master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+        let sheet = pad
+            .sheets()
+            .find(|sheet| sheet.name() == "Sheet 2")
+            .unwrap();
+
+        // No update names cells so the name is given in place
+        let data = &master.data;
+        let version = pad.version().version();
+        let mut sheet_data = (*data.read_sheet(sheet.id(), version)).clone();
+        data.change_sheet_cell(sheet.id(), &mut sheet_data, (4, 2), version, |cell| {
+            cell.name = Name::Custom(Intern::from("Total"))
+        });
+        data.write_sheet(sheet.id(), Arc::new(sheet_data), version);
+        let pad = master.active_version();
+
+        // Names not on the active sheet are looked for on the others
+        assert_eq!(
+            (String::from("Sheet 2"), (4, 2), (4, 2)),
+            resolve(&pad, "Total")
+        );
+        assert_eq!(
+            (String::from("Sheet 2"), (0, 0), (4, 2)),
+            resolve(&pad, "Sheet 2!A1:Total")
+        );
+        assert_eq!(
+            ReferenceError::UnknownName(String::from("Total")),
+            pad.resolve_reference("Sheet 3!Total").unwrap_err()
+        );
+        assert_eq!(
+            ReferenceError::UnknownName(String::from("Total")),
+            pad.resolve_reference("A1:Total").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn sheet_name_splitting() {
+        assert_eq!(
+            Some((String::from("Sheet 1"), "A1")),
+            split_sheet_name("Sheet 1!A1")
+        );
+        assert_eq!(
+            Some((String::from("It's"), "A1")),
+            split_sheet_name("'It''s'!A1")
+        );
+        assert_eq!(None, split_sheet_name("A1"));
+        assert_eq!(None, split_sheet_name("'Sheet 1'A1"));
+    }
+}
@@ -0,0 +1,385 @@
+use std::fmt;
+
+use regex::{NoExpand, Regex, RegexBuilder};
+use rust_i18n::t;
+
+use crate::{Cell, Sheet, Workpad, WorkpadUpdate};
+
+/// Options that control how a [`Finder`] matches the values of cells.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FindOptions {
+    /// Only match text with the same case as the search text
+    pub match_case: bool,
+    /// Only match when the whole value of a cell matches the search text
+    pub whole_cell: bool,
+    /// Treat the search text as a regular expression
+    pub regex: bool,
+}
+
+/// The part of a workpad that is searched
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FindScope {
+    /// Only the sheet where the search starts
+    #[default]
+    Sheet,
+    /// All sheets of the workpad
+    Workpad,
+}
+
+/// The direction in which to look for the next match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindDirection {
+    Forward,
+    Backward,
+}
+
+/// A compiled search that can be used to find and replace text within cells.
+#[derive(Debug, Clone)]
+pub struct Finder {
+    regex: Regex,
+    expand_replacement: bool,
+}
+
+impl Finder {
+    /// Create a new [`Finder`] for the given search text and [`FindOptions`].
+    pub fn new(text: &str, options: FindOptions) -> Result<Self, FindError> {
+        if text.is_empty() {
+            return Err(FindError::Empty);
+        }
+
+        let pattern = if options.regex {
+            text.to_owned()
+        } else {
+            regex::escape(text)
+        };
+        let pattern = if options.whole_cell {
+            format!("^(?:{pattern})$")
+        } else {
+            pattern
+        };
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.match_case)
+            .build()
+            .map_err(|err| FindError::InvalidPattern(err.to_string()))?;
+
+        Ok(Self {
+            regex,
+            expand_replacement: options.regex,
+        })
+    }
+
+    /// Returns true if the given value matches this [`Finder`].  Empty values never match.
+    pub fn is_match(&self, value: &str) -> bool {
+        !value.is_empty() && self.regex.is_match(value)
+    }
+
+    /// Returns the result of replacing all matches within a value or `None` if the value
+    /// does not match.  When the [`Finder`] was created from a regular expression the
+    /// replacement may refer to capture groups (e.g. `$1`).
+    pub fn replace(&self, value: &str, replacement: &str) -> Option<String> {
+        if !self.is_match(value) {
+            return None;
+        }
+
+        let result = if self.expand_replacement {
+            self.regex.replace_all(value, replacement)
+        } else {
+            self.regex.replace_all(value, NoExpand(replacement))
+        };
+        Some(result.into_owned())
+    }
+}
+
+/// The error returned when a [`Finder`] cannot be created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FindError {
+    Empty,
+    InvalidPattern(String),
+}
+
+impl fmt::Display for FindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str(&t!("FindError.Empty")),
+            Self::InvalidPattern(reason) => {
+                f.write_str(&t!("FindError.InvalidPattern").replace("{reason}", reason))
+            }
+        }
+    }
+}
+
+impl std::error::Error for FindError {}
+
+impl Sheet {
+    /// Returns the [`Cell`]s of this [`Sheet`] whose values match the given [`Finder`].
+    /// Cells are returned row by row.
+    pub fn find_all<'a>(&'a self, finder: &'a Finder) -> impl Iterator<Item = Cell> + 'a {
+        self.non_empty_cells()
+            .filter(|cell| finder.is_match(cell.value()))
+    }
+}
+
+impl Workpad {
+    /// Returns the [`Cell`]s whose values match the given [`Finder`].  For [`FindScope::Sheet`]
+    /// only the active sheet is searched, otherwise all sheets are searched in order.
+    pub fn find_all(&self, finder: &Finder, scope: FindScope) -> Vec<Cell> {
+        let sheets: Vec<Sheet> = match scope {
+            FindScope::Sheet => self.active_sheet().into_iter().collect(),
+            FindScope::Workpad => self.sheets().collect(),
+        };
+        sheets
+            .iter()
+            .flat_map(|sheet| sheet.find_all(finder).collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// Returns the next [`Cell`] (in the given direction) after `from` that matches the given
+    /// [`Finder`].  The search wraps around so `from` itself is returned if it is the only match.
+    pub fn find_next(
+        &self,
+        finder: &Finder,
+        scope: FindScope,
+        from: &Cell,
+        direction: FindDirection,
+    ) -> Option<Cell> {
+        let sheets: Vec<Sheet> = match scope {
+            FindScope::Sheet => vec![from.sheet()],
+            FindScope::Workpad => self.sheets().collect(),
+        };
+        let position = |cell: &Cell| {
+            let sheet = sheets
+                .iter()
+                .position(|s| s.id() == cell.sheet().id())
+                .unwrap_or_default();
+            (sheet, cell.row().index(), cell.column().index())
+        };
+
+        let from = position(from);
+        let mut matches = sheets.iter().flat_map(|sheet| sheet.find_all(finder));
+        match direction {
+            FindDirection::Forward => {
+                let mut first = None;
+                for cell in matches.by_ref() {
+                    if position(&cell) > from {
+                        return Some(cell);
+                    }
+                    if first.is_none() {
+                        first = Some(cell);
+                    }
+                }
+                first
+            }
+            FindDirection::Backward => {
+                let mut before = None;
+                let mut last = None;
+                for cell in matches {
+                    if position(&cell) < from {
+                        before = Some(cell);
+                    } else {
+                        last = Some(cell);
+                    }
+                }
+                before.or(last)
+            }
+        }
+    }
+
+    /// Returns a [`WorkpadUpdate`] that replaces every match of the given [`Finder`] so that
+    /// all replacements are applied as a single version.  Returns `None` if nothing matches.
+    pub fn replace_all(
+        &self,
+        finder: &Finder,
+        replacement: &str,
+        scope: FindScope,
+    ) -> Option<WorkpadUpdate> {
+        let updates: Vec<WorkpadUpdate> = self
+            .find_all(finder, scope)
+            .iter()
+            .filter_map(|cell| {
+                finder.replace(cell.value(), replacement).map(|value| {
+                    WorkpadUpdate::SheetSetCellValue {
+                        sheet_id: cell.sheet().id(),
+                        row_id: cell.row().id(),
+                        column_id: cell.column().id(),
+                        value,
+                    }
+                })
+            })
+            .collect();
+
+        (!updates.is_empty()).then_some(WorkpadUpdate::Multi(updates))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::set_cell_values, SheetKind, WorkpadMaster};
+
+    fn options(match_case: bool, whole_cell: bool, regex: bool) -> FindOptions {
+        FindOptions {
+            match_case,
+            whole_cell,
+            regex,
+        }
+    }
+
+    fn rc(cell: &Cell) -> (usize, usize) {
+        (cell.row().index(), cell.column().index())
+    }
+
+    #[test]
+    fn finder_options() {
+        let finder = Finder::new("abc", options(false, false, false)).unwrap();
+        assert!(finder.is_match("xxABCxx"));
+        assert!(!finder.is_match(""));
+
+        let finder = Finder::new("abc", options(true, false, false)).unwrap();
+        assert!(!finder.is_match("xxABCxx"));
+        assert!(finder.is_match("xxabcxx"));
+
+        let finder = Finder::new("abc", options(false, true, false)).unwrap();
+        assert!(!finder.is_match("xxabcxx"));
+        assert!(finder.is_match("ABC"));
+
+        let finder = Finder::new("a.c", options(false, false, false)).unwrap();
+        assert!(!finder.is_match("abc"));
+        assert!(finder.is_match("a.c"));
+
+        let finder = Finder::new("a.c", options(false, false, true)).unwrap();
+        assert!(finder.is_match("abc"));
+    }
+
+    #[test]
+    fn finder_errors() {
+        assert_eq!(
+            FindError::Empty,
+            Finder::new("", FindOptions::default()).unwrap_err()
+        );
+        assert!(matches!(
+            Finder::new("(", options(false, false, true)),
+            Err(FindError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn finder_replace() {
+        let finder = Finder::new("a", options(false, false, false)).unwrap();
+        assert_eq!(Some(String::from("$1B$1")), finder.replace("aBA", "$1"));
+        assert_eq!(None, finder.replace("xyz", "$1"));
+
+        let finder = Finder::new("(\\d+)-(\\d+)", options(false, false, true)).unwrap();
+        assert_eq!(
+            Some(String::from("2-1 and 4-3")),
+            finder.replace("1-2 and 3-4", "$2-$1")
+        );
+    }
+
+    #[test]
+    fn find_next_in_sheet_wraps() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        set_cell_values(
+            &mut master,
+            &sheet,
+            &[(0, 1, "apple"), (3, 0, "pineapple"), (5, 5, "pear")],
+        );
+
+        let pad = master.active_version();
+        let sheet = pad.active_sheet().unwrap();
+        let finder = Finder::new("apple", FindOptions::default()).unwrap();
+        let forward = |from: &Cell| {
+            pad.find_next(&finder, FindScope::Sheet, from, FindDirection::Forward)
+                .unwrap()
+        };
+        let backward = |from: &Cell| {
+            pad.find_next(&finder, FindScope::Sheet, from, FindDirection::Backward)
+                .unwrap()
+        };
+
+        let first = forward(&sheet.cell(0, 0));
+        assert_eq!((0, 1), rc(&first));
+        let second = forward(&first);
+        assert_eq!((3, 0), rc(&second));
+        assert_eq!((0, 1), rc(&forward(&second)));
+
+        assert_eq!((3, 0), rc(&backward(&first)));
+        assert_eq!((0, 1), rc(&backward(&second)));
+    }
+
+    #[test]
+    fn find_next_across_sheets() {
+        let mut master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+        let sheets: Vec<Sheet> = pad.sheets().collect();
+        set_cell_values(&mut master, &sheets[0], &[(2, 2, "match")]);
+        set_cell_values(&mut master, &sheets[2], &[(1, 1, "match")]);
+
+        let pad = master.active_version();
+        let sheet_1 = pad.sheet_by_id(sheets[0].id()).unwrap();
+        let finder = Finder::new("match", FindOptions::default()).unwrap();
+
+        let from = sheet_1.cell(2, 2);
+        let next = pad
+            .find_next(&finder, FindScope::Sheet, &from, FindDirection::Forward)
+            .unwrap();
+        assert_eq!(sheets[0].id(), next.sheet().id());
+
+        let next = pad
+            .find_next(&finder, FindScope::Workpad, &from, FindDirection::Forward)
+            .unwrap();
+        assert_eq!(sheets[2].id(), next.sheet().id());
+        assert_eq!((1, 1), rc(&next));
+
+        let next = pad
+            .find_next(&finder, FindScope::Workpad, &next, FindDirection::Forward)
+            .unwrap();
+        assert_eq!(sheets[0].id(), next.sheet().id());
+    }
+
+    #[test]
+    fn find_next_without_match() {
+        let master = WorkpadMaster::new_starter();
+        let pad = master.active_version();
+        let from = pad.active_sheet().unwrap().cell(0, 0);
+        let finder = Finder::new("missing", FindOptions::default()).unwrap();
+
+        assert!(pad
+            .find_next(&finder, FindScope::Workpad, &from, FindDirection::Forward)
+            .is_none());
+        assert!(pad
+            .replace_all(&finder, "found", FindScope::Workpad)
+            .is_none());
+    }
+
+    #[test]
+    fn replace_all_is_one_version() {
+        let mut master = WorkpadMaster::new_blank();
+        master
+            .update(WorkpadUpdate::SheetAdd {
+                kind: SheetKind::Worksheet,
+                name: String::from("Data"),
+            })
+            .expect("Update should succeed");
+        let sheet = master.active_version().active_sheet().unwrap();
+        set_cell_values(
+            &mut master,
+            &sheet,
+            &[(0, 0, "red"), (1, 0, "Red Car"), (2, 0, "blue")],
+        );
+
+        let pad = master.active_version();
+        let finder = Finder::new("red", FindOptions::default()).unwrap();
+        let update = pad
+            .replace_all(&finder, "green", FindScope::Sheet)
+            .expect("Should match");
+        let pad = master.update(update).expect("Update should succeed");
+
+        assert_eq!(3, pad.version().version());
+        let sheet = pad.active_sheet().unwrap();
+        assert_eq!("green", sheet.cell(0, 0).value());
+        assert_eq!("green Car", sheet.cell(1, 0).value());
+        assert_eq!("blue", sheet.cell(2, 0).value());
+    }
+}
@@ -0,0 +1,402 @@
+use std::{error::Error, fmt, sync::Arc};
+
+use regex::Regex;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+use crate::{number_format::DateTime, SheetRange};
+
+/// A rule limiting the values that may be entered in the cells of a range of a sheet.
+/// Empty values are always allowed, so cells can be cleared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Validation {
+    /// The cells the rule applies to
+    #[serde(flatten)]
+    pub range: SheetRange,
+    pub rule: ValidationRule,
+    /// Whether values that break the rule are rejected, rather than entered and flagged
+    pub reject: bool,
+}
+
+impl Validation {
+    /// Checks that the rule itself makes sense, e.g. that its bounds are in order
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match &self.rule {
+            ValidationRule::List(values) if values.iter().all(|v| v.trim().is_empty()) => {
+                Err(ValidationError::EmptyList)
+            }
+            ValidationRule::Number { min, max } => match (min, max) {
+                (Some(min), _) if !min.is_finite() => {
+                    Err(ValidationError::InvalidBound(min.to_string()))
+                }
+                (_, Some(max)) if !max.is_finite() => {
+                    Err(ValidationError::InvalidBound(max.to_string()))
+                }
+                (Some(min), Some(max)) if min > max => Err(ValidationError::BoundsOutOfOrder),
+                _ => Ok(()),
+            },
+            ValidationRule::Date { min, max } => {
+                let bound = |bound: &Option<String>| match bound {
+                    Some(bound) => DateTime::parse(bound.trim())
+                        .map(Some)
+                        .ok_or_else(|| ValidationError::InvalidBound(bound.clone())),
+                    None => Ok(None),
+                };
+                match (bound(min)?, bound(max)?) {
+                    (Some(min), Some(max)) if min > max => Err(ValidationError::BoundsOutOfOrder),
+                    _ => Ok(()),
+                }
+            }
+            ValidationRule::TextLength {
+                min: Some(min),
+                max: Some(max),
+            } if min > max => Err(ValidationError::BoundsOutOfOrder),
+            ValidationRule::Pattern(pattern) => pattern_regex(pattern).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// What the value of a cell must be for a [`Validation`] to allow it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValidationRule {
+    /// One of a list of values, ignoring case.  The active cell offers them to pick from.
+    List(Vec<String>),
+    /// A number, optionally between bounds (inclusive)
+    Number { min: Option<f64>, max: Option<f64> },
+    /// A date (and/or time) such as `2024-01-31`, optionally between bounds (inclusive)
+    /// written the same way
+    Date {
+        min: Option<String>,
+        max: Option<String>,
+    },
+    /// Text with a number of characters between bounds (inclusive)
+    TextLength {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    /// Text that a regular expression matches in full
+    Pattern(String),
+}
+
+impl fmt::Display for ValidationRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds = |min: Option<String>, max: Option<String>| match (min, max) {
+            (Some(min), Some(max)) => t!("ValidationRule.Between")
+                .replace("{min}", &min)
+                .replace("{max}", &max),
+            (Some(min), None) => t!("ValidationRule.AtLeast").replace("{min}", &min),
+            (None, Some(max)) => t!("ValidationRule.AtMost").replace("{max}", &max),
+            (None, None) => String::new(),
+        };
+        let text = match self {
+            ValidationRule::List(values) => {
+                t!("ValidationRule.List").replace("{values}", &values.join(", "))
+            }
+            ValidationRule::Number { min, max } => t!("ValidationRule.Number").replace(
+                "{bounds}",
+                &bounds(min.map(|n| n.to_string()), max.map(|n| n.to_string())),
+            ),
+            ValidationRule::Date { min, max } => {
+                t!("ValidationRule.Date").replace("{bounds}", &bounds(min.clone(), max.clone()))
+            }
+            ValidationRule::TextLength { min, max } => t!("ValidationRule.TextLength").replace(
+                "{bounds}",
+                &bounds(min.map(|n| n.to_string()), max.map(|n| n.to_string())),
+            ),
+            ValidationRule::Pattern(pattern) => {
+                t!("ValidationRule.Pattern").replace("{pattern}", pattern)
+            }
+        };
+        f.write_str(text.trim_end())
+    }
+}
+
+/// Why a value is not allowed by a [`Validation`], or why the rule itself is not valid
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    NotInList,
+    NotANumber,
+    NotADate,
+    TooLow(String),
+    TooHigh(String),
+    TooShort(usize),
+    TooLong(usize),
+    NoMatch(String),
+    EmptyList,
+    InvalidBound(String),
+    BoundsOutOfOrder,
+    InvalidPattern(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInList => f.write_str(&t!("ValidationError.NotInList")),
+            Self::NotANumber => f.write_str(&t!("ValidationError.NotANumber")),
+            Self::NotADate => f.write_str(&t!("ValidationError.NotADate")),
+            Self::TooLow(min) => f.write_str(&t!("ValidationError.TooLow").replace("{min}", min)),
+            Self::TooHigh(max) => f.write_str(&t!("ValidationError.TooHigh").replace("{max}", max)),
+            Self::TooShort(min) => {
+                f.write_str(&t!("ValidationError.TooShort").replace("{min}", &min.to_string()))
+            }
+            Self::TooLong(max) => {
+                f.write_str(&t!("ValidationError.TooLong").replace("{max}", &max.to_string()))
+            }
+            Self::NoMatch(pattern) => {
+                f.write_str(&t!("ValidationError.NoMatch").replace("{pattern}", pattern))
+            }
+            Self::EmptyList => f.write_str(&t!("ValidationError.EmptyList")),
+            Self::InvalidBound(bound) => {
+                f.write_str(&t!("ValidationError.InvalidBound").replace("{bound}", bound))
+            }
+            Self::BoundsOutOfOrder => f.write_str(&t!("ValidationError.BoundsOutOfOrder")),
+            Self::InvalidPattern(reason) => {
+                f.write_str(&t!("ValidationError.InvalidPattern").replace("{reason}", reason))
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// The [`Validation`]s of a version of a [`crate::Sheet`], ready to check values against.
+/// Patterns are compiled once, when this is created, rather than for each value.
+#[derive(Debug)]
+pub struct Validator {
+    rules: Arc<Vec<Validation>>,
+    // The compiled pattern of each rule that has one
+    patterns: Vec<Option<Regex>>,
+}
+
+impl Validator {
+    pub(crate) fn new(rules: Arc<Vec<Validation>>) -> Self {
+        let patterns = rules
+            .iter()
+            .map(|rule| match &rule.rule {
+                ValidationRule::Pattern(pattern) => pattern_regex(pattern).ok(),
+                _ => None,
+            })
+            .collect();
+        Self { rules, patterns }
+    }
+
+    /// Returns true if there are no rules to check
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the rules covering the cell at a (row, column) index that a value breaks,
+    /// in order, with why each is broken
+    pub fn broken_rules<'a>(
+        &'a self,
+        row: usize,
+        column: usize,
+        value: &'a str,
+    ) -> impl Iterator<Item = (&'a Validation, ValidationError)> + 'a {
+        self.rules
+            .iter()
+            .zip(&self.patterns)
+            .filter(move |(rule, _)| !value.trim().is_empty() && rule.range.contains(row, column))
+            .filter_map(move |(rule, pattern)| {
+                check(&rule.rule, pattern.as_ref(), value)
+                    .err()
+                    .map(|err| (rule, err))
+            })
+    }
+
+    /// Returns the values offered for the cell at a (row, column) index, those of the first
+    /// [`ValidationRule::List`] covering it
+    pub fn list_of(&self, row: usize, column: usize) -> Option<&[String]> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.range.contains(row, column))
+            .find_map(|rule| match &rule.rule {
+                ValidationRule::List(values) => Some(&values[..]),
+                _ => None,
+            })
+    }
+}
+
+/// Checks a (non-empty) value against a rule
+fn check(
+    rule: &ValidationRule,
+    pattern: Option<&Regex>,
+    value: &str,
+) -> Result<(), ValidationError> {
+    let trimmed = value.trim();
+    match rule {
+        ValidationRule::List(values) => values
+            .iter()
+            .any(|allowed| allowed.trim().eq_ignore_ascii_case(trimmed))
+            .then_some(())
+            .ok_or(ValidationError::NotInList),
+        ValidationRule::Number { min, max } => {
+            let number = number(trimmed).ok_or(ValidationError::NotANumber)?;
+            match (min, max) {
+                (Some(min), _) if number < *min => Err(ValidationError::TooLow(min.to_string())),
+                (_, Some(max)) if number > *max => Err(ValidationError::TooHigh(max.to_string())),
+                _ => Ok(()),
+            }
+        }
+        ValidationRule::Date { min, max } => {
+            // Dates may be entered as text or as serial numbers
+            let date = DateTime::parse(trimmed)
+                .or_else(|| number(trimmed).and_then(DateTime::from_serial))
+                .ok_or(ValidationError::NotADate)?;
+            let bound = |bound: &Option<String>| DateTime::parse(bound.as_ref()?.trim());
+            match (bound(min), bound(max)) {
+                (Some(low), _) if date < low => {
+                    Err(ValidationError::TooLow(min.clone().unwrap_or_default()))
+                }
+                (_, Some(high)) if date > high => {
+                    Err(ValidationError::TooHigh(max.clone().unwrap_or_default()))
+                }
+                _ => Ok(()),
+            }
+        }
+        ValidationRule::TextLength { min, max } => {
+            let length = value.chars().count();
+            match (min, max) {
+                (Some(min), _) if length < *min => Err(ValidationError::TooShort(*min)),
+                (_, Some(max)) if length > *max => Err(ValidationError::TooLong(*max)),
+                _ => Ok(()),
+            }
+        }
+        ValidationRule::Pattern(text) => match pattern {
+            Some(pattern) if pattern.is_match(value) => Ok(()),
+            _ => Err(ValidationError::NoMatch(text.clone())),
+        },
+    }
+}
+
+/// Compiles a pattern so that it must match the whole of a value
+fn pattern_regex(pattern: &str) -> Result<Regex, ValidationError> {
+    Regex::new(&format!("^(?:{pattern})$"))
+        .map_err(|err| ValidationError::InvalidPattern(err.to_string()))
+}
+
+fn number(value: &str) -> Option<f64> {
+    value.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, WorkpadMaster, WorkpadUpdate};
+
+    #[test]
+    fn values_are_checked_against_rules() {
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let rule = |column, rule, reject| Validation {
+            range: SheetRange::new(0..=9, column..=column),
+            rule,
+            reject,
+        };
+        let validations = vec![
+            rule(
+                0,
+                ValidationRule::List(vec!["Red".to_owned(), "Green".to_owned()]),
+                true,
+            ),
+            rule(
+                1,
+                ValidationRule::Number {
+                    min: Some(0.0),
+                    max: Some(100.0),
+                },
+                true,
+            ),
+            rule(
+                2,
+                ValidationRule::Date {
+                    min: Some("2024-01-01".to_owned()),
+                    max: None,
+                },
+                true,
+            ),
+            rule(
+                3,
+                ValidationRule::TextLength {
+                    min: None,
+                    max: Some(3),
+                },
+                false,
+            ),
+            rule(4, ValidationRule::Pattern("[A-Z]{2}\\d+".to_owned()), true),
+        ];
+        master
+            .update(WorkpadUpdate::SheetSetValidations {
+                sheet_id: sheet.id(),
+                validations: validations.clone(),
+            })
+            .unwrap();
+
+        let mut set = |column: usize, value: &str| {
+            let cell = sheet.cell(1, column);
+            master.update(WorkpadUpdate::SheetSetCellValue {
+                sheet_id: sheet.id(),
+                row_id: cell.row().id(),
+                column_id: cell.column().id(),
+                value: value.to_owned(),
+            })
+        };
+        let rejected = |result: crate::UpdateResult| match result {
+            Err(err) => match err.kind {
+                ErrorKind::InvalidValue(err) => err,
+                kind => panic!("unexpected error {kind:?}"),
+            },
+            Ok(_) => panic!("value not rejected"),
+        };
+
+        assert!(set(0, "green").is_ok());
+        assert_eq!(ValidationError::NotInList, rejected(set(0, "Blue")));
+        assert!(set(1, "42.5").is_ok());
+        assert_eq!(ValidationError::NotANumber, rejected(set(1, "lots")));
+        assert_eq!(
+            ValidationError::TooHigh("100".to_owned()),
+            rejected(set(1, "101"))
+        );
+        assert!(set(2, "2024-02-29").is_ok());
+        assert_eq!(
+            ValidationError::TooLow("2024-01-01".to_owned()),
+            rejected(set(2, "2023-12-31"))
+        );
+        assert!(set(4, "AB12").is_ok());
+        assert_eq!(
+            ValidationError::NoMatch("[A-Z]{2}\\d+".to_owned()),
+            rejected(set(4, "AB12x"))
+        );
+        // Empty values are always allowed
+        assert!(set(0, "").is_ok());
+
+        // Values that break a rule that only warns are entered and flagged
+        let pad = set(3, "Long text").unwrap();
+        let sheet = pad.active_sheet().unwrap();
+        let validator = sheet.validator();
+        let broken: Vec<_> = validator
+            .broken_rules(1, 3, sheet.cell(1, 3).value())
+            .map(|(_, err)| err)
+            .collect();
+        assert_eq!(vec![ValidationError::TooLong(3)], broken);
+        assert_eq!(
+            Some(&["Red".to_owned(), "Green".to_owned()][..]),
+            validator.list_of(5, 0)
+        );
+        assert_eq!(None, validator.list_of(5, 1));
+        assert_eq!(&validations[..], sheet.validations());
+
+        // Rules that make no sense are refused
+        let err = master
+            .update(WorkpadUpdate::SheetSetValidations {
+                sheet_id: sheet.id(),
+                validations: vec![rule(0, ValidationRule::Pattern("(".to_owned()), true)],
+            })
+            .unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::InvalidValidation(ValidationError::InvalidPattern(_))
+        ));
+    }
+}
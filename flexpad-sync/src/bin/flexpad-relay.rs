@@ -0,0 +1,22 @@
+//! The relay through which Flexpad users collaborate on a workpad in real time.
+//!
+//! Usage: `flexpad-relay [ADDRESS]` where the address to listen on defaults to
+//! `127.0.0.1:7411`.
+
+use flexpad_sync::{Relay, DEFAULT_RELAY_ADDRESS};
+use tracing::info;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+fn main() -> std::io::Result<()> {
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(EnvFilter::from_default_env())
+        .init();
+
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_RELAY_ADDRESS.to_owned());
+    let relay = Relay::bind(address)?;
+    info!(target: "flexpad_sync", address = %relay.local_addr()?, "Relay started");
+    relay.run()
+}
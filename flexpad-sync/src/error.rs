@@ -0,0 +1,44 @@
+use std::{error::Error, fmt, io, sync::Arc};
+
+use flexpad_model::UpdateError;
+
+/// The errors that can occur when collaborating on a workpad
+#[derive(Debug, Clone)]
+pub enum SyncError {
+    /// Communication with the relay failed
+    Io(Arc<io::Error>),
+    /// There is no session to join at the relay
+    NoSession,
+    /// The relay sent a message that was not expected
+    Protocol,
+    /// The connection with the relay has been lost
+    Disconnected,
+    /// An update made locally failed
+    Update(UpdateError),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Communication with the relay failed: {err}"),
+            Self::NoSession => write!(f, "There is no session to join"),
+            Self::Protocol => write!(f, "The relay sent an unexpected message"),
+            Self::Disconnected => write!(f, "The connection with the relay has been lost"),
+            Self::Update(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for SyncError {}
+
+impl From<io::Error> for SyncError {
+    fn from(value: io::Error) -> Self {
+        SyncError::Io(Arc::new(value))
+    }
+}
+
+impl From<UpdateError> for SyncError {
+    fn from(value: UpdateError) -> Self {
+        SyncError::Update(value)
+    }
+}
@@ -0,0 +1,30 @@
+//! Real-time collaboration on Flexpad workpads.
+//!
+//! Collaborators share a workpad through a [`Relay`], a small server (see the `flexpad-relay`
+//! binary) that puts the updates made by all of them into a single order.  Each collaborator
+//! holds a copy of the workpad in a [`Session`] which sends the updates made locally to the
+//! relay and applies the updates it relays, in the relay's order, to the copy.  So every copy
+//! passes through the same shared history.
+//!
+//! Updates are exchanged as serialized [`flexpad_model::WorkpadUpdate`]s (see [`protocol`]).
+//! An update is based on the version its author could see, which may be behind the shared
+//! history when others have made changes concurrently; it is rebased by applying it on top of
+//! the latest version of the shared history instead.  When concurrent updates change the same
+//! cell the one relayed first wins (see [`Session`]).
+//!
+//! Changes of active sheet and cell, and moves between versions, are not shared.  Instead the
+//! active cell of each collaborator is shared as their presence (see [`Collaborator`]).
+
+mod error;
+pub mod protocol;
+mod relay;
+mod session;
+mod snapshot;
+
+pub use error::SyncError;
+pub use relay::Relay;
+pub use session::{Collaborator, Session, SessionEvent};
+pub use snapshot::Snapshot;
+
+/// The address the relay listens on unless told otherwise
+pub const DEFAULT_RELAY_ADDRESS: &str = "127.0.0.1:7411";
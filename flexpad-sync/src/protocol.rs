@@ -0,0 +1,102 @@
+//! The messages exchanged between a [`crate::Session`] and a [`crate::Relay`].
+//!
+//! Each message is sent as a single line of JSON.  A session starts by sending
+//! [`ClientMessage::Join`] and the relay replies with [`ServerMessage::Welcome`], after which
+//! either side may send at any time.
+//!
+//! Sheets are identified by the order in which they were added to the shared workpad (those
+//! of the [`Snapshot`] the session started from first) rather than by the ids each copy of
+//! the workpad allocates.  Rows and columns are identified by index (see
+//! [`WorkpadUpdate::portable`]).
+
+use std::io::{self, BufRead, Write};
+
+use flexpad_model::{SheetId, WorkpadUpdate};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::Snapshot;
+
+/// The position of an update in the shared history, starting at 1 for the first update
+/// relayed.  Zero is the position of the [`Snapshot`] the session started from.
+pub type Seq = u64;
+
+/// Identifies a client of the relay for as long as it is connected
+pub type ClientId = u32;
+
+/// A message sent to the relay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Join the session of the relay, starting it from `snapshot` if there is none
+    Join {
+        user: String,
+        snapshot: Option<Snapshot>,
+    },
+    /// An update made by the client to the workpad as it was at `base`
+    Update { base: Seq, update: WorkpadUpdate },
+    /// The cell that is now active for the client, if any
+    Presence(Option<Position>),
+}
+
+/// A message sent by the relay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// The reply to [`ClientMessage::Join`] with all that is needed to join the session
+    Welcome {
+        client_id: ClientId,
+        snapshot: Snapshot,
+        updates: Vec<Sequenced>,
+        presence: Vec<Presence>,
+    },
+    /// The reply to [`ClientMessage::Join`] when there is no session to join
+    NoSession,
+    /// An update added to the shared history
+    Update(Sequenced),
+    /// The active cell of a client has changed
+    Presence(Presence),
+    /// A client has left the session
+    Left(ClientId),
+}
+
+/// An update given its position in the shared history by the relay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sequenced {
+    pub seq: Seq,
+    /// The position in the shared history that the author of the update had seen
+    pub base: Seq,
+    pub client_id: ClientId,
+    pub user: String,
+    pub update: WorkpadUpdate,
+}
+
+/// The active cell of a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Presence {
+    pub client_id: ClientId,
+    pub user: String,
+    pub position: Option<Position>,
+}
+
+/// The position of a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub sheet_id: SheetId,
+    pub row: usize,
+    pub column: usize,
+}
+
+/// Writes a message as a line of JSON
+pub fn write_message(writer: &mut impl Write, message: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    writer.flush()
+}
+
+/// Reads a message written by [`write_message`], returning `None` at the end of the input
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
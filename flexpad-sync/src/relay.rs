@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, BufReader},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+use tracing::{debug, info};
+
+use crate::{
+    protocol::{
+        read_message, write_message, ClientId, ClientMessage, Position, Presence, Seq, Sequenced,
+        ServerMessage,
+    },
+    Snapshot,
+};
+
+/// How long writing a message to a client may take before the client is given up on
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A server that relays the updates made to a shared workpad between the sessions of the
+/// collaborators.  See the [crate documentation](crate).
+///
+/// The relay holds a single session.  It starts with the [`Snapshot`] offered by the first
+/// collaborator to join and ends when the last one leaves.  The relay does not interpret the
+/// updates, it only puts them in order and keeps them so that others can join later.
+pub struct Relay {
+    listener: TcpListener,
+    state: Arc<Mutex<RelayState>>,
+}
+
+impl Relay {
+    /// Returns a relay that listens for sessions on an address
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            state: Default::default(),
+        })
+    }
+
+    /// Returns the address the relay is listening on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves sessions, each on its own thread, until listening fails
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let state = self.state.clone();
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                if let Err(err) = serve(stream, state) {
+                    debug!(target: "flexpad_sync", ?peer, %err, "Session failed");
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+fn serve(mut stream: TcpStream, state: Arc<Mutex<RelayState>>) -> io::Result<()> {
+    // Any thread that panicked did so before leaving the state inconsistent
+    let lock = || state.lock().unwrap_or_else(PoisonError::into_inner);
+
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(ClientMessage::Join { user, snapshot }) = read_message(&mut reader)? else {
+        return Ok(());
+    };
+    let sender = spawn_writer(stream.try_clone()?);
+    let Some(client_id) = lock().join(user, snapshot, sender) else {
+        return write_message(&mut stream, &ServerMessage::NoSession);
+    };
+
+    let result = loop {
+        match read_message(&mut reader) {
+            Ok(Some(message)) => lock().receive(client_id, message),
+            Ok(None) => break Ok(()),
+            Err(err) => break Err(err),
+        }
+    };
+    lock().leave(client_id);
+    result
+}
+
+#[derive(Default)]
+struct RelayState {
+    snapshot: Option<Snapshot>,
+    updates: Vec<Sequenced>,
+    clients: BTreeMap<ClientId, Client>,
+    next_client_id: ClientId,
+}
+
+/// Starts a thread that writes the messages sent to a client to its connection, in order, and
+/// returns the sender of the messages.  Writes are made without holding the relay state so
+/// that a client that is slow to read holds up no other.
+///
+/// If a write fails the connection is closed, which ends the client's session, and the
+/// thread ends so that sending to the client fails too.  The thread also ends once the
+/// client has left and the sender is dropped.
+fn spawn_writer(mut stream: TcpStream) -> Sender<Arc<ServerMessage>> {
+    let (sender, receiver) = mpsc::channel::<Arc<ServerMessage>>();
+    thread::spawn(move || {
+        for message in receiver {
+            if let Err(err) = write_message(&mut stream, &*message) {
+                debug!(target: "flexpad_sync", %err, "Write failed");
+                let _ = stream.shutdown(Shutdown::Both);
+                break;
+            }
+        }
+    });
+    sender
+}
+
+struct Client {
+    user: String,
+    sender: Sender<Arc<ServerMessage>>,
+    position: Option<Position>,
+}
+
+impl RelayState {
+    /// Adds a client to the session, starting the session if needed, and returns its id, or
+    /// `None` if there is no session to join.  The welcome is sent while the state is held
+    /// so that the client receives every update relayed after those in the welcome.
+    fn join(
+        &mut self,
+        user: String,
+        snapshot: Option<Snapshot>,
+        sender: Sender<Arc<ServerMessage>>,
+    ) -> Option<ClientId> {
+        if self.snapshot.is_none() {
+            self.snapshot = snapshot;
+        }
+        let snapshot = self.snapshot.as_ref()?;
+
+        let client_id = self.next_client_id;
+        self.next_client_id += 1;
+        let welcome = ServerMessage::Welcome {
+            client_id,
+            snapshot: snapshot.clone(),
+            updates: self.updates.clone(),
+            presence: self
+                .clients
+                .iter()
+                .map(|(client_id, client)| client.presence(*client_id))
+                .collect(),
+        };
+        // The writer only stops after failing to write, which leaving the session follows
+        sender.send(Arc::new(welcome)).ok()?;
+
+        info!(target: "flexpad_sync", client_id, user, "Joined");
+        let client = Client {
+            user,
+            sender,
+            position: None,
+        };
+        self.clients.insert(client_id, client);
+        Some(client_id)
+    }
+
+    fn receive(&mut self, client_id: ClientId, message: ClientMessage) {
+        let Some(client) = self.clients.get_mut(&client_id) else {
+            return;
+        };
+
+        match message {
+            ClientMessage::Join { .. } => (),
+            ClientMessage::Update { base, update } => {
+                let seq = self.updates.len() as Seq + 1;
+                let sequenced = Sequenced {
+                    seq,
+                    base: base.min(seq - 1),
+                    client_id,
+                    user: client.user.clone(),
+                    update,
+                };
+                self.updates.push(sequenced.clone());
+                self.broadcast(ServerMessage::Update(sequenced));
+            }
+            ClientMessage::Presence(position) => {
+                client.position = position;
+                let presence = client.presence(client_id);
+                self.broadcast(ServerMessage::Presence(presence));
+            }
+        }
+    }
+
+    fn leave(&mut self, client_id: ClientId) {
+        if let Some(client) = self.clients.remove(&client_id) {
+            info!(target: "flexpad_sync", client_id, user = client.user, "Left");
+            self.broadcast(ServerMessage::Left(client_id));
+        }
+        if self.clients.is_empty() {
+            self.snapshot = None;
+            self.updates.clear();
+        }
+    }
+
+    fn broadcast(&mut self, message: ServerMessage) {
+        let message = Arc::new(message);
+        let gone: Vec<ClientId> = self
+            .clients
+            .iter()
+            .filter(|(_, client)| client.sender.send(message.clone()).is_err())
+            .map(|(client_id, _)| *client_id)
+            .collect();
+
+        // A client that could not be written to is given up on straight away, rather than
+        // once its session notices the closed connection, so that others learn it has gone
+        for client_id in gone {
+            self.leave(client_id);
+        }
+    }
+}
+
+impl Client {
+    fn presence(&self, client_id: ClientId) -> Presence {
+        Presence {
+            client_id,
+            user: self.user.clone(),
+            position: self.position,
+        }
+    }
+}
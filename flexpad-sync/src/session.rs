@@ -0,0 +1,970 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::BufReader,
+    net::{Shutdown, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, Weak},
+    thread,
+};
+
+use flexpad_model::{
+    Cell, ColumnId, RowId, SheetId, UpdateError, Version, Workpad, WorkpadMaster, WorkpadUpdate,
+};
+use tracing::debug;
+
+use crate::{
+    protocol::{
+        read_message, write_message, ClientId, ClientMessage, Position, Presence, Seq, Sequenced,
+        ServerMessage,
+    },
+    Snapshot, SyncError,
+};
+
+/// A change in a session reported to its listener.  See [`Session::listen`].
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    /// A collaborator joined, left or moved their active cell
+    CollaboratorsChanged,
+    /// An update made locally could not be applied to the shared history, for example
+    /// because a collaborator deleted the sheet it changes
+    Rejected(UpdateError),
+    /// Changes made locally to cells were discarded because `user` changed them first
+    Conflicted { cells: Vec<Cell>, user: String },
+    /// The connection with the relay was lost.  The workpad can still be used but is no
+    /// longer shared.
+    Disconnected,
+}
+
+/// A collaborator in a session and the cell that is active for them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collaborator {
+    id: ClientId,
+    user: String,
+    sheet_id: SheetId,
+    row: usize,
+    column: usize,
+}
+
+impl Collaborator {
+    /// Returns the id of the collaborator, unique within the session
+    pub fn id(&self) -> ClientId {
+        self.id
+    }
+
+    /// Returns the name of the collaborator
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// Returns the id of the sheet of the collaborator's active cell
+    pub fn sheet_id(&self) -> SheetId {
+        self.sheet_id
+    }
+
+    /// Returns the row index of the collaborator's active cell
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Returns the column index of the collaborator's active cell
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// A copy of a workpad shared with collaborators through a [`crate::Relay`].
+///
+/// Updates that change the content of the workpad (see [`Session::update`]) are sent to the
+/// relay and applied once relayed, in the relay's order, so that every copy of the workpad
+/// passes through the same shared history.  Relayed updates are always applied to the latest
+/// version of the shared history, so a collaborator looking at an earlier version is brought
+/// back to the latest.
+///
+/// When collaborators change the same cell concurrently, neither having seen the other's
+/// change, the change relayed first wins and the later one is discarded, its author being
+/// told by [`SessionEvent::Conflicted`].  Every copy applies the same updates in the same
+/// order so all reach the same result.
+///
+/// The session ends when the last clone of it is dropped.
+#[derive(Clone)]
+pub struct Session {
+    data: Arc<SessionData>,
+}
+
+impl Session {
+    /// Shares a workpad through the relay at `address`, joining the session there instead if
+    /// one has already started.  The content of `pad` is copied to a new workpad (see
+    /// [`Session::master`]) which is the one shared.
+    pub fn share(
+        address: impl ToSocketAddrs,
+        pad: &Workpad,
+        user: &str,
+    ) -> Result<Self, SyncError> {
+        Self::connect(address, user, Some(Snapshot::of(pad)))
+    }
+
+    /// Joins the session of the relay at `address`
+    pub fn join(address: impl ToSocketAddrs, user: &str) -> Result<Self, SyncError> {
+        Self::connect(address, user, None)
+    }
+
+    fn connect(
+        address: impl ToSocketAddrs,
+        user: &str,
+        snapshot: Option<Snapshot>,
+    ) -> Result<Self, SyncError> {
+        let mut stream = TcpStream::connect(address)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let join = ClientMessage::Join {
+            user: user.to_owned(),
+            snapshot,
+        };
+        write_message(&mut stream, &join)?;
+
+        let (client_id, snapshot, updates, presence) = match read_message(&mut reader)? {
+            Some(ServerMessage::Welcome {
+                client_id,
+                snapshot,
+                updates,
+                presence,
+            }) => (client_id, snapshot, updates, presence),
+            Some(ServerMessage::NoSession) => return Err(SyncError::NoSession),
+            _ => return Err(SyncError::Protocol),
+        };
+
+        let (master, sheet_ids) = snapshot.build()?;
+        let mut state = SessionState::new(master, sheet_ids);
+        for sequenced in updates {
+            state.apply(sequenced, client_id);
+        }
+        state.collaborators = presence
+            .into_iter()
+            .map(|presence| (presence.client_id, presence))
+            .collect();
+
+        let data = Arc::new(SessionData {
+            client_id,
+            user: user.to_owned(),
+            stream: Mutex::new(stream),
+            state: Mutex::new(state),
+            listener: Default::default(),
+        });
+        let receiver = Arc::downgrade(&data);
+        thread::spawn(move || receive(receiver, reader));
+
+        let session = Session { data };
+        session.data.send_position(&mut session.data.state())?;
+        Ok(session)
+    }
+
+    /// Returns the shared workpad
+    pub fn master(&self) -> WorkpadMaster {
+        self.data.state().master.clone()
+    }
+
+    /// Returns the name this session gives its user
+    pub fn user(&self) -> &str {
+        &self.data.user
+    }
+
+    /// Returns true until the connection with the relay is lost
+    pub fn is_connected(&self) -> bool {
+        self.data.state().connected
+    }
+
+    /// Returns the other collaborators in the session that have an active cell
+    pub fn collaborators(&self) -> Vec<Collaborator> {
+        let state = self.data.state();
+        state
+            .collaborators
+            .values()
+            .filter(|presence| presence.client_id != self.data.client_id)
+            .filter_map(|presence| {
+                let position = presence.position?;
+                Some(Collaborator {
+                    id: presence.client_id,
+                    user: presence.user.clone(),
+                    sheet_id: state.sheets.to_local(position.sheet_id)?,
+                    row: position.row,
+                    column: position.column,
+                })
+            })
+            .collect()
+    }
+
+    /// Sets the listener called with each [`SessionEvent`], replacing any set before.  The
+    /// listener is called on the thread that receives from the relay.
+    pub fn listen(&self, listener: impl Fn(&SessionEvent) + Send + Sync + 'static) {
+        *self.data.listener.write().unwrap() = Some(Arc::new(listener));
+    }
+
+    /// Updates the shared workpad.
+    ///
+    /// Changes to the content of the workpad are sent to the relay and applied once relayed,
+    /// which observers of the workpad are told of (see [`WorkpadMaster::observe`]).  Changes
+    /// of active sheet or cell, moves between versions and checkpoints are not shared; they
+    /// are applied straight away and the resulting workpad returned.
+    pub fn update(&self, update: WorkpadUpdate) -> Result<Option<Workpad>, SyncError> {
+        let (local, shared) = split(update);
+        let mut state = self.data.state();
+        if !state.connected {
+            return Err(SyncError::Disconnected);
+        }
+
+        if let Some(shared) = shared {
+            let pad = state.master.active_version();
+            let update = state
+                .sheets
+                .to_session_update(&shared.portable(&pad))
+                .ok_or(SyncError::Protocol)?;
+            let base = state.seq;
+            self.data.send(&ClientMessage::Update { base, update })?;
+        }
+
+        let Some(local) = local else {
+            return Ok(None);
+        };
+        let creates_version = !matches!(
+            local,
+            WorkpadUpdate::SetVersion { .. }
+                | WorkpadUpdate::SetCheckpoint { .. }
+                | WorkpadUpdate::ClearCheckpoint { .. }
+        );
+        let at_head = state.master.active_version().version().version() == state.head;
+        let pad = state.master.update(local)?;
+        if creates_version && at_head {
+            state.head = pad.version().version();
+        }
+        self.data.send_position(&mut state)?;
+        Ok(Some(pad))
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("client_id", &self.data.client_id)
+            .field("user", &self.data.user)
+            .finish()
+    }
+}
+
+type Listener = Arc<dyn Fn(&SessionEvent) + Send + Sync>;
+
+/// Data that backs a [`Session`]
+struct SessionData {
+    client_id: ClientId,
+    user: String,
+    stream: Mutex<TcpStream>,
+    state: Mutex<SessionState>,
+    listener: RwLock<Option<Listener>>,
+}
+
+impl SessionData {
+    fn state(&self) -> MutexGuard<'_, SessionState> {
+        // A thread that panicked while applying an update leaves the workpad as it was
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn send(&self, message: &ClientMessage) -> Result<(), SyncError> {
+        let mut stream = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(write_message(&mut *stream, message)?)
+    }
+
+    /// Sends the position of the active cell if it has changed since last sent
+    fn send_position(&self, state: &mut SessionState) -> Result<(), SyncError> {
+        let position = state.position(&state.master.active_version());
+        if position != state.position {
+            state.position = position;
+            self.send(&ClientMessage::Presence(position))?;
+        }
+        Ok(())
+    }
+
+    fn notify(&self, event: SessionEvent) {
+        let listener = self.listener.read().unwrap().clone();
+        if let Some(listener) = listener {
+            listener(&event);
+        }
+    }
+
+    fn receive(&self, message: ServerMessage) {
+        let event = {
+            let mut state = self.state();
+            match message {
+                ServerMessage::Update(sequenced) => state.apply(sequenced, self.client_id),
+                ServerMessage::Presence(presence) if presence.client_id != self.client_id => {
+                    state.collaborators.insert(presence.client_id, presence);
+                    Some(SessionEvent::CollaboratorsChanged)
+                }
+                ServerMessage::Left(client_id) => state
+                    .collaborators
+                    .remove(&client_id)
+                    .map(|_| SessionEvent::CollaboratorsChanged),
+                _ => None,
+            }
+        };
+        // Listeners are called without the state held so that they can use the session
+        if let Some(event) = event {
+            self.notify(event);
+        }
+    }
+
+    fn disconnected(&self) {
+        self.state().connected = false;
+        self.notify(SessionEvent::Disconnected);
+    }
+}
+
+impl Drop for SessionData {
+    fn drop(&mut self) {
+        // Ends the thread receiving from the relay too
+        if let Ok(stream) = self.stream.get_mut() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Receives messages from the relay until the connection is closed
+fn receive(data: Weak<SessionData>, mut reader: BufReader<TcpStream>) {
+    loop {
+        let message = read_message::<ServerMessage>(&mut reader);
+        let Some(data) = data.upgrade() else {
+            return;
+        };
+        match message {
+            Ok(Some(message)) => data.receive(message),
+            Ok(None) => return data.disconnected(),
+            Err(err) => {
+                debug!(target: "flexpad_sync", %err, "Connection with relay failed");
+                return data.disconnected();
+            }
+        }
+    }
+}
+
+/// A cell as identified in relayed updates
+type CellKey = (SheetId, RowId, ColumnId);
+
+/// An update applied to the shared history
+struct Applied {
+    seq: Seq,
+    client_id: ClientId,
+    user: String,
+    cells: Vec<CellKey>,
+}
+
+struct SessionState {
+    master: WorkpadMaster,
+    /// The position in the shared history of the last update relayed
+    seq: Seq,
+    /// The latest version of the shared history, to which relayed updates are applied
+    head: Version,
+    sheets: SheetIds,
+    /// The updates applied during the session, used to find those that conflict
+    applied: Vec<Applied>,
+    collaborators: BTreeMap<ClientId, Presence>,
+    /// The position last sent to the relay
+    position: Option<Position>,
+    connected: bool,
+}
+
+impl SessionState {
+    fn new(master: WorkpadMaster, sheet_ids: Vec<SheetId>) -> Self {
+        let head = master.active_version().version().version();
+        let mut sheets = SheetIds::default();
+        for sheet_id in sheet_ids {
+            sheets.add(sheet_id);
+        }
+        Self {
+            master,
+            seq: 0,
+            head,
+            sheets,
+            applied: vec![],
+            collaborators: BTreeMap::new(),
+            position: None,
+            connected: true,
+        }
+    }
+
+    /// Applies a relayed update to the shared history returning any event for its author,
+    /// when `me` is that author
+    fn apply(&mut self, sequenced: Sequenced, me: ClientId) -> Option<SessionEvent> {
+        self.seq = sequenced.seq;
+        let is_mine = sequenced.client_id == me;
+
+        let lost = self.conflicts(&sequenced);
+        let lost_cells: Vec<CellKey> = lost.iter().map(|(cell, _)| *cell).collect();
+        let rejected = without_cells(&sequenced.update, &lost_cells)
+            .and_then(|update| self.apply_update(update, &sequenced, is_mine));
+
+        if rejected.is_some() || !is_mine || lost.is_empty() {
+            return rejected;
+        }
+        let pad = self.master.active_version();
+        let cells = lost_cells
+            .iter()
+            .filter_map(|(sheet_id, row_id, column_id)| {
+                let sheet = pad.sheet_by_id(self.sheets.to_local(*sheet_id)?)?;
+                sheet.cell_by_ids(*row_id, *column_id)
+            })
+            .collect();
+        let (_, user) = lost.into_iter().next().unwrap();
+        Some(SessionEvent::Conflicted { cells, user })
+    }
+
+    fn apply_update(
+        &mut self,
+        update: WorkpadUpdate,
+        sequenced: &Sequenced,
+        is_mine: bool,
+    ) -> Option<SessionEvent> {
+        // Every copy has the same sheets so one that is unknown is unknown to all
+        let local = self.sheets.to_local_update(&update)?;
+
+        // Relayed updates extend the shared history, whichever version is being looked at
+        if self.master.active_version().version().version() != self.head {
+            let _ = self
+                .master
+                .update(WorkpadUpdate::SetVersion { version: self.head });
+        }
+
+        let active_sheet = self.master.active_version().active_sheet().map(|s| s.id());
+        let pad = match self.master.update_by(local, &sequenced.user) {
+            Ok(pad) => pad,
+            Err(err) => return is_mine.then_some(SessionEvent::Rejected(err)),
+        };
+        self.head = pad.version().version();
+        for sheet in pad.sheets() {
+            if self.sheets.to_session(sheet.id()).is_none() {
+                self.sheets.add(sheet.id());
+            }
+        }
+        self.applied.push(Applied {
+            seq: sequenced.seq,
+            client_id: sequenced.client_id,
+            user: sequenced.user.clone(),
+            cells: cells_set(&update),
+        });
+
+        // Sheets added by collaborators would otherwise become the active sheet
+        let still_active = active_sheet.filter(|sheet_id| pad.sheet_by_id(*sheet_id).is_some());
+        if let Some(sheet_id) = still_active {
+            if !is_mine && pad.active_sheet().map(|s| s.id()) != Some(sheet_id) {
+                if let Ok(pad) = self
+                    .master
+                    .update(WorkpadUpdate::SetActiveSheet { sheet_id })
+                {
+                    self.head = pad.version().version();
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the cells set by an update that collaborators set after those the author of
+    /// the update had seen, with who set them.  These earlier changes win.
+    fn conflicts(&self, sequenced: &Sequenced) -> Vec<(CellKey, String)> {
+        let cells = cells_set(&sequenced.update);
+        let mut lost: Vec<(CellKey, String)> = vec![];
+        let unseen = self
+            .applied
+            .iter()
+            .rev()
+            .take_while(|applied| applied.seq > sequenced.base)
+            .filter(|applied| applied.client_id != sequenced.client_id);
+        for applied in unseen {
+            for cell in applied.cells.iter().filter(|cell| cells.contains(cell)) {
+                if !lost.iter().any(|(lost_cell, _)| lost_cell == cell) {
+                    lost.push((*cell, applied.user.clone()));
+                }
+            }
+        }
+        lost
+    }
+
+    /// Returns the position of the active cell of a version of the workpad
+    fn position(&self, pad: &Workpad) -> Option<Position> {
+        let sheet = pad.active_sheet()?;
+        let cell = sheet.active_cell()?;
+        Some(Position {
+            sheet_id: self.sheets.to_session(sheet.id())?,
+            row: cell.row().index(),
+            column: cell.column().index(),
+        })
+    }
+}
+
+/// The ids of the sheets in this copy of the workpad and in relayed updates, where sheets are
+/// identified by the order in which they were added to the session
+#[derive(Default)]
+struct SheetIds {
+    local: HashMap<SheetId, SheetId>,
+    session: HashMap<SheetId, SheetId>,
+}
+
+impl SheetIds {
+    fn add(&mut self, local_id: SheetId) {
+        let session_id = SheetId::from(self.local.len() as u32);
+        self.local.insert(session_id, local_id);
+        self.session.insert(local_id, session_id);
+    }
+
+    fn to_local(&self, session_id: SheetId) -> Option<SheetId> {
+        self.local.get(&session_id).copied()
+    }
+
+    fn to_session(&self, local_id: SheetId) -> Option<SheetId> {
+        self.session.get(&local_id).copied()
+    }
+
+    fn to_local_update(&self, update: &WorkpadUpdate) -> Option<WorkpadUpdate> {
+        map_sheets(update, &|sheet_id| self.to_local(sheet_id))
+    }
+
+    fn to_session_update(&self, update: &WorkpadUpdate) -> Option<WorkpadUpdate> {
+        map_sheets(update, &|sheet_id| self.to_session(sheet_id))
+    }
+}
+
+/// Returns an update with the sheets it refers to mapped, or `None` if any cannot be
+fn map_sheets(
+    update: &WorkpadUpdate,
+    map: &impl Fn(SheetId) -> Option<SheetId>,
+) -> Option<WorkpadUpdate> {
+    type WU = WorkpadUpdate;
+    let update = match update {
+        WU::Multi(updates) => WU::Multi(
+            updates
+                .iter()
+                .map(|update| map_sheets(update, map))
+                .collect::<Option<_>>()?,
+        ),
+        WU::SetActiveSheet { sheet_id } => WU::SetActiveSheet {
+            sheet_id: map(*sheet_id)?,
+        },
+        WU::SheetDelete { sheet_id } => WU::SheetDelete {
+            sheet_id: map(*sheet_id)?,
+        },
+        WU::SheetSetProperties { sheet_id, new_name } => WU::SheetSetProperties {
+            sheet_id: map(*sheet_id)?,
+            new_name: new_name.clone(),
+        },
+        WU::SheetSetCellValue {
+            sheet_id,
+            row_id,
+            column_id,
+            value,
+        } => WU::SheetSetCellValue {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            value: value.clone(),
+        },
+        WU::SheetSetActiveCell {
+            sheet_id,
+            row_id,
+            column_id,
+        } => WU::SheetSetActiveCell {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+        },
+        WU::SheetSetCellFormat {
+            sheet_id,
+            row_id,
+            column_id,
+            format,
+        } => WU::SheetSetCellFormat {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            format: format.clone(),
+        },
+        WU::SheetApplyCellFormat {
+            sheet_id,
+            row_id,
+            column_id,
+            format,
+        } => WU::SheetApplyCellFormat {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            format: format.clone(),
+        },
+        WU::SheetSetStyle { sheet_id, style } => WU::SheetSetStyle {
+            sheet_id: map(*sheet_id)?,
+            style: style.clone(),
+        },
+        WU::SheetSetRowStyle {
+            sheet_id,
+            row_id,
+            style,
+        } => WU::SheetSetRowStyle {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            style: style.clone(),
+        },
+        WU::SheetSetColumnStyle {
+            sheet_id,
+            column_id,
+            style,
+        } => WU::SheetSetColumnStyle {
+            sheet_id: map(*sheet_id)?,
+            column_id: *column_id,
+            style: style.clone(),
+        },
+        WU::SheetSetCellStyle {
+            sheet_id,
+            row_id,
+            column_id,
+            style,
+        } => WU::SheetSetCellStyle {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            style: style.clone(),
+        },
+        WU::SheetSetConditionalFormats {
+            sheet_id,
+            conditional_formats,
+        } => WU::SheetSetConditionalFormats {
+            sheet_id: map(*sheet_id)?,
+            conditional_formats: conditional_formats.clone(),
+        },
+        WU::SheetSetValidations {
+            sheet_id,
+            validations,
+        } => WU::SheetSetValidations {
+            sheet_id: map(*sheet_id)?,
+            validations: validations.clone(),
+        },
+        WU::SheetMergeCells {
+            sheet_id,
+            first_row_id,
+            first_column_id,
+            last_row_id,
+            last_column_id,
+        } => WU::SheetMergeCells {
+            sheet_id: map(*sheet_id)?,
+            first_row_id: *first_row_id,
+            first_column_id: *first_column_id,
+            last_row_id: *last_row_id,
+            last_column_id: *last_column_id,
+        },
+        WU::SheetUnmergeCells {
+            sheet_id,
+            row_id,
+            column_id,
+        } => WU::SheetUnmergeCells {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+        },
+        WU::SheetAddCellComment {
+            sheet_id,
+            row_id,
+            column_id,
+            comment,
+        } => WU::SheetAddCellComment {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            comment: comment.clone(),
+        },
+        WU::SheetEditCellComment {
+            sheet_id,
+            row_id,
+            column_id,
+            index,
+            text,
+        } => WU::SheetEditCellComment {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            index: *index,
+            text: text.clone(),
+        },
+        WU::SheetDeleteCellComment {
+            sheet_id,
+            row_id,
+            column_id,
+            index,
+        } => WU::SheetDeleteCellComment {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            index: *index,
+        },
+        WU::SheetSetCellHyperlink {
+            sheet_id,
+            row_id,
+            column_id,
+            hyperlink,
+        } => WU::SheetSetCellHyperlink {
+            sheet_id: map(*sheet_id)?,
+            row_id: *row_id,
+            column_id: *column_id,
+            hyperlink: hyperlink.clone(),
+        },
+        update => update.clone(),
+    };
+    Some(update)
+}
+
+/// Splits an update into the part applied only locally and the part that is shared
+fn split(update: WorkpadUpdate) -> (Option<WorkpadUpdate>, Option<WorkpadUpdate>) {
+    let multi = |mut updates: Vec<WorkpadUpdate>| match updates.len() {
+        0 => None,
+        1 => updates.pop(),
+        _ => Some(WorkpadUpdate::Multi(updates)),
+    };
+
+    match update {
+        WorkpadUpdate::Multi(updates) => {
+            let (mut local, mut shared) = (vec![], vec![]);
+            for update in updates {
+                let (local_part, shared_part) = split(update);
+                local.extend(local_part);
+                shared.extend(shared_part);
+            }
+            (multi(local), multi(shared))
+        }
+        WorkpadUpdate::NewWorkpad
+        | WorkpadUpdate::SetVersion { .. }
+        | WorkpadUpdate::SetCheckpoint { .. }
+        | WorkpadUpdate::ClearCheckpoint { .. }
+        | WorkpadUpdate::SetActiveSheet { .. }
+        | WorkpadUpdate::SheetSetActiveCell { .. } => (Some(update), None),
+        update => (None, Some(update)),
+    }
+}
+
+/// Returns the cells whose values are set by an update
+fn cells_set(update: &WorkpadUpdate) -> Vec<CellKey> {
+    match update {
+        WorkpadUpdate::Multi(updates) => updates.iter().flat_map(cells_set).collect(),
+        WorkpadUpdate::SheetSetCellValue {
+            sheet_id,
+            row_id,
+            column_id,
+            ..
+        } => vec![(*sheet_id, *row_id, *column_id)],
+        _ => vec![],
+    }
+}
+
+/// Returns an update without the parts that set the values of some cells, or `None` if
+/// nothing remains
+fn without_cells(update: &WorkpadUpdate, cells: &[CellKey]) -> Option<WorkpadUpdate> {
+    match update {
+        WorkpadUpdate::Multi(updates) => {
+            let kept: Vec<WorkpadUpdate> = updates
+                .iter()
+                .filter_map(|update| without_cells(update, cells))
+                .collect();
+            (!kept.is_empty()).then_some(WorkpadUpdate::Multi(kept))
+        }
+        WorkpadUpdate::SheetSetCellValue {
+            sheet_id,
+            row_id,
+            column_id,
+            ..
+        } if cells.contains(&(*sheet_id, *row_id, *column_id)) => None,
+        update => Some(update.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::Relay;
+
+    #[test]
+    fn collaborators_share_updates_and_presence() {
+        let relay = Relay::bind("127.0.0.1:0").unwrap();
+        let address = relay.local_addr().unwrap();
+        thread::spawn(move || relay.run());
+
+        assert!(matches!(
+            Session::join(address, "Guest"),
+            Err(SyncError::NoSession)
+        ));
+
+        let mut master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        master.update(set_value(&sheet, 0, 0, "x")).unwrap();
+        let host = Session::share(address, &master.active_version(), "Host").unwrap();
+        let guest = Session::join(address, "Guest").unwrap();
+        assert_eq!("x", value_of(&guest, 0, 0));
+        assert_eq!(3, guest.master().active_version().sheets().count());
+
+        let sheet = host.master().active_version().active_sheet().unwrap();
+        assert!(host.update(set_value(&sheet, 1, 1, "y")).unwrap().is_none());
+        wait_until(|| value_of(&guest, 1, 1) == "y");
+        wait_until(|| value_of(&host, 1, 1) == "y");
+        let pad = guest.master().active_version();
+        assert_eq!("Host", pad.version().author());
+
+        // Moving the active cell is not shared but seen as presence
+        let sheet = pad.active_sheet().unwrap();
+        let cell = sheet.cell(2, 3);
+        let pad = guest
+            .update(WorkpadUpdate::SheetSetActiveCell {
+                sheet_id: sheet.id(),
+                row_id: cell.row().id(),
+                column_id: cell.column().id(),
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            "D3",
+            pad.active_sheet().unwrap().active_cell().unwrap().name()
+        );
+        wait_until(|| {
+            host.collaborators()
+                .iter()
+                .any(|c| c.user() == "Guest" && (c.row(), c.column()) == (2, 3))
+        });
+        let host_sheet = host.master().active_version().active_sheet().unwrap();
+        assert_eq!("A1", host_sheet.active_cell().unwrap().name());
+
+        drop(guest);
+        wait_until(|| host.collaborators().is_empty());
+    }
+
+    #[test]
+    fn first_change_to_a_cell_wins() {
+        let snapshot = Snapshot::of(&WorkpadMaster::new_starter().active_version());
+        let (master, _) = snapshot.build().unwrap();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let update = |row, column, value| session_update(&sheet, row, column, value, 0);
+
+        // Neither client had seen the other's change to B2 when making their own, but the
+        // first client had seen both when changing B2 again
+        let relayed = [
+            sequenced(1, 0, 1, update(1, 1, "first")),
+            sequenced(
+                2,
+                0,
+                2,
+                WorkpadUpdate::Multi(vec![update(1, 1, "second"), update(2, 2, "kept")]),
+            ),
+            sequenced(3, 2, 1, update(1, 1, "third")),
+        ];
+
+        for me in [1, 2] {
+            let (copy, sheet_ids) = snapshot.build().unwrap();
+            let mut state = SessionState::new(copy, sheet_ids);
+            let mut events = relayed
+                .iter()
+                .map(|sequenced| state.apply(sequenced.clone(), me));
+
+            assert!(events.next().unwrap().is_none());
+            match events.next().unwrap() {
+                Some(SessionEvent::Conflicted { cells, user }) => {
+                    assert_eq!(2, me);
+                    assert_eq!(vec!["B2"], cells.iter().map(Cell::name).collect::<Vec<_>>());
+                    assert_eq!("client 1", user);
+                }
+                event => assert!(me == 1 && event.is_none()),
+            }
+            assert!(events.next().unwrap().is_none());
+
+            let pad = state.master.active_version();
+            let sheet = pad.active_sheet().unwrap();
+            assert_eq!("third", sheet.cell(1, 1).value());
+            assert_eq!("kept", sheet.cell(2, 2).value());
+        }
+    }
+
+    #[test]
+    fn sheets_are_identified_by_the_order_added() {
+        let snapshot = Snapshot::of(&WorkpadMaster::new_starter().active_version());
+        let (master, sheet_ids) = snapshot.build().unwrap();
+        let mut state = SessionState::new(master, sheet_ids);
+        let pad = state.master.active_version();
+        let active_sheet = pad.active_sheet().unwrap();
+
+        let add = WorkpadUpdate::SheetAdd {
+            kind: Default::default(),
+            name: String::from("Added"),
+        };
+        assert!(state.apply(sequenced(1, 0, 1, add), 0).is_none());
+        let set = session_update(&active_sheet, 0, 0, "new", 3);
+        assert!(state.apply(sequenced(2, 1, 1, set), 0).is_none());
+
+        let pad = state.master.active_version();
+        let added = pad.sheets().find(|sheet| sheet.name() == "Added").unwrap();
+        assert_eq!("new", added.cell(0, 0).value());
+        assert_eq!(Some(SheetId::from(3)), state.sheets.to_session(added.id()));
+        // A sheet added by a collaborator does not become active
+        assert_eq!(active_sheet.id(), pad.active_sheet().unwrap().id());
+
+        // Updates that fail are reported to their author only
+        let rename = WorkpadUpdate::SheetSetProperties {
+            sheet_id: SheetId::from(0),
+            new_name: String::from("Added"),
+        };
+        assert!(state.apply(sequenced(3, 2, 1, rename.clone()), 0).is_none());
+        assert!(matches!(
+            state.apply(sequenced(4, 3, 0, rename), 0),
+            Some(SessionEvent::Rejected(_))
+        ));
+    }
+
+    /// Returns an update setting a cell's value as it is relayed
+    fn session_update(
+        sheet: &flexpad_model::Sheet,
+        row: usize,
+        column: usize,
+        value: &str,
+        session_sheet: u32,
+    ) -> WorkpadUpdate {
+        let update = set_value(sheet, row, column, value).portable(&sheet.workpad());
+        map_sheets(&update, &|_| Some(SheetId::from(session_sheet))).unwrap()
+    }
+
+    fn sequenced(seq: Seq, base: Seq, client_id: ClientId, update: WorkpadUpdate) -> Sequenced {
+        Sequenced {
+            seq,
+            base,
+            client_id,
+            user: format!("client {client_id}"),
+            update,
+        }
+    }
+
+    fn set_value(
+        sheet: &flexpad_model::Sheet,
+        row: usize,
+        column: usize,
+        value: &str,
+    ) -> WorkpadUpdate {
+        let cell = sheet.cell(row, column);
+        WorkpadUpdate::SheetSetCellValue {
+            sheet_id: sheet.id(),
+            row_id: cell.row().id(),
+            column_id: cell.column().id(),
+            value: String::from(value),
+        }
+    }
+
+    fn value_of(session: &Session, row: usize, column: usize) -> String {
+        let pad = session.master().active_version();
+        let sheet = pad.active_sheet().unwrap();
+        let value = sheet.cell(row, column).value().to_owned();
+        value
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        let start = Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < Duration::from_secs(5), "Timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use flexpad_model::{
+    Checkpoint, Comment, ConditionalFormat, Format, Hyperlink, SheetId, SheetKind, UpdateError,
+    Validation, Workpad, WorkpadMaster, WorkpadUpdate,
+};
+use serde::{Deserialize, Serialize};
+
+/// The content of a workpad that a session starts from.  Only the content is held, not the
+/// history of the workpad, so each collaborator's history begins with the session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    name: String,
+    author: String,
+    sheets: Vec<SheetSnapshot>,
+    active_sheet: Option<usize>,
+    #[serde(default)]
+    styles: Vec<(String, Format)>,
+    /// The checkpoint marking the version the snapshot is of, if any.  Those of earlier
+    /// versions are not held as the versions are not.
+    #[serde(default)]
+    checkpoint: Option<Checkpoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SheetSnapshot {
+    kind: SheetKind,
+    name: String,
+    cells: Vec<(usize, usize, String)>,
+    #[serde(default)]
+    formats: Vec<(usize, usize, Format)>,
+    #[serde(default)]
+    style: Option<String>,
+    #[serde(default)]
+    row_styles: Vec<(usize, String)>,
+    #[serde(default)]
+    column_styles: Vec<(usize, String)>,
+    #[serde(default)]
+    cell_styles: Vec<(usize, usize, String)>,
+    #[serde(default)]
+    conditional_formats: Vec<ConditionalFormat>,
+    #[serde(default)]
+    validations: Vec<Validation>,
+    /// The (first row, first column, last row, last column) of each block of merged cells
+    #[serde(default)]
+    merges: Vec<(usize, usize, usize, usize)>,
+    /// The thread of comments of each cell that has them
+    #[serde(default)]
+    comments: Vec<(usize, usize, Vec<Comment>)>,
+    #[serde(default)]
+    hyperlinks: Vec<(usize, usize, Hyperlink)>,
+}
+
+impl Snapshot {
+    /// Returns a snapshot of the content of a version of a workpad
+    pub fn of(pad: &Workpad) -> Self {
+        let active_sheet = pad.active_sheet();
+        Self {
+            name: pad.name().to_owned(),
+            author: pad.author().to_owned(),
+            sheets: pad
+                .sheets()
+                .map(|sheet| SheetSnapshot {
+                    kind: sheet.kind(),
+                    name: sheet.name().to_owned(),
+                    cells: sheet
+                        .non_empty_cells()
+                        .map(|cell| {
+                            let value = cell.value().to_owned();
+                            (cell.row().index(), cell.column().index(), value)
+                        })
+                        .collect(),
+                    formats: sheet
+                        .cells()
+                        .filter(|cell| !cell.format().is_empty())
+                        .map(|cell| {
+                            let format = cell.format().clone();
+                            (cell.row().index(), cell.column().index(), format)
+                        })
+                        .collect(),
+                    style: sheet.style().map(str::to_owned),
+                    row_styles: sheet
+                        .styled_rows()
+                        .filter_map(|row| Some((row.index(), row.style()?.to_owned())))
+                        .collect(),
+                    column_styles: sheet
+                        .styled_columns()
+                        .filter_map(|column| Some((column.index(), column.style()?.to_owned())))
+                        .collect(),
+                    cell_styles: sheet
+                        .cells()
+                        .filter_map(|cell| {
+                            let style = cell.style()?.to_owned();
+                            Some((cell.row().index(), cell.column().index(), style))
+                        })
+                        .collect(),
+                    conditional_formats: sheet.conditional_formats().to_vec(),
+                    validations: sheet.validations().to_vec(),
+                    merges: sheet
+                        .merged_ranges()
+                        .map(|merged| {
+                            let (rows, columns) = (merged.rows(), merged.columns());
+                            (*rows.start(), *columns.start(), *rows.end(), *columns.end())
+                        })
+                        .collect(),
+                    comments: sheet
+                        .commented_cells()
+                        .map(|cell| {
+                            let comments = cell.comments().to_vec();
+                            (cell.row().index(), cell.column().index(), comments)
+                        })
+                        .collect(),
+                    hyperlinks: sheet
+                        .cells()
+                        .filter_map(|cell| {
+                            let hyperlink = cell.hyperlink()?.clone();
+                            Some((cell.row().index(), cell.column().index(), hyperlink))
+                        })
+                        .collect(),
+                })
+                .collect(),
+            active_sheet: pad
+                .sheets()
+                .position(|sheet| Some(&sheet) == active_sheet.as_ref()),
+            styles: pad
+                .styles()
+                .map(|(name, format)| (name.to_owned(), format.clone()))
+                .collect(),
+            checkpoint: pad.checkpoint(),
+        }
+    }
+
+    /// Returns a new workpad with the content of the snapshot and the ids of its sheets, in
+    /// the order of the snapshot.  The workpad has a single version.
+    pub(crate) fn build(&self) -> Result<(WorkpadMaster, Vec<SheetId>), UpdateError> {
+        let mut master = WorkpadMaster::new_blank();
+        master.update(WorkpadUpdate::WorkpadSetProperties {
+            new_name: self.name.clone(),
+            new_author: self.author.clone(),
+        })?;
+
+        // New workpads start with styles that the snapshot may not have
+        let pad = master.active_version();
+        let removed = pad
+            .styles()
+            .filter(|(name, _)| !self.styles.iter().any(|(style, _)| style == name))
+            .map(|(name, _)| WorkpadUpdate::RemoveStyle {
+                name: name.to_owned(),
+            });
+        let styles = self
+            .styles
+            .iter()
+            .map(|(name, format)| WorkpadUpdate::SetStyle {
+                name: name.clone(),
+                format: format.clone(),
+            });
+        let updates: Vec<WorkpadUpdate> = removed.chain(styles).collect();
+        if !updates.is_empty() {
+            master.update(WorkpadUpdate::Multi(updates))?;
+        }
+
+        let mut sheet_ids = vec![];
+        for snapshot in &self.sheets {
+            let pad = master.update(WorkpadUpdate::SheetAdd {
+                kind: snapshot.kind,
+                name: snapshot.name.clone(),
+            })?;
+            // An added sheet becomes the active sheet
+            let sheet = pad.active_sheet().unwrap();
+            sheet_ids.push(sheet.id());
+
+            let values = snapshot.cells.iter().map(|(row, column, value)| {
+                let cell = sheet.cell(*row, *column);
+                WorkpadUpdate::SheetSetCellValue {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    value: value.clone(),
+                }
+            });
+            let formats = snapshot.formats.iter().map(|(row, column, format)| {
+                let cell = sheet.cell(*row, *column);
+                WorkpadUpdate::SheetSetCellFormat {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    format: format.clone(),
+                }
+            });
+            let sheet_style = snapshot
+                .style
+                .iter()
+                .map(|style| WorkpadUpdate::SheetSetStyle {
+                    sheet_id: sheet.id(),
+                    style: Some(style.clone()),
+                });
+            let row_styles =
+                snapshot
+                    .row_styles
+                    .iter()
+                    .map(|(row, style)| WorkpadUpdate::SheetSetRowStyle {
+                        sheet_id: sheet.id(),
+                        row_id: sheet.row(*row).id(),
+                        style: Some(style.clone()),
+                    });
+            let column_styles = snapshot.column_styles.iter().map(|(column, style)| {
+                WorkpadUpdate::SheetSetColumnStyle {
+                    sheet_id: sheet.id(),
+                    column_id: sheet.column(*column).id(),
+                    style: Some(style.clone()),
+                }
+            });
+            let cell_styles = snapshot.cell_styles.iter().map(|(row, column, style)| {
+                let cell = sheet.cell(*row, *column);
+                WorkpadUpdate::SheetSetCellStyle {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    style: Some(style.clone()),
+                }
+            });
+            let conditional_formats = (!snapshot.conditional_formats.is_empty()).then(|| {
+                WorkpadUpdate::SheetSetConditionalFormats {
+                    sheet_id: sheet.id(),
+                    conditional_formats: snapshot.conditional_formats.clone(),
+                }
+            });
+            // Set after the values, which may break rules that only warn
+            let validations =
+                (!snapshot.validations.is_empty()).then(|| WorkpadUpdate::SheetSetValidations {
+                    sheet_id: sheet.id(),
+                    validations: snapshot.validations.clone(),
+                });
+            let merges = snapshot.merges.iter().map(|(top, left, bottom, right)| {
+                WorkpadUpdate::SheetMergeCells {
+                    sheet_id: sheet.id(),
+                    first_row_id: sheet.row(*top).id(),
+                    first_column_id: sheet.column(*left).id(),
+                    last_row_id: sheet.row(*bottom).id(),
+                    last_column_id: sheet.column(*right).id(),
+                }
+            });
+            let comments = snapshot
+                .comments
+                .iter()
+                .flat_map(|(row, column, comments)| {
+                    let cell = sheet.cell(*row, *column);
+                    let (sheet_id, row_id, column_id) =
+                        (sheet.id(), cell.row().id(), cell.column().id());
+                    comments
+                        .iter()
+                        .map(move |comment| WorkpadUpdate::SheetAddCellComment {
+                            sheet_id,
+                            row_id,
+                            column_id,
+                            comment: comment.clone(),
+                        })
+                });
+            let hyperlinks = snapshot.hyperlinks.iter().map(|(row, column, hyperlink)| {
+                let cell = sheet.cell(*row, *column);
+                WorkpadUpdate::SheetSetCellHyperlink {
+                    sheet_id: sheet.id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                    hyperlink: Some(hyperlink.clone()),
+                }
+            });
+            let updates: Vec<WorkpadUpdate> = values
+                .chain(formats)
+                .chain(sheet_style)
+                .chain(row_styles)
+                .chain(column_styles)
+                .chain(cell_styles)
+                .chain(conditional_formats)
+                .chain(validations)
+                .chain(merges)
+                .chain(comments)
+                .chain(hyperlinks)
+                .collect();
+            if !updates.is_empty() {
+                master.update(WorkpadUpdate::Multi(updates))?;
+            }
+        }
+
+        if let Some(sheet_id) = self.active_sheet.and_then(|index| sheet_ids.get(index)) {
+            master.update(WorkpadUpdate::SetActiveSheet {
+                sheet_id: *sheet_id,
+            })?;
+        }
+        let pad = master.compact_history(Some(1));
+        if let Some(checkpoint) = &self.checkpoint {
+            master.update(WorkpadUpdate::SetCheckpoint {
+                version: pad.version().version(),
+                label: checkpoint.label().to_owned(),
+                note: checkpoint.note().to_owned(),
+            })?;
+        }
+        Ok((master, sheet_ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_keep_the_checkpoint_of_their_version() {
+        let mut master = WorkpadMaster::new_starter();
+        let version = master.active_version().version().version();
+        let pad = master
+            .update(WorkpadUpdate::SetCheckpoint {
+                version,
+                label: String::from("Q3 final"),
+                note: String::from("Signed off"),
+            })
+            .unwrap();
+
+        let json = serde_json::to_string(&Snapshot::of(&pad)).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        let (copy, _) = snapshot.build().unwrap();
+        let copy = copy.active_version();
+        assert_eq!(pad.checkpoint(), copy.checkpoint());
+        assert_eq!(1, copy.checkpoints().len());
+    }
+}
@@ -5,14 +5,34 @@ use rust_i18n::t;
 #[derive(Debug)]
 pub enum FlexpadAction {
     Cancel,
+    CellComments,
+    CellHistory,
+    Checkpoint,
+    Comments,
+    Compare,
+    Find,
+    FormatBold,
+    FormatCells,
+    FormatConditional,
+    FormatItalic,
+    FormatStyles,
+    FormatUnderline,
+    FormatValidation,
+    Goto,
+    History,
+    Hyperlink,
+    MergeCells,
     NewBlank,
     NewStarter,
     NewTextsheet,
     NewWorksheet,
     Ok,
     PadClose,
+    PadCompact,
     PadDelete,
+    PadJoin,
     PadProperties,
+    PadShare,
     Print,
     Properties,
     Redo,
@@ -20,12 +40,30 @@ pub enum FlexpadAction {
     SheetNew,
     SheetProperties,
     Undo,
+    UnmergeCells,
 }
 
 impl FlexpadAction {
     fn icon_codepoint(&self) -> Option<char> {
         match self {
             Self::Cancel => None,
+            Self::CellComments => None,
+            Self::CellHistory => None,
+            Self::Checkpoint => None,
+            Self::Comments => None,
+            Self::Compare => None,
+            Self::Find => None,
+            Self::FormatBold => None,
+            Self::FormatCells => None,
+            Self::FormatConditional => None,
+            Self::FormatItalic => None,
+            Self::FormatStyles => None,
+            Self::FormatUnderline => None,
+            Self::FormatValidation => None,
+            Self::Goto => None,
+            Self::History => None,
+            Self::Hyperlink => None,
+            Self::MergeCells => None,
             Self::NewBlank => Some('\u{E81B}'),
             Self::NewStarter => Some('\u{E81C}'),
             Self::NewTextsheet => Some('\u{E81E}'),
@@ -33,7 +71,10 @@ impl FlexpadAction {
             Self::Ok => None,
             Self::PadDelete => None,
             Self::PadClose => None,
+            Self::PadCompact => None,
+            Self::PadJoin => None,
             Self::PadProperties => None,
+            Self::PadShare => None,
             Self::Print => Some('\u{E807}'),
             Self::Properties => Some('\u{E808}'),
             Self::Redo => Some('\u{E800}'),
@@ -41,6 +82,7 @@ impl FlexpadAction {
             Self::SheetNew => None,
             Self::SheetProperties => None,
             Self::Undo => Some('\u{E801}'),
+            Self::UnmergeCells => None,
         }
     }
 
@@ -48,6 +90,23 @@ impl FlexpadAction {
     fn shortcut(&self) -> Option<Key> {
         match self {
             Self::Cancel => Some(key(KeyCode::Escape)),
+            Self::CellComments => None,
+            Self::CellHistory => None,
+            Self::Checkpoint => None,
+            Self::Comments => None,
+            Self::Compare => None,
+            Self::Find => Some(logo(key(KeyCode::F))),
+            Self::FormatBold => Some(logo(key(KeyCode::B))),
+            Self::FormatCells => Some(logo(key(KeyCode::Key1))),
+            Self::FormatConditional => None,
+            Self::FormatItalic => Some(logo(key(KeyCode::I))),
+            Self::FormatStyles => None,
+            Self::FormatUnderline => Some(logo(key(KeyCode::U))),
+            Self::FormatValidation => None,
+            Self::Goto => Some(logo(key(KeyCode::G))),
+            Self::History => Some(shift(logo(key(KeyCode::H)))),
+            Self::Hyperlink => Some(logo(key(KeyCode::K))),
+            Self::MergeCells => None,
             Self::NewBlank => Some(logo(key(KeyCode::N))),
             Self::NewStarter => Some(shift(logo(key(KeyCode::N)))),
             Self::NewTextsheet => None,
@@ -55,7 +114,10 @@ impl FlexpadAction {
             Self::Ok => Some(key(KeyCode::Enter)),
             Self::PadDelete => Some(logo(key(KeyCode::Delete))),
             Self::PadClose => Some(logo(key(KeyCode::W))),
+            Self::PadCompact => None,
+            Self::PadJoin => None,
             Self::PadProperties => Some(logo(key(KeyCode::Comma))),
+            Self::PadShare => None,
             Self::Print => Some(logo(key(KeyCode::P))),
             Self::Properties => None,
             Self::Redo => Some(shift(logo(key(KeyCode::Z)))),
@@ -63,6 +125,7 @@ impl FlexpadAction {
             Self::SheetNew => Some(alt(key(KeyCode::N))),
             Self::SheetProperties => Some(alt(key(KeyCode::Comma))),
             Self::Undo => Some(logo(key(KeyCode::Z))),
+            Self::UnmergeCells => None,
         }
     }
 
@@ -70,6 +133,23 @@ impl FlexpadAction {
     fn shortcut(&self) -> Option<Key> {
         match self {
             Self::Cancel => Some(key(KeyCode::Escape)),
+            Self::CellComments => None,
+            Self::CellHistory => None,
+            Self::Checkpoint => None,
+            Self::Comments => None,
+            Self::Compare => None,
+            Self::Find => Some(ctrl(key(KeyCode::F))),
+            Self::FormatBold => Some(ctrl(key(KeyCode::B))),
+            Self::FormatCells => Some(ctrl(key(KeyCode::Key1))),
+            Self::FormatConditional => None,
+            Self::FormatItalic => Some(ctrl(key(KeyCode::I))),
+            Self::FormatStyles => None,
+            Self::FormatUnderline => Some(ctrl(key(KeyCode::U))),
+            Self::FormatValidation => None,
+            Self::Goto => Some(ctrl(key(KeyCode::G))),
+            Self::History => Some(shift(ctrl(key(KeyCode::H)))),
+            Self::Hyperlink => Some(ctrl(key(KeyCode::K))),
+            Self::MergeCells => None,
             Self::NewBlank => Some(ctrl(key(KeyCode::N))),
             Self::NewStarter => Some(shift(ctrl(key(KeyCode::N)))),
             Self::NewTextsheet => None,
@@ -77,7 +157,10 @@ impl FlexpadAction {
             Self::Ok => Some(key(KeyCode::Enter)),
             Self::PadDelete => Some(ctrl(key(KeyCode::Delete))),
             Self::PadClose => Some(ctrl(key(KeyCode::W))),
+            Self::PadCompact => None,
+            Self::PadJoin => None,
             Self::PadProperties => Some(ctrl(key(KeyCode::Comma))),
+            Self::PadShare => None,
             Self::Print => Some(ctrl(key(KeyCode::P))),
             Self::Properties => None,
             Self::Redo => Some(shift(ctrl(key(KeyCode::Z)))),
@@ -85,6 +168,7 @@ impl FlexpadAction {
             Self::SheetNew => Some(alt(key(KeyCode::N))),
             Self::SheetProperties => Some(alt(key(KeyCode::Comma))),
             Self::Undo => Some(ctrl(key(KeyCode::Z))),
+            Self::UnmergeCells => None,
         }
     }
 }
@@ -1,10 +1,17 @@
-use flexpad_model::{UpdateResult, WorkpadMaster, WorkpadUpdate};
+use flexpad_model::{Sheet, UpdateResult, Workpad, WorkpadMaster, WorkpadUpdate};
+use flexpad_sync::{Session, SessionEvent, SyncError, DEFAULT_RELAY_ADDRESS};
 use flexpad_toolkit::{
     menu::{MenuedContent, PathVec},
     modal::Modal,
     prelude::*,
 };
-use iced::{window, Application, Color, Command, Settings, Theme};
+use iced::{
+    futures::{
+        channel::{mpsc, oneshot},
+        SinkExt, StreamExt,
+    },
+    window, Application, Color, Command, Settings, Theme,
+};
 use rust_i18n::{i18n, t};
 use tracing::{debug, info};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
@@ -20,10 +27,25 @@ mod version;
 mod view {
     pub mod active_sheet;
     pub mod add_sheet;
+    pub mod cell_comments;
+    pub mod cell_history;
+    pub mod checkpoint;
+    pub mod comments;
+    pub mod compact_history;
+    pub mod compare;
+    pub mod conditional_formats;
     pub mod error;
+    pub mod find;
+    pub mod format_cells;
+    pub mod goto;
+    pub mod history;
+    pub mod hyperlink;
     pub mod lobby;
+    pub mod merge_cells;
     pub mod pad_properties;
     pub mod sheet_properties;
+    pub mod styles;
+    pub mod validations;
 }
 pub mod widget {
     pub mod active_cell;
@@ -70,9 +92,18 @@ enum Screen {
 enum Dialog {
     #[default]
     None,
+    Checkpoint(checkpoint::CheckpointUi),
+    CompactHistory(compact_history::CompactHistoryUi),
+    ConditionalFormats(conditional_formats::ConditionalFormatsUi),
+    MergeCells(merge_cells::MergeCellsUi),
     Error(error::ErrorUi),
+    FormatCells(format_cells::FormatCellsUi),
+    Goto(goto::GotoUi),
+    Hyperlink(hyperlink::HyperlinkUi),
     PadProperties(pad_properties::PadPropertiesUi),
     SheetProperties(sheet_properties::SheetPropertiesUi),
+    Styles(styles::StylesUi),
+    Validations(validations::ValidationsUi),
 }
 
 impl std::fmt::Display for Screen {
@@ -90,6 +121,11 @@ impl std::fmt::Display for Screen {
 pub enum DataEvent {
     PadOpened(WorkpadMaster),
     PadUpdated(UpdateResult),
+    PadChanged(Workpad),
+    UpdateSent,
+    SessionStarted(Session),
+    SessionFailed(SyncError),
+    Session(SessionEvent),
 }
 
 impl std::fmt::Display for DataEvent {
@@ -100,19 +136,29 @@ impl std::fmt::Display for DataEvent {
                 f,
                 "Opened id:{} version:({}, {})",
                 master.id(),
-                master.active_version().version().0,
-                master.active_version().version().1
+                master.active_version().version().version(),
+                master.active_version().version().description()
             ),
             Self::PadUpdated(Ok(pad)) => write!(
                 f,
                 "Message::PadUpdated(Ok) id:{} version:({}, {})",
                 pad.id(),
-                pad.version().0,
-                pad.version().1,
+                pad.version().version(),
+                pad.version().description(),
             ),
             Self::PadUpdated(Err(error)) => {
                 write!(f, "Message::PadUpdated(ERROR) {}", error)
             }
+            Self::PadChanged(pad) => write!(
+                f,
+                "Message::PadChanged id:{} version:{}",
+                pad.id(),
+                pad.version().version()
+            ),
+            Self::UpdateSent => write!(f, "Message::UpdateSent"),
+            Self::SessionStarted(session) => write!(f, "Message::SessionStarted {session:?}"),
+            Self::SessionFailed(error) => write!(f, "Message::SessionFailed {error}"),
+            Self::Session(event) => write!(f, "Message::Session {event:?}"),
         }
     }
 }
@@ -125,6 +171,15 @@ pub enum Message {
     AddSheet(add_sheet::Message),
     Error(error::Message),
     SheetProperties(sheet_properties::Message),
+    Goto(goto::Message),
+    Checkpoint(checkpoint::Message),
+    CompactHistory(compact_history::Message),
+    FormatCells(format_cells::Message),
+    Styles(styles::Message),
+    ConditionalFormats(conditional_formats::Message),
+    MergeCells(merge_cells::Message),
+    Hyperlink(hyperlink::Message),
+    Validations(validations::Message),
     PadProperties(pad_properties::Message),
     Data(DataEvent),
 }
@@ -142,6 +197,15 @@ impl std::fmt::Display for Message {
             Self::Error(msg) => msg.fmt(f),
             Self::PadProperties(msg) => msg.fmt(f),
             Self::SheetProperties(msg) => msg.fmt(f),
+            Self::Goto(msg) => msg.fmt(f),
+            Self::Checkpoint(msg) => msg.fmt(f),
+            Self::CompactHistory(msg) => msg.fmt(f),
+            Self::FormatCells(msg) => msg.fmt(f),
+            Self::Styles(msg) => msg.fmt(f),
+            Self::ConditionalFormats(msg) => msg.fmt(f),
+            Self::MergeCells(msg) => msg.fmt(f),
+            Self::Hyperlink(msg) => msg.fmt(f),
+            Self::Validations(msg) => msg.fmt(f),
             Self::Data(msg) => msg.fmt(f),
         }
     }
@@ -153,6 +217,7 @@ pub struct Flexpad {
     version: Version,
     screen: Screen,
     dialog: Dialog,
+    session: Option<Session>,
 }
 
 impl Application for Flexpad {
@@ -168,6 +233,7 @@ impl Application for Flexpad {
                 version: Default::default(),
                 screen: Screen::Loading(screen),
                 dialog: Default::default(),
+                session: None,
             },
             Command::batch(vec![
                 loading_command,
@@ -199,6 +265,7 @@ impl Application for Flexpad {
                 match ui.update(m) {
                     lobby::Event::NewBlankWorkpadRequested => new_blank_workpad(),
                     lobby::Event::NewStarterWorkpadRequested => new_starter_workpad(),
+                    lobby::Event::JoinWorkpadRequested => join_workpad(),
                 }
             }
             Message::ActiveSheet(m) => {
@@ -213,6 +280,7 @@ impl Application for Flexpad {
                         Command::none()
                     }
                     active_sheet::Event::CloseWorkpadRequested => {
+                        self.session = None;
                         self.screen = Screen::Lobby(lobby::Lobby::new(self.version));
                         Command::none()
                     }
@@ -222,13 +290,59 @@ impl Application for Flexpad {
                         );
                         Command::none()
                     }
+                    active_sheet::Event::GotoRequested(pad) => {
+                        self.dialog = Dialog::Goto(goto::GotoUi::new(pad));
+                        Command::none()
+                    }
+                    active_sheet::Event::CheckpointRequested(pad) => {
+                        self.dialog = Dialog::Checkpoint(checkpoint::CheckpointUi::new(pad));
+                        Command::none()
+                    }
+                    active_sheet::Event::CompactHistoryRequested(pad) => {
+                        self.dialog =
+                            Dialog::CompactHistory(compact_history::CompactHistoryUi::new(pad));
+                        Command::none()
+                    }
+                    active_sheet::Event::FormatCellsRequested(cell) => {
+                        self.dialog = Dialog::FormatCells(format_cells::FormatCellsUi::new(cell));
+                        Command::none()
+                    }
+                    active_sheet::Event::StylesRequested(cell) => {
+                        self.dialog = Dialog::Styles(styles::StylesUi::new(cell));
+                        Command::none()
+                    }
+                    active_sheet::Event::ConditionalFormatsRequested(cell) => {
+                        self.dialog = Dialog::ConditionalFormats(
+                            conditional_formats::ConditionalFormatsUi::new(cell),
+                        );
+                        Command::none()
+                    }
+                    active_sheet::Event::MergeCellsRequested(cell) => {
+                        self.dialog = Dialog::MergeCells(merge_cells::MergeCellsUi::new(cell));
+                        Command::none()
+                    }
+                    active_sheet::Event::HyperlinkRequested(cell) => {
+                        self.dialog = Dialog::Hyperlink(hyperlink::HyperlinkUi::new(cell));
+                        Command::none()
+                    }
+                    active_sheet::Event::HyperlinkFailed(message) => {
+                        self.dialog = Dialog::Error(error::ErrorUi::new(message));
+                        Command::none()
+                    }
+                    active_sheet::Event::ValidationsRequested(cell) => {
+                        self.dialog = Dialog::Validations(validations::ValidationsUi::new(cell));
+                        Command::none()
+                    }
                     active_sheet::Event::AddSheetRequested(pad) => {
                         self.screen = Screen::AddSheet(add_sheet::AddSheetUi::new(pad));
                         Command::none()
                     }
+                    active_sheet::Event::ShareRequested(pad) => share_workpad(pad),
+                    active_sheet::Event::JoinRequested => join_workpad(),
                     active_sheet::Event::UpdateRequested(master, update) => {
-                        update_pad(master, update)
+                        update_pad(self.session.as_ref(), master, update)
                     }
+                    active_sheet::Event::Command(command) => command.map(Message::ActiveSheet),
                 }
             }
             Message::AddSheet(m) => {
@@ -243,16 +357,19 @@ impl Application for Flexpad {
                         Command::none()
                     }
                     add_sheet::Event::CloseWorkpadRequested => {
+                        self.session = None;
                         self.screen = Screen::Lobby(lobby::Lobby::new(self.version));
                         Command::none()
                     }
                     add_sheet::Event::Cancelled(pad) => {
                         // Can only cancel if there are sheets present
                         let sheet = pad.active_sheet().unwrap();
-                        self.screen = Screen::ActiveSheet(active_sheet::ActiveSheetUi::new(sheet));
+                        self.screen = active_sheet_screen(sheet, self.session.as_ref());
                         Command::none()
                     }
-                    add_sheet::Event::Submitted(master, update) => update_pad(master, update),
+                    add_sheet::Event::Submitted(master, update) => {
+                        update_pad(self.session.as_ref(), master, update)
+                    }
                 }
             }
             Message::Error(m) => {
@@ -278,7 +395,7 @@ impl Application for Flexpad {
                     }
                     pad_properties::Event::Submitted(master, update) => {
                         self.dialog = Dialog::None;
-                        update_pad(master, update)
+                        update_pad(self.session.as_ref(), master, update)
                     }
                 }
             }
@@ -294,7 +411,153 @@ impl Application for Flexpad {
                     }
                     sheet_properties::Event::Submitted(master, update) => {
                         self.dialog = Dialog::None;
-                        update_pad(master, update)
+                        update_pad(self.session.as_ref(), master, update)
+                    }
+                }
+            }
+            Message::Goto(m) => {
+                let Dialog::Goto(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    goto::Event::None => Command::none(),
+                    goto::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    goto::Event::Submitted(reference) => {
+                        self.dialog = Dialog::None;
+                        self.update(Message::ActiveSheet(active_sheet::Message::GotoReference(
+                            reference,
+                        )))
+                    }
+                }
+            }
+            Message::Checkpoint(m) => {
+                let Dialog::Checkpoint(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    checkpoint::Event::None => Command::none(),
+                    checkpoint::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    checkpoint::Event::Submitted(master, update) => {
+                        self.dialog = Dialog::None;
+                        update_pad(self.session.as_ref(), master, update)
+                    }
+                }
+            }
+            Message::CompactHistory(m) => {
+                let Dialog::CompactHistory(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    compact_history::Event::None => Command::none(),
+                    compact_history::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    compact_history::Event::Submitted(master, retain) => {
+                        self.dialog = Dialog::None;
+                        compact_history(master, retain)
+                    }
+                }
+            }
+            Message::FormatCells(m) => {
+                let Dialog::FormatCells(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    format_cells::Event::None => Command::none(),
+                    format_cells::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    format_cells::Event::Submitted(master, update) => {
+                        self.dialog = Dialog::None;
+                        update_pad(self.session.as_ref(), master, update)
+                    }
+                }
+            }
+            Message::Styles(m) => {
+                let Dialog::Styles(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    styles::Event::None => Command::none(),
+                    styles::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    styles::Event::Submitted(master, update) => {
+                        self.dialog = Dialog::None;
+                        update_pad(self.session.as_ref(), master, update)
+                    }
+                }
+            }
+            Message::ConditionalFormats(m) => {
+                let Dialog::ConditionalFormats(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    conditional_formats::Event::None => Command::none(),
+                    conditional_formats::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    conditional_formats::Event::Submitted(master, update) => {
+                        self.dialog = Dialog::None;
+                        update_pad(self.session.as_ref(), master, update)
+                    }
+                }
+            }
+            Message::Validations(m) => {
+                let Dialog::Validations(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    validations::Event::None => Command::none(),
+                    validations::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    validations::Event::Submitted(master, update) => {
+                        self.dialog = Dialog::None;
+                        update_pad(self.session.as_ref(), master, update)
+                    }
+                }
+            }
+            Message::MergeCells(m) => {
+                let Dialog::MergeCells(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    merge_cells::Event::None => Command::none(),
+                    merge_cells::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    merge_cells::Event::Submitted(master, update) => {
+                        self.dialog = Dialog::None;
+                        update_pad(self.session.as_ref(), master, update)
+                    }
+                }
+            }
+            Message::Hyperlink(m) => {
+                let Dialog::Hyperlink(ui) = &mut self.dialog else {
+                    unreachable!()
+                };
+                match ui.update(m) {
+                    hyperlink::Event::None => Command::none(),
+                    hyperlink::Event::Cancelled => {
+                        self.dialog = Dialog::None;
+                        Command::none()
+                    }
+                    hyperlink::Event::Submitted(master, update) => {
+                        self.dialog = Dialog::None;
+                        update_pad(self.session.as_ref(), master, update)
                     }
                 }
             }
@@ -307,13 +570,21 @@ impl Application for Flexpad {
                     };
                     Command::none()
                 }
+                DataEvent::SessionStarted(session) => {
+                    let pad = session.master().active_version();
+                    self.session = Some(session);
+                    self.screen = match pad.active_sheet() {
+                        Some(sheet) => active_sheet_screen(sheet, self.session.as_ref()),
+                        None => Screen::AddSheet(add_sheet::AddSheetUi::new(pad.clone())),
+                    };
+                    Command::none()
+                }
                 DataEvent::PadUpdated(Ok(pad)) => match pad.active_sheet() {
                     Some(sheet) => {
                         if let Screen::ActiveSheet(ui) = &mut self.screen {
                             ui.pad_updated(pad).map(Message::ActiveSheet)
                         } else {
-                            self.screen =
-                                Screen::ActiveSheet(active_sheet::ActiveSheetUi::new(sheet));
+                            self.screen = active_sheet_screen(sheet, self.session.as_ref());
                             Command::none()
                         }
                     }
@@ -322,10 +593,29 @@ impl Application for Flexpad {
                         Command::none()
                     }
                 },
+                DataEvent::PadChanged(pad) => match &mut self.screen {
+                    // Changes made by this view have usually been seen already (as PadUpdated)
+                    Screen::ActiveSheet(ui)
+                        if ui.active_sheet.workpad().id() == pad.id()
+                            && ui.active_sheet.workpad().version().version()
+                                != pad.version().version()
+                            && pad.active_sheet().is_some() =>
+                    {
+                        ui.pad_updated(pad).map(Message::ActiveSheet)
+                    }
+                    _ => Command::none(),
+                },
                 DataEvent::PadUpdated(Err(err)) => {
                     self.dialog = Dialog::Error(error::ErrorUi::new(err.to_string()));
                     Command::none()
                 }
+                DataEvent::UpdateSent => Command::none(),
+                DataEvent::SessionFailed(err) => {
+                    let message = t!("Session.Failed").replace("{error}", &err.to_string());
+                    self.dialog = Dialog::Error(error::ErrorUi::new(message));
+                    Command::none()
+                }
+                DataEvent::Session(event) => self.session_event(event),
             },
         }
     }
@@ -359,11 +649,40 @@ impl Application for Flexpad {
             Dialog::SheetProperties(ui) => {
                 Modal::new(screen, ui.view().map(Message::SheetProperties)).into()
             }
+            Dialog::Goto(ui) => Modal::new(screen, ui.view().map(Message::Goto)).into(),
+            Dialog::Checkpoint(ui) => {
+                Modal::new(screen, ui.view().map(Message::Checkpoint)).into()
+            }
+            Dialog::CompactHistory(ui) => {
+                Modal::new(screen, ui.view().map(Message::CompactHistory)).into()
+            }
+            Dialog::FormatCells(ui) => {
+                Modal::new(screen, ui.view().map(Message::FormatCells)).into()
+            }
+            Dialog::Styles(ui) => Modal::new(screen, ui.view().map(Message::Styles)).into(),
+            Dialog::ConditionalFormats(ui) => {
+                Modal::new(screen, ui.view().map(Message::ConditionalFormats)).into()
+            }
+            Dialog::MergeCells(ui) => Modal::new(screen, ui.view().map(Message::MergeCells)).into(),
+            Dialog::Hyperlink(ui) => Modal::new(screen, ui.view().map(Message::Hyperlink)).into(),
+            Dialog::Validations(ui) => {
+                Modal::new(screen, ui.view().map(Message::Validations)).into()
+            }
         }
     }
 
     fn subscription(&self) -> iced::Subscription<Self::Message> {
-        match &self.dialog {
+        let pad_changes = match &self.screen {
+            Screen::ActiveSheet(ui) => pad_changes(ui.active_sheet.workpad().master()),
+            _ => iced::Subscription::none(),
+        };
+
+        let session_events = match &self.session {
+            Some(session) => session_events(session.clone()),
+            None => iced::Subscription::none(),
+        };
+
+        let ui_subscription = match &self.dialog {
             Dialog::None => match &self.screen {
                 Screen::Loading(_) => iced::Subscription::none(),
                 Screen::Lobby(ui) => ui.subscription().map(Message::Lobby),
@@ -373,10 +692,60 @@ impl Application for Flexpad {
             Dialog::Error(ui) => ui.subscription().map(Message::Error),
             Dialog::PadProperties(ui) => ui.subscription().map(Message::PadProperties),
             Dialog::SheetProperties(ui) => ui.subscription().map(Message::SheetProperties),
-        }
+            Dialog::Goto(ui) => ui.subscription().map(Message::Goto),
+            Dialog::Checkpoint(ui) => ui.subscription().map(Message::Checkpoint),
+            Dialog::CompactHistory(ui) => ui.subscription().map(Message::CompactHistory),
+            Dialog::FormatCells(ui) => ui.subscription().map(Message::FormatCells),
+            Dialog::Styles(ui) => ui.subscription().map(Message::Styles),
+            Dialog::ConditionalFormats(ui) => ui.subscription().map(Message::ConditionalFormats),
+            Dialog::MergeCells(ui) => ui.subscription().map(Message::MergeCells),
+            Dialog::Hyperlink(ui) => ui.subscription().map(Message::Hyperlink),
+            Dialog::Validations(ui) => ui.subscription().map(Message::Validations),
+        };
+
+        iced::Subscription::batch(vec![pad_changes, session_events, ui_subscription])
+    }
+}
+
+impl Flexpad {
+    fn session_event(&mut self, event: SessionEvent) -> Command<Message> {
+        let message = match event {
+            SessionEvent::CollaboratorsChanged => {
+                if let (Screen::ActiveSheet(ui), Some(session)) = (&mut self.screen, &self.session)
+                {
+                    ui.collaborators_updated(Some(session.collaborators()));
+                }
+                return Command::none();
+            }
+            SessionEvent::Rejected(err) => err.to_string(),
+            SessionEvent::Conflicted { cells, user } => {
+                let cells = cells.iter().map(|cell| cell.name()).collect::<Vec<_>>();
+                t!("Session.Conflicted")
+                    .replace("{cells}", &cells.join(", "))
+                    .replace("{user}", &user)
+            }
+            SessionEvent::Disconnected => {
+                self.session = None;
+                if let Screen::ActiveSheet(ui) = &mut self.screen {
+                    ui.collaborators_updated(None);
+                }
+                t!("Session.Disconnected")
+            }
+        };
+        self.dialog = Dialog::Error(error::ErrorUi::new(message));
+        Command::none()
     }
 }
 
+/// Returns the screen for the active sheet of a workpad, showing its collaborators if shared
+fn active_sheet_screen(sheet: Sheet, session: Option<&Session>) -> Screen {
+    let mut ui = active_sheet::ActiveSheetUi::new(sheet);
+    if let Some(session) = session {
+        ui.collaborators_updated(Some(session.collaborators()));
+    }
+    Screen::ActiveSheet(ui)
+}
+
 /// Create a [`Command`] to open a new blank workpad
 fn new_blank_workpad() -> Command<Message> {
     Command::perform(new_blank_workpad_async(), DataEvent::PadOpened).map(Message::Data)
@@ -387,9 +756,92 @@ fn new_starter_workpad() -> Command<Message> {
     Command::perform(new_starter_workpad_async(), DataEvent::PadOpened).map(Message::Data)
 }
 
-/// Create a [`Command`] to update a workpad
-fn update_pad(master: WorkpadMaster, update: WorkpadUpdate) -> Command<Message> {
-    Command::perform(update_pad_async(master, update), DataEvent::PadUpdated).map(Message::Data)
+/// Create a [`Command`] to update a workpad, through its session when shared
+fn update_pad(
+    session: Option<&Session>,
+    master: WorkpadMaster,
+    update: WorkpadUpdate,
+) -> Command<Message> {
+    match session {
+        Some(session) if session.master().id() == master.id() => {
+            Command::perform(update_session_async(session.clone(), update), |result| {
+                match result {
+                    Ok(Some(pad)) => DataEvent::PadUpdated(Ok(pad)),
+                    // Shared changes are seen once relayed (as PadChanged)
+                    Ok(None) => DataEvent::UpdateSent,
+                    Err(SyncError::Update(err)) => DataEvent::PadUpdated(Err(err)),
+                    Err(err) => DataEvent::SessionFailed(err),
+                }
+            })
+            .map(Message::Data)
+        }
+        _ => Command::perform(update_pad_async(master, update), DataEvent::PadUpdated)
+            .map(Message::Data),
+    }
+}
+
+/// Create a [`Command`] to compact the history of a workpad
+fn compact_history(master: WorkpadMaster, retain: Option<usize>) -> Command<Message> {
+    Command::perform(compact_history_async(master, retain), |pad| {
+        DataEvent::PadUpdated(Ok(pad))
+    })
+    .map(Message::Data)
+}
+
+/// Create a [`Command`] to share a workpad with collaborators
+fn share_workpad(pad: Workpad) -> Command<Message> {
+    Command::perform(share_workpad_async(pad), session_started).map(Message::Data)
+}
+
+/// Create a [`Command`] to join a workpad shared by collaborators
+fn join_workpad() -> Command<Message> {
+    Command::perform(join_workpad_async(), session_started).map(Message::Data)
+}
+
+fn session_started(result: Result<Session, SyncError>) -> DataEvent {
+    match result {
+        Ok(session) => DataEvent::SessionStarted(session),
+        Err(err) => DataEvent::SessionFailed(err),
+    }
+}
+
+/// Create a [`iced::Subscription`] to the events of the session sharing a workpad
+fn session_events(session: Session) -> iced::Subscription<Message> {
+    let id = ("session", session.master().id().to_owned());
+    iced::subscription::channel(id, 100, |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+        session.listen(move |event| {
+            // Only fails once the subscription (and so the receiver) has been dropped
+            let _ = sender.unbounded_send(event.clone());
+        });
+
+        loop {
+            let event = receiver.select_next_some().await;
+            let _ = output.send(Message::Data(DataEvent::Session(event))).await;
+        }
+    })
+}
+
+/// Create a [`iced::Subscription`] to the changes made to a workpad, however they were made
+fn pad_changes(master: WorkpadMaster) -> iced::Subscription<Message> {
+    let id = master.id().to_owned();
+    iced::subscription::channel(id, 100, |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+        let _observation = master.observe(move |event| {
+            // Only fails once the subscription (and so the receiver) has been dropped
+            let _ = sender.unbounded_send(event.version());
+        });
+
+        loop {
+            // The observer holds the sender so the receiver never ends
+            let version = receiver.select_next_some().await;
+            let pad = master.active_version();
+            // Skip changes overtaken by later ones
+            if pad.version().version() == version {
+                let _ = output.send(Message::Data(DataEvent::PadChanged(pad))).await;
+            }
+        }
+    })
 }
 
 async fn new_blank_workpad_async() -> WorkpadMaster {
@@ -407,6 +859,45 @@ async fn update_pad_async(mut master: WorkpadMaster, update: WorkpadUpdate) -> U
     master.update(update)
 }
 
+async fn compact_history_async(mut master: WorkpadMaster, retain: Option<usize>) -> Workpad {
+    info!(target: "flexpad", ?retain, "compact_history");
+    master.compact_history(retain)
+}
+
+async fn update_session_async(
+    session: Session,
+    update: WorkpadUpdate,
+) -> Result<Option<Workpad>, SyncError> {
+    info!(target: "flexpad", %update, "Session update");
+    unblock(move || session.update(update)).await
+}
+
+/// The address of the relay through which workpads are shared
+fn relay_address() -> String {
+    std::env::var("FLEXPAD_RELAY").unwrap_or_else(|_| DEFAULT_RELAY_ADDRESS.to_owned())
+}
+
+async fn share_workpad_async(pad: Workpad) -> Result<Session, SyncError> {
+    info!(target: "flexpad", "share_workpad");
+    unblock(move || Session::share(relay_address(), &pad, &whoami::realname())).await
+}
+
+async fn join_workpad_async() -> Result<Session, SyncError> {
+    info!(target: "flexpad", "join_workpad");
+    unblock(|| Session::join(relay_address(), &whoami::realname())).await
+}
+
+/// Run work that blocks, such as talking to the relay, on a thread of its own so that it
+/// does not hold up the executor running commands
+async fn unblock<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    let (sender, receiver) = oneshot::channel();
+    std::thread::spawn(move || {
+        // Only fails if the command, and so the receiver, has been dropped
+        let _ = sender.send(work());
+    });
+    receiver.await.expect("Blocking work panicked")
+}
+
 #[derive(Debug)]
 enum FlexpadError {
     IcedError(iced::Error),
@@ -1,5 +1,5 @@
 use crate::FlexpadAction;
-use flexpad_toolkit::menu;
+use flexpad_toolkit::{menu, prelude::*};
 use rust_i18n::t;
 
 fn root<Message>() -> menu::PathToMenu<Message>
@@ -22,3 +22,93 @@ where
 {
     menu::Path::new(root(), FlexpadAction::Redo, on_select)
 }
+
+pub fn history<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(root().section("history"), FlexpadAction::History, on_select)
+}
+
+pub fn cell_history<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(
+        root().section("history"),
+        FlexpadAction::CellHistory,
+        on_select,
+    )
+}
+
+pub fn cell_comments<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(
+        root().section("comments"),
+        FlexpadAction::CellComments,
+        on_select,
+    )
+}
+
+pub fn comments<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(
+        root().section("comments"),
+        FlexpadAction::Comments,
+        on_select,
+    )
+}
+
+pub fn hyperlink<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(root().section("links"), FlexpadAction::Hyperlink, on_select)
+}
+
+pub fn checkpoint<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(
+        root().section("history"),
+        FlexpadAction::Checkpoint,
+        on_select,
+    )
+}
+
+pub fn compare<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(root().section("history"), FlexpadAction::Compare, on_select)
+}
+
+pub fn goto_checkpoint<Message>(label: String, on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(
+        root().submenu(t!("Checkpoint.Menu")),
+        Action::new(label),
+        on_select,
+    )
+}
+
+pub fn find<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(root().section("find"), FlexpadAction::Find, on_select)
+}
+
+pub fn goto<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(root().section("find"), FlexpadAction::Goto, on_select)
+}
@@ -16,6 +16,13 @@ where
     root().section("1")
 }
 
+fn section_2<Message>() -> menu::PathToMenuSection<Message>
+where
+    Message: Clone,
+{
+    root().section("2")
+}
+
 pub fn new_blank_workpad<Message>(on_select: Option<Message>) -> menu::Path<Message>
 where
     Message: Clone,
@@ -37,6 +44,13 @@ where
     menu::Path::new(section_1(), FlexpadAction::PadProperties, on_select)
 }
 
+pub fn compact_history<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(section_1(), FlexpadAction::PadCompact, on_select)
+}
+
 pub fn delete_pad<Message>(on_select: Option<Message>) -> menu::Path<Message>
 where
     Message: Clone,
@@ -50,3 +64,17 @@ where
 {
     menu::Path::new(section_1(), FlexpadAction::PadClose, on_select)
 }
+
+pub fn share_pad<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(section_2(), FlexpadAction::PadShare, on_select)
+}
+
+pub fn join_pad<Message>(on_select: Option<Message>) -> menu::Path<Message>
+where
+    Message: Clone,
+{
+    menu::Path::new(section_2(), FlexpadAction::PadJoin, on_select)
+}
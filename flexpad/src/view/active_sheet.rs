@@ -1,5 +1,14 @@
 use crate::{
     menu::*,
+    view::{
+        cell_comments::{self, CellCommentsUi},
+        cell_history::{self, CellHistoryUi},
+        comments::{self, CommentsUi},
+        compare::{self, CompareUi},
+        find::{self, FindUi, FIND_INPUT_ID},
+        history::{self, HistoryUi},
+        hyperlink,
+    },
     widget::{
         active_cell::{self, Editor},
         inactive_cell,
@@ -10,24 +19,85 @@ use flexpad_grid::{
     style, Border, Borders, CellRange, ColumnHead, Grid, GridCell, GridCorner, GridScrollable,
     RowCol, RowHead, SumSeq, Viewport,
 };
-use flexpad_model::{Cell, Sheet, SheetId, Version, Workpad, WorkpadMaster, WorkpadUpdate};
+use flexpad_model::{
+    Cell, CellReference, FindDirection, FindScope, Finder, Format, HorizontalAlignment, Hyperlink,
+    Sheet, SheetId, TextOverflow, Version, VerticalAlignment, Workpad, WorkpadMaster,
+    WorkpadUpdate,
+};
+use flexpad_sync::Collaborator;
 use flexpad_toolkit::{menu, prelude::*, toolbar::Toolbar};
 use iced::{
     advanced::{mouse::click, widget},
-    alignment, theme,
+    alignment, event, font, keyboard, subscription, theme,
     widget::{
-        button, column, container, horizontal_rule, horizontal_space, row, text, vertical_rule,
+        button, column, container, horizontal_rule, horizontal_space, row, scrollable, text,
+        text_input, tooltip, vertical_rule, Column,
     },
-    Alignment, Color, Command, Element, Length, Pixels, Subscription,
+    Alignment, Color, Command, Element, Font, Length, Pixels,
 };
+use internment::Intern;
 use once_cell::sync::Lazy;
 use rust_i18n::t;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 use tracing::debug;
 
 static FORMULA_BAR_ID: Lazy<active_cell::Id> = Lazy::new(active_cell::Id::unique);
 static ACTIVE_CELL_ID: Lazy<active_cell::Id> = Lazy::new(active_cell::Id::unique);
 
+/// The size of the text of cells whose format does not set one
+const DEFAULT_FONT_SIZE: f32 = 10.0;
+
+/// The most rows the list of values allowed in the active cell covers before it scrolls
+const MAX_VALIDATION_LIST_ROWS: usize = 8;
+
+/// The colour of the border highlighting the active cell
+const ACTIVE_CELL_COLOR: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+/// The colour of the borders highlighting the cells changed since a compared version
+const CHANGED_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.6,
+    b: 0.0,
+    a: 1.0,
+};
+
+/// The colours of the borders showing the active cells of collaborators
+const COLLABORATOR_COLORS: [Color; 4] = [
+    Color {
+        r: 0.9,
+        g: 0.1,
+        b: 0.5,
+        a: 1.0,
+    },
+    Color {
+        r: 0.2,
+        g: 0.7,
+        b: 0.2,
+        a: 1.0,
+    },
+    Color {
+        r: 0.6,
+        g: 0.3,
+        b: 0.9,
+        a: 1.0,
+    },
+    Color {
+        r: 0.8,
+        g: 0.7,
+        b: 0.0,
+        a: 1.0,
+    },
+];
+
 pub static GRID_SCROLLABLE_ID: Lazy<flexpad_grid::scroll::Id> =
     Lazy::new(flexpad_grid::scroll::Id::unique);
 
@@ -48,8 +118,39 @@ pub enum Message {
     SheetAdd,
     PadClose,
     PadShowProperties,
+    PadCompactHistory,
+    PadShare,
+    PadJoin,
+    FormatBold,
+    FormatItalic,
+    FormatUnderline,
+    FormatCellsShow,
+    StylesShow,
+    ConditionalFormatsShow,
+    MergeCellsShow,
+    UnmergeCells,
     SetActiveSheet(SheetId),
     GotoVersion(Version),
+    FindShow,
+    Find(find::Message),
+    GotoShow,
+    GotoReference(CellReference),
+    HistoryShow,
+    History(history::Message),
+    CheckpointShow,
+    CompareShow,
+    Compare(compare::Message),
+    CellHistoryShow,
+    CellHistory(cell_history::Message),
+    CellCommentsShow,
+    CellComments(cell_comments::Message),
+    CommentsShow,
+    Comments(comments::Message),
+    HyperlinkShow,
+    HyperlinkActivate(RowCol),
+    ValidationsShow,
+    ValidationListToggle,
+    ValidationListSelect(String),
 }
 
 impl std::fmt::Display for Message {
@@ -65,9 +166,42 @@ impl std::fmt::Display for Message {
             Self::SheetDelete => write!(f, "DeleteSheet"),
             Self::SheetAdd => write!(f, "AddSheet"),
             Self::PadShowProperties => write!(f, "PadShowProperties"),
+            Self::PadCompactHistory => write!(f, "PadCompactHistory"),
             Self::PadClose => write!(f, "PadClose"),
+            Self::PadShare => write!(f, "PadShare"),
+            Self::PadJoin => write!(f, "PadJoin"),
+            Self::FormatBold => write!(f, "FormatBold"),
+            Self::FormatItalic => write!(f, "FormatItalic"),
+            Self::FormatUnderline => write!(f, "FormatUnderline"),
+            Self::FormatCellsShow => write!(f, "FormatCellsShow"),
+            Self::StylesShow => write!(f, "StylesShow"),
+            Self::ConditionalFormatsShow => write!(f, "ConditionalFormatsShow"),
+            Self::MergeCellsShow => write!(f, "MergeCellsShow"),
+            Self::UnmergeCells => write!(f, "UnmergeCells"),
             Self::SetActiveSheet(id) => write!(f, "SetActiveSheet({id})"),
             Self::GotoVersion(version) => write!(f, "GotoVersion({version})"),
+            Self::FindShow => write!(f, "FindShow"),
+            Self::Find(msg) => write!(f, "Find({msg})"),
+            Self::GotoShow => write!(f, "GotoShow"),
+            Self::GotoReference(reference) => {
+                write!(f, "GotoReference({})", reference.first_cell().name())
+            }
+            Self::HistoryShow => write!(f, "HistoryShow"),
+            Self::History(msg) => write!(f, "History({msg})"),
+            Self::CheckpointShow => write!(f, "CheckpointShow"),
+            Self::CompareShow => write!(f, "CompareShow"),
+            Self::Compare(msg) => write!(f, "Compare({msg})"),
+            Self::CellHistoryShow => write!(f, "CellHistoryShow"),
+            Self::CellHistory(msg) => write!(f, "CellHistory({msg})"),
+            Self::CellCommentsShow => write!(f, "CellCommentsShow"),
+            Self::CellComments(msg) => write!(f, "CellComments({msg})"),
+            Self::CommentsShow => write!(f, "CommentsShow"),
+            Self::Comments(msg) => write!(f, "Comments({msg})"),
+            Self::HyperlinkShow => write!(f, "HyperlinkShow"),
+            Self::HyperlinkActivate(rc) => write!(f, "HyperlinkActivate({rc})"),
+            Self::ValidationsShow => write!(f, "ValidationsShow"),
+            Self::ValidationListToggle => write!(f, "ValidationListToggle"),
+            Self::ValidationListSelect(value) => write!(f, "ValidationListSelect({value})"),
         }
     }
 }
@@ -82,28 +216,135 @@ pub enum Move {
     JumpRight,
     JumpUp,
     JumpDown,
+    PageUp,
+    PageDown,
+    PageLeft,
+    PageRight,
+    Home,
+    End,
+    First,
+    Last,
     To(RowCol),
 }
 
 impl Move {
-    fn apply(&self, position: RowCol, rows_count: usize, columns_count: usize) -> RowCol {
+    /// Applies this move to a position on a sheet where `page` is the range of cells
+    /// currently visible (which determines the distance moved when paging)
+    fn apply(&self, position: RowCol, sheet: &Sheet, page: &CellRange) -> RowCol {
         let RowCol { row, column } = position;
-        let max_column = columns_count.saturating_sub(1);
-        let max_row = rows_count.saturating_sub(1);
+        let max = RowCol::new(
+            sheet.row_count().saturating_sub(1),
+            sheet.column_count().saturating_sub(1),
+        );
+        let page_rows = page.rows().len().max(1);
+        let page_columns = page.columns().len().max(1);
+        let is_filled = |rc: RowCol| !cell_by_rc(sheet, rc).value().is_empty();
+        let used = sheet.used_range().map(|used_range| {
+            (
+                RowCol::new(*used_range.rows().start(), *used_range.columns().start()),
+                RowCol::new(*used_range.rows().end(), *used_range.columns().end()),
+            )
+        });
+
         match self {
             Move::Left => RowCol::new(row, column.saturating_sub(1)),
-            Move::Right => RowCol::new(row, (column + 1).min(max_column)),
+            Move::Right => RowCol::new(row, (column + 1).min(max.column)),
             Move::Up => RowCol::new(row.saturating_sub(1), column),
-            Move::Down => RowCol::new((row + 1).min(max_row), column),
-            Move::JumpLeft => RowCol::new(row, 0),
-            Move::JumpRight => RowCol::new(row, max_column),
-            Move::JumpUp => RowCol::new(0, column),
-            Move::JumpDown => RowCol::new(max_row, column),
+            Move::Down => RowCol::new((row + 1).min(max.row), column),
+            Move::JumpLeft => jump(position, (0, -1), max, used, is_filled),
+            Move::JumpRight => jump(position, (0, 1), max, used, is_filled),
+            Move::JumpUp => jump(position, (-1, 0), max, used, is_filled),
+            Move::JumpDown => jump(position, (1, 0), max, used, is_filled),
+            Move::PageUp => RowCol::new(row.saturating_sub(page_rows), column),
+            Move::PageDown => RowCol::new((row + page_rows).min(max.row), column),
+            Move::PageLeft => RowCol::new(row, column.saturating_sub(page_columns)),
+            Move::PageRight => RowCol::new(row, (column + page_columns).min(max.column)),
+            Move::Home => RowCol::new(row, 0),
+            Move::End => RowCol::new(
+                row,
+                sheet
+                    .used_range()
+                    .map_or(0, |used_range| *used_range.columns().end()),
+            ),
+            Move::First => sheet.used_range().map_or(RowCol::TOP_LEFT, |used_range| {
+                rc_of_cell(&used_range.first_cell())
+            }),
+            Move::Last => sheet.used_range().map_or(RowCol::TOP_LEFT, |used_range| {
+                rc_of_cell(&used_range.last_cell())
+            }),
             Move::To(rc) => *rc,
         }
     }
 }
 
+/// Jumps in a direction the way spreadsheets do: from within a block of non-empty cells to
+/// the edge of that block, otherwise to the start of the next block (or the edge of the sheet).
+/// `used` is the first and last cells of the sheet's used range, beyond which no cell is
+/// filled, so there is no need to look at the (possibly very many) cells past it.
+fn jump(
+    position: RowCol,
+    (row_step, column_step): (isize, isize),
+    max: RowCol,
+    used: Option<(RowCol, RowCol)>,
+    is_filled: impl Fn(RowCol) -> bool,
+) -> RowCol {
+    let offset = |index: usize, step: isize| match step {
+        0.. => index.checked_add(step as usize),
+        _ => index.checked_sub(step.unsigned_abs()),
+    };
+    let step = |rc: RowCol| {
+        let row = offset(rc.row, row_step)?;
+        let column = offset(rc.column, column_step)?;
+        (row <= max.row && column <= max.column).then(|| RowCol::new(row, column))
+    };
+    let edge = RowCol::new(
+        match row_step {
+            1.. => max.row,
+            0 => position.row,
+            _ => 0,
+        },
+        match column_step {
+            1.. => max.column,
+            0 => position.column,
+            _ => 0,
+        },
+    );
+    // Whether neither a cell nor any of the cells after it in the direction of the jump
+    // can be filled
+    let is_past_used = |rc: RowCol| match used {
+        None => true,
+        Some((first, last)) => {
+            let is_past = |index: usize, step: isize, first: usize, last: usize| match step {
+                1.. => index > last,
+                0 => index < first || index > last,
+                _ => index < first,
+            };
+            is_past(rc.row, row_step, first.row, last.row)
+                || is_past(rc.column, column_step, first.column, last.column)
+        }
+    };
+
+    let Some(mut current) = step(position) else {
+        return position;
+    };
+    if is_filled(position) && is_filled(current) {
+        while let Some(next) = step(current).filter(|rc| is_filled(*rc)) {
+            current = next;
+        }
+    } else {
+        while !is_filled(current) {
+            if is_past_used(current) {
+                return edge;
+            }
+            match step(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+    }
+    current
+}
+
 impl std::fmt::Display for Move {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -115,6 +356,14 @@ impl std::fmt::Display for Move {
             Move::JumpRight => write!(f, "JumpRight"),
             Move::JumpUp => write!(f, "JumpUp"),
             Move::JumpDown => write!(f, "JumpDown"),
+            Move::PageUp => write!(f, "PageUp"),
+            Move::PageDown => write!(f, "PageDown"),
+            Move::PageLeft => write!(f, "PageLeft"),
+            Move::PageRight => write!(f, "PageRight"),
+            Move::Home => write!(f, "Home"),
+            Move::End => write!(f, "End"),
+            Move::First => write!(f, "First"),
+            Move::Last => write!(f, "Last"),
             Move::To(cell) => write!(f, "To({cell}"),
         }
     }
@@ -123,10 +372,23 @@ impl std::fmt::Display for Move {
 pub enum Event {
     None,
     EditPadPropertiesRequested(Workpad),
+    CompactHistoryRequested(Workpad),
     CloseWorkpadRequested,
     EditSheetPropertiesRequested(Sheet),
     AddSheetRequested(Workpad),
+    GotoRequested(Workpad),
+    CheckpointRequested(Workpad),
+    FormatCellsRequested(Cell),
+    StylesRequested(Cell),
+    ConditionalFormatsRequested(Cell),
+    MergeCellsRequested(Cell),
+    HyperlinkRequested(Cell),
+    HyperlinkFailed(String),
+    ValidationsRequested(Cell),
+    ShareRequested(Workpad),
+    JoinRequested,
     UpdateRequested(WorkpadMaster, WorkpadUpdate),
+    Command(Command<Message>),
 }
 
 #[derive(Debug)]
@@ -135,6 +397,20 @@ pub struct ActiveSheetUi {
     visible_cells: CellRange,
     active_cell: Option<(Cell, Rc<RefCell<active_cell::Editor>>)>,
     focus: widget::Id,
+    find: Option<FindUi>,
+    history: Option<HistoryUi>,
+    compare: Option<CompareUi>,
+    cell_history: Option<CellHistoryUi>,
+    cell_comments: Option<CellCommentsUi>,
+    comments: Option<CommentsUi>,
+    // Present only while the workpad is shared
+    collaborators: Option<Vec<Collaborator>>,
+    scroll_to_active_cell: bool,
+    // The cell whose list of allowed values is open beneath it, if any
+    validation_list: Option<RowCol>,
+    // The rows grown to fit the text of cells that wrap, with their heights, worked out
+    // once for each version of the sheet rather than on every render
+    row_heights: BTreeMap<usize, f32>,
 }
 
 impl ActiveSheetUi {
@@ -157,11 +433,23 @@ impl ActiveSheetUi {
             None => CellRange::empty(),
         };
 
+        let row_heights = wrapped_row_heights(&active_sheet);
+
         Self {
             active_sheet,
             visible_cells,
             active_cell,
             focus: ACTIVE_CELL_ID.clone().into(),
+            find: None,
+            history: None,
+            compare: None,
+            cell_history: None,
+            cell_comments: None,
+            comments: None,
+            collaborators: None,
+            scroll_to_active_cell: false,
+            validation_list: None,
+            row_heights,
         }
     }
 
@@ -170,13 +458,48 @@ impl ActiveSheetUi {
     }
 
     pub fn view(&self) -> iced::Element<'_, Message> {
-        column![
-            self.toolbar_view(),
-            self.sheet_and_formula_row_view(),
-            self.grid_view(),
-        ]
-        .align_items(Alignment::Start)
-        .into()
+        let mut content = column![self.toolbar_view(), self.sheet_and_formula_row_view()]
+            .align_items(Alignment::Start);
+        if let Some(find) = &self.find {
+            content = content.push(find.view().map(Message::Find));
+        }
+        if self.history.is_none()
+            && self.compare.is_none()
+            && self.cell_history.is_none()
+            && self.cell_comments.is_none()
+            && self.comments.is_none()
+        {
+            return content.push(self.grid_view()).into();
+        }
+
+        let pad = self.active_sheet.workpad();
+        let mut panels = row![self.grid_view()];
+        if let Some(compare) = &self.compare {
+            panels = panels
+                .push(vertical_rule(1))
+                .push(compare.view(&pad).map(Message::Compare));
+        }
+        if let Some(cell_history) = &self.cell_history {
+            panels = panels
+                .push(vertical_rule(1))
+                .push(cell_history.view().map(Message::CellHistory));
+        }
+        if let Some(cell_comments) = &self.cell_comments {
+            panels = panels
+                .push(vertical_rule(1))
+                .push(cell_comments.view().map(Message::CellComments));
+        }
+        if let Some(comments) = &self.comments {
+            panels = panels
+                .push(vertical_rule(1))
+                .push(comments.view(&pad).map(Message::Comments));
+        }
+        if let Some(history) = &self.history {
+            panels = panels
+                .push(vertical_rule(1))
+                .push(history.view(&pad).map(Message::History));
+        }
+        content.push(panels).into()
     }
 
     fn toolbar_view(&self) -> iced::Element<'_, Message> {
@@ -266,16 +589,41 @@ impl ActiveSheetUi {
 
     fn grid_view(&self) -> Element<'_, Message> {
         let active_sheet = &self.active_sheet;
-        // TODO Allow hetrogenious sizes
+
+        // Conditional formats are overlaid on the formatting of the cells
+        let conditional = active_sheet.conditional_evaluator();
+        let format_of = |cell: &Cell| {
+            let format = cell.resolved_format();
+            match conditional.format_of(cell) {
+                Some(conditional) => format.overlay(&conditional),
+                None => format,
+            }
+        };
+
+        // Values breaking a validation rule are flagged
+        let validator = active_sheet.validator();
+
+        // Merged cells are drawn once across their range as their top-left cell
+        let merged: Vec<CellRange> = active_sheet.merged_ranges().map(range_of).collect();
+        let is_merged = |rc: RowCol| merged.iter().any(|range| range.contains(&rc));
+
+        // TODO Allow hetrogenious column widths
+        let column_width = active_sheet.default_column_width();
         let mut widths = SumSeq::new();
-        widths.push_many(
-            active_sheet.columns().count() as u32,
-            active_sheet.columns().next().unwrap().width(),
-        );
+        widths.push_many(active_sheet.column_count() as u32, column_width);
+
+        // Rows grow to fit the text of cells that wrap
+        let default_row_height = active_sheet.default_row_height();
         let mut heights = SumSeq::new();
+        let mut next_row = 0;
+        for (&row, &height) in &self.row_heights {
+            heights.push_many((row - next_row) as u32, default_row_height);
+            heights.push(height);
+            next_row = row + 1;
+        }
         heights.push_many(
-            active_sheet.rows().count() as u32,
-            active_sheet.rows().next().unwrap().height(),
+            active_sheet.row_count().saturating_sub(next_row) as u32,
+            default_row_height,
         );
 
         // TODO Hardcoded text sizes
@@ -300,36 +648,174 @@ impl ActiveSheetUi {
             grid = grid.push_row_head(RowHead::new(rw, text(row.name()).size(12).line_height(1.0)))
         }
 
-        let active_cell_rc = self.active_cell.as_ref().map(|(cell, _)| rc_of_cell(cell));
-        for rc in self.visible_cells.cells() {
-            if Some(rc) != active_cell_rc {
+        let range_at = |rc: RowCol| {
+            merged
+                .iter()
+                .find(|range| range.contains(&rc))
+                .copied()
+                .unwrap_or_else(|| CellRange::new_single(rc))
+        };
+        let unmerged = self
+            .visible_cells
+            .cells()
+            .filter(|rc| !is_merged(*rc))
+            .map(CellRange::new_single);
+        let visible_merged = merged
+            .iter()
+            .filter(|range| range.intersects(&self.visible_cells))
+            .copied();
+
+        let active_range = self
+            .active_cell
+            .as_ref()
+            .map(|(cell, _)| range_at(rc_of_cell(cell)));
+
+        // The values a list rule allows in the active cell are picked from beneath it, hiding
+        // the cells the open list covers
+        let list_values = self.active_cell.as_ref().and_then(|(cell, _)| {
+            let rc = rc_of_cell(cell);
+            validator.list_of(rc.row, rc.column)
+        });
+        let list_range = list_values
+            .zip(active_range)
+            .filter(|_| {
+                self.validation_list == self.active_cell.as_ref().map(|(cell, _)| rc_of_cell(cell))
+            })
+            .and_then(|(values, range)| {
+                let first = range.rows().end;
+                let last = (first + values.len().min(MAX_VALIDATION_LIST_ROWS))
+                    .min(active_sheet.row_count());
+                (first < last).then(|| {
+                    CellRange::new(
+                        RowCol::new(first, range.columns().start),
+                        RowCol::new(last - 1, range.columns().end - 1),
+                    )
+                })
+            });
+        let is_covered =
+            |range: &CellRange| list_range.map_or(false, |list| list.intersects(range));
+
+        for range in unmerged.chain(visible_merged) {
+            if Some(range) != active_range && !is_covered(&range) {
+                let rc = RowCol::new(range.rows().start, range.columns().start);
                 let cell = cell_by_rc(active_sheet, rc);
-                let ic = inactive_cell::InactiveCell::new(rc, cell.value())
-                    // TODO Set details from spreadsheet data
-                    .horizontal_alignment(alignment::Horizontal::Center)
-                    .vertical_alignment(alignment::Vertical::Center)
-                    .font_size(10.0);
+                let style = CellStyle::of(&format_of(&cell));
+                let mut ic = inactive_cell::InactiveCell::new(rc, &cell.display_value())
+                    .horizontal_alignment(style.horizontal_alignment)
+                    .vertical_alignment(style.vertical_alignment)
+                    .font(style.font)
+                    .font_size(style.font_size)
+                    .text_color(style.text_color)
+                    .underline(style.underline)
+                    .text_overflow(style.text_overflow)
+                    .commented(!cell.comments().is_empty())
+                    .hyperlink(cell.hyperlink().is_some());
+                let broken = validator
+                    .broken_rules(rc.row, rc.column, cell.value())
+                    .next()
+                    .map(|(_, err)| {
+                        t!("ActiveSheet.InvalidValue").replace("{error}", &err.to_string())
+                    });
+                ic = ic.invalid(broken.is_some());
+                if style.text_overflow == TextOverflow::Overflow && range.count() == 1 {
+                    // Text overflows into the empty cells either side that are visible
+                    let is_empty = |rc: RowCol| {
+                        !is_merged(rc) && cell_by_rc(active_sheet, rc).display_value().is_empty()
+                    };
+                    let columns = self.visible_cells.columns();
+                    let left = (columns.start..rc.column)
+                        .rev()
+                        .take_while(|&column| is_empty(RowCol::new(rc.row, column)))
+                        .count();
+                    let right = (rc.column + 1..columns.end)
+                        .take_while(|&column| is_empty(RowCol::new(rc.row, column)))
+                        .count();
+                    ic = ic.spill(left as f32 * column_width, right as f32 * column_width);
+                }
 
-                let grid_cell = GridCell::new(rc, ic);
+                let borders = if let Some(color) = self.collaborator_color(rc) {
+                    Borders::new(Border::new(2.0, color))
+                } else if self.is_changed(rc) {
+                    Borders::new(Border::new(1.0, CHANGED_COLOR))
+                } else {
+                    style.borders
+                };
+                // Hovering over a cell with comments, or a value breaking a rule, shows them
+                let summary = match (broken, cell.comments()) {
+                    (broken, []) => broken,
+                    (None, comments) => Some(cell_comments::thread_summary(comments)),
+                    (Some(broken), comments) => Some(format!(
+                        "{broken}\n\n{}",
+                        cell_comments::thread_summary(comments)
+                    )),
+                };
+                let content: Element<'_, Message> = match summary {
+                    None => ic.into(),
+                    Some(summary) => tooltip(ic, summary, tooltip::Position::FollowCursor)
+                        .size(TEXT_SIZE_TOOLTIP)
+                        .style(theme::Container::Box)
+                        .into(),
+                };
+                let mut grid_cell = GridCell::new(range, content).borders(borders);
+                if let Some(fill_color) = style.fill_color {
+                    grid_cell = grid_cell.background(fill_color);
+                }
                 grid = grid.push_cell(grid_cell);
             };
         }
 
         if let Some((cell, editor)) = &self.active_cell {
             let rc = rc_of_cell(cell);
+            let style = CellStyle::of(&format_of(cell));
             // Always add the active cell even when not visible so keystrokes are handled
             let ac = active_cell::ActiveCell::new(editor.clone())
                 .id(ACTIVE_CELL_ID.clone())
                 .focused(self.focus == ACTIVE_CELL_ID.clone().into())
                 .edit_when_clicked(click::Kind::Double)
-                // TODO Set details from spreadsheet data
-                .horizontal_alignment(alignment::Horizontal::Center)
-                .vertical_alignment(alignment::Vertical::Center)
-                .font_size(10.0);
-            let grid_cell = GridCell::new(rc, ac)
-                // TODO Hardcoding
-                .borders(Borders::new(Border::new(1.0, Color::from_rgb8(0, 0, 255))));
+                .display_text(cell.display_value())
+                .horizontal_alignment(style.horizontal_alignment)
+                .vertical_alignment(style.vertical_alignment)
+                .font(style.font)
+                .font_size(style.font_size)
+                .text_color(style.text_color)
+                .underline(style.underline);
+            let content: Element<'_, Message> = match list_values {
+                Some(_) => row![
+                    ac,
+                    button(icon(ICON_OPEN_DOWN, style.font_size))
+                        .on_press(Message::ValidationListToggle)
+                        .padding(0)
+                        .style(theme::Button::Text),
+                ]
+                .align_items(Alignment::Center)
+                .into(),
+                None => ac.into(),
+            };
+            // The highlight replaces any borders of the cell's own
+            let mut grid_cell = GridCell::new(range_at(rc), content)
+                .borders(Borders::new(Border::new(1.0, ACTIVE_CELL_COLOR)));
+            if let Some(fill_color) = style.fill_color {
+                grid_cell = grid_cell.background(fill_color);
+            }
             grid = grid.push_cell(grid_cell);
+
+            if let Some((values, list_range)) = list_values.zip(list_range) {
+                let mut list = Column::new();
+                for value in values {
+                    list = list.push(
+                        button(text(value).size(style.font_size).line_height(1.0))
+                            .on_press(Message::ValidationListSelect(value.clone()))
+                            .padding([0.0, SPACE_S])
+                            .width(Length::Fill)
+                            .style(theme::Button::Text),
+                    );
+                }
+                grid = grid.push_cell(
+                    GridCell::new(list_range, scrollable(list))
+                        .borders(Borders::new(Border::new(1.0, ACTIVE_CELL_COLOR)))
+                        .background(Color::WHITE),
+                );
+            }
         }
 
         GridScrollable::new(grid)
@@ -390,7 +876,99 @@ impl ActiveSheetUi {
             Message::PadShowProperties => {
                 Event::EditPadPropertiesRequested(self.active_sheet.workpad())
             }
+            Message::PadCompactHistory => {
+                Event::CompactHistoryRequested(self.active_sheet.workpad())
+            }
             Message::PadClose => Event::CloseWorkpadRequested,
+            Message::PadShare => Event::ShareRequested(self.active_sheet.workpad()),
+            Message::PadJoin => Event::JoinRequested,
+            Message::FormatBold => self.apply_format(|format| Format {
+                bold: Some(!format.bold.unwrap_or_default()),
+                ..Format::NONE
+            }),
+            Message::FormatItalic => self.apply_format(|format| Format {
+                italic: Some(!format.italic.unwrap_or_default()),
+                ..Format::NONE
+            }),
+            Message::FormatUnderline => self.apply_format(|format| Format {
+                underline: Some(!format.underline.unwrap_or_default()),
+                ..Format::NONE
+            }),
+            Message::FormatCellsShow => match &self.active_cell {
+                Some((cell, _)) => Event::FormatCellsRequested(cell.clone()),
+                None => Event::None,
+            },
+            Message::StylesShow => match &self.active_cell {
+                Some((cell, _)) => Event::StylesRequested(cell.clone()),
+                None => Event::None,
+            },
+            Message::ConditionalFormatsShow => match &self.active_cell {
+                Some((cell, _)) => Event::ConditionalFormatsRequested(cell.clone()),
+                None => Event::None,
+            },
+            Message::MergeCellsShow => match &self.active_cell {
+                Some((cell, _)) => Event::MergeCellsRequested(cell.clone()),
+                None => Event::None,
+            },
+            Message::ValidationsShow => match &self.active_cell {
+                Some((cell, _)) => Event::ValidationsRequested(cell.clone()),
+                None => Event::None,
+            },
+            Message::ValidationListToggle => {
+                let rc = self.active_cell.as_ref().map(|(cell, _)| rc_of_cell(cell));
+                self.validation_list = match self.validation_list {
+                    Some(_) => None,
+                    None => rc,
+                };
+                Event::None
+            }
+            Message::ValidationListSelect(ref value) => {
+                debug!(target: "flexpad", %message);
+                let Some((cell, _)) = &self.active_cell else {
+                    return Event::None;
+                };
+                let rc = rc_of_cell(cell);
+                self.validation_list = None;
+                self.update_value_and_move(Some(value.clone()), Move::To(rc))
+            }
+            Message::HyperlinkShow => match &self.active_cell {
+                Some((cell, _)) => Event::HyperlinkRequested(cell.clone()),
+                None => Event::None,
+            },
+            Message::HyperlinkActivate(rc) => {
+                debug!(target: "flexpad", %message);
+                let cell = cell_by_rc(&self.active_sheet, rc);
+                match cell.hyperlink() {
+                    Some(Hyperlink::Reference(target)) => {
+                        match hyperlink::link_target(&self.active_sheet.workpad(), target) {
+                            Ok(cell) => self.goto_cell(&cell),
+                            Err(err) => Event::HyperlinkFailed(err.to_string()),
+                        }
+                    }
+                    Some(link @ Hyperlink::External(target)) => {
+                        match hyperlink::open_external(link) {
+                            Ok(()) => Event::None,
+                            Err(err) => Event::HyperlinkFailed(
+                                t!("Hyperlink.OpenError")
+                                    .replace("{target}", target)
+                                    .replace("{error}", &err.to_string()),
+                            ),
+                        }
+                    }
+                    None => Event::None,
+                }
+            }
+            Message::UnmergeCells => match &self.active_cell {
+                Some((cell, _)) => Event::UpdateRequested(
+                    self.active_sheet.workpad().master(),
+                    WorkpadUpdate::SheetUnmergeCells {
+                        sheet_id: cell.sheet().id(),
+                        row_id: cell.row().id(),
+                        column_id: cell.column().id(),
+                    },
+                ),
+                None => Event::None,
+            },
             Message::SetActiveSheet(sheet_id) => Event::UpdateRequested(
                 self.active_sheet.workpad().master(),
                 WorkpadUpdate::SetActiveSheet { sheet_id },
@@ -399,9 +977,312 @@ impl ActiveSheetUi {
                 self.active_sheet.workpad().master(),
                 WorkpadUpdate::SetVersion { version },
             ),
+            Message::GotoShow => Event::GotoRequested(self.active_sheet.workpad()),
+            Message::GotoReference(ref reference) => {
+                debug!(target: "flexpad", %message);
+                self.goto_cell(&reference.first_cell())
+            }
+            Message::HistoryShow => {
+                debug!(target: "flexpad", %message);
+                self.history = match self.history {
+                    Some(_) => None,
+                    None => Some(HistoryUi::new()),
+                };
+                Event::None
+            }
+            Message::History(msg) => {
+                let Some(history) = &mut self.history else {
+                    return Event::None;
+                };
+                match history.update(msg) {
+                    history::Event::None => Event::None,
+                    history::Event::Closed => {
+                        self.history = None;
+                        Event::None
+                    }
+                    history::Event::VersionSelected(version) => Event::UpdateRequested(
+                        self.active_sheet.workpad().master(),
+                        WorkpadUpdate::SetVersion { version },
+                    ),
+                }
+            }
+            Message::CheckpointShow => Event::CheckpointRequested(self.active_sheet.workpad()),
+            Message::CellHistoryShow => {
+                debug!(target: "flexpad", %message);
+                self.cell_history = match self.cell_history {
+                    Some(_) => None,
+                    None => Some(CellHistoryUi::new(
+                        self.active_cell.as_ref().map(|(cell, _)| cell),
+                    )),
+                };
+                Event::None
+            }
+            Message::CellHistory(msg) => {
+                let Some(cell_history) = &mut self.cell_history else {
+                    return Event::None;
+                };
+                match cell_history.update(msg) {
+                    cell_history::Event::None => Event::None,
+                    cell_history::Event::Closed => {
+                        self.cell_history = None;
+                        Event::None
+                    }
+                    cell_history::Event::VersionSelected(version) => Event::UpdateRequested(
+                        self.active_sheet.workpad().master(),
+                        WorkpadUpdate::SetVersion { version },
+                    ),
+                }
+            }
+            Message::CellCommentsShow => {
+                debug!(target: "flexpad", %message);
+                match self.cell_comments {
+                    Some(_) => {
+                        self.cell_comments = None;
+                        self.focus = ACTIVE_CELL_ID.clone().into();
+                        Event::None
+                    }
+                    None => {
+                        let cell_comments =
+                            CellCommentsUi::new(self.active_cell.as_ref().map(|(cell, _)| cell));
+                        let input_id = cell_comments.input_id();
+                        self.cell_comments = Some(cell_comments);
+                        self.focus = input_id.clone().into();
+                        Event::Command(text_input::focus(input_id))
+                    }
+                }
+            }
+            Message::CellComments(msg) => {
+                let Some(cell_comments) = &mut self.cell_comments else {
+                    return Event::None;
+                };
+                let event = cell_comments.update(msg);
+                self.focus = cell_comments.input_id().into();
+                match event {
+                    cell_comments::Event::None => Event::None,
+                    cell_comments::Event::Closed => {
+                        self.cell_comments = None;
+                        self.focus = ACTIVE_CELL_ID.clone().into();
+                        Event::None
+                    }
+                    cell_comments::Event::Focus(id) => {
+                        self.focus = id.clone().into();
+                        Event::Command(text_input::focus(id))
+                    }
+                    cell_comments::Event::UpdateRequested(update) => {
+                        Event::UpdateRequested(self.active_sheet.workpad().master(), update)
+                    }
+                }
+            }
+            Message::CommentsShow => {
+                debug!(target: "flexpad", %message);
+                self.comments = match self.comments {
+                    Some(_) => None,
+                    None => Some(CommentsUi::new()),
+                };
+                Event::None
+            }
+            Message::Comments(msg) => {
+                let Some(comments) = &mut self.comments else {
+                    return Event::None;
+                };
+                match comments.update(msg) {
+                    comments::Event::None => Event::None,
+                    comments::Event::Closed => {
+                        self.comments = None;
+                        Event::None
+                    }
+                    comments::Event::CellSelected(cell) => self.goto_cell(&cell),
+                }
+            }
+            Message::CompareShow => {
+                debug!(target: "flexpad", %message);
+                self.compare = match self.compare {
+                    Some(_) => None,
+                    None => Some(CompareUi::new(&self.active_sheet.workpad())),
+                };
+                Event::None
+            }
+            Message::Compare(msg) => {
+                let Some(compare) = &mut self.compare else {
+                    return Event::None;
+                };
+                match compare.update(msg, &self.active_sheet.workpad()) {
+                    compare::Event::None => Event::None,
+                    compare::Event::Closed => {
+                        self.compare = None;
+                        Event::None
+                    }
+                }
+            }
+            Message::FindShow => {
+                debug!(target: "flexpad", %message);
+                if self.find.is_none() {
+                    self.find = Some(FindUi::new());
+                }
+                self.focus = FIND_INPUT_ID.clone().into();
+                Event::Command(text_input::focus(FIND_INPUT_ID.clone()))
+            }
+            Message::Find(msg) => {
+                let Some(find) = &mut self.find else {
+                    return Event::None;
+                };
+                self.focus = FIND_INPUT_ID.clone().into();
+                match find.update(msg) {
+                    find::Event::None => Event::None,
+                    find::Event::Closed => {
+                        self.find = None;
+                        self.focus = ACTIVE_CELL_ID.clone().into();
+                        Event::None
+                    }
+                    find::Event::FindNext(finder, scope, direction) => {
+                        self.find_next(&finder, scope, direction, None)
+                    }
+                    find::Event::Replace(finder, scope, replacement) => {
+                        self.replace(&finder, scope, &replacement)
+                    }
+                    find::Event::ReplaceAll(finder, scope, replacement) => {
+                        let pad = self.active_sheet.workpad();
+                        match pad.replace_all(&finder, &replacement, scope) {
+                            Some(update) => Event::UpdateRequested(pad.master(), update),
+                            None => self.not_found(),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Makes a cell, which may be on another sheet, the active cell and scrolls to it
+    fn goto_cell(&mut self, cell: &Cell) -> Event {
+        match &self.active_cell {
+            Some((active_cell, _)) if is_same_cell(active_cell, cell) => {
+                Event::Command(scroll_to(rc_of_cell(cell)))
+            }
+            _ => {
+                let mut updates = vec![];
+                if cell.sheet() != self.active_sheet {
+                    updates.push(WorkpadUpdate::SetActiveSheet {
+                        sheet_id: cell.sheet().id(),
+                    });
+                }
+                updates.push(WorkpadUpdate::SheetSetActiveCell {
+                    sheet_id: cell.sheet().id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                });
+                self.scroll_to_active_cell = true;
+                Event::UpdateRequested(
+                    self.active_sheet.workpad().master(),
+                    WorkpadUpdate::Multi(updates),
+                )
+            }
+        }
+    }
+
+    /// Returns true if the cell has changed since the version being compared with (if any)
+    fn is_changed(&self, rc: RowCol) -> bool {
+        self.compare.as_ref().map_or(false, |compare| {
+            compare.is_changed(self.active_sheet.id(), rc.row, rc.column)
+        })
+    }
+
+    /// Returns the colour showing the collaborator whose active cell is at `rc`, if any
+    fn collaborator_color(&self, rc: RowCol) -> Option<Color> {
+        let collaborator = self.collaborators.iter().flatten().find(|collaborator| {
+            collaborator.sheet_id() == self.active_sheet.id()
+                && RowCol::new(collaborator.row(), collaborator.column()) == rc
+        })?;
+        let index = collaborator.id() as usize % COLLABORATOR_COLORS.len();
+        Some(COLLABORATOR_COLORS[index])
+    }
+
+    /// Records the collaborators sharing the workpad, `None` if it is not shared
+    pub fn collaborators_updated(&mut self, collaborators: Option<Vec<Collaborator>>) {
+        self.collaborators = collaborators;
+    }
+
+    /// Moves the active cell to the next match, preceding that move with `prior`
+    /// (if any) as part of the same update.
+    fn find_next(
+        &mut self,
+        finder: &Finder,
+        scope: FindScope,
+        direction: FindDirection,
+        prior: Option<WorkpadUpdate>,
+    ) -> Event {
+        let Some((from, _)) = &self.active_cell else {
+            return Event::None;
+        };
+        let pad = self.active_sheet.workpad();
+
+        let mut updates: Vec<WorkpadUpdate> = prior.into_iter().collect();
+        match pad.find_next(finder, scope, from, direction) {
+            Some(cell) if is_same_cell(&cell, from) => (),
+            Some(cell) => {
+                if cell.sheet() != self.active_sheet {
+                    updates.push(WorkpadUpdate::SetActiveSheet {
+                        sheet_id: cell.sheet().id(),
+                    });
+                }
+                updates.push(WorkpadUpdate::SheetSetActiveCell {
+                    sheet_id: cell.sheet().id(),
+                    row_id: cell.row().id(),
+                    column_id: cell.column().id(),
+                });
+            }
+            None if updates.is_empty() => return self.not_found(),
+            None => (),
+        }
+
+        match updates.len() {
+            0 => Event::None,
+            1 => Event::UpdateRequested(pad.master(), updates.pop().unwrap()),
+            _ => Event::UpdateRequested(pad.master(), WorkpadUpdate::Multi(updates)),
         }
     }
 
+    /// Replaces the match in the active cell (when it matches) and moves on to the next
+    fn replace(&mut self, finder: &Finder, scope: FindScope, replacement: &str) -> Event {
+        let Some((cell, _)) = &self.active_cell else {
+            return Event::None;
+        };
+
+        let update = finder.replace(cell.value(), replacement).map(|value| {
+            WorkpadUpdate::SheetSetCellValue {
+                sheet_id: cell.sheet().id(),
+                row_id: cell.row().id(),
+                column_id: cell.column().id(),
+                value,
+            }
+        });
+        self.find_next(finder, scope, FindDirection::Forward, update)
+    }
+
+    /// Changes the properties of the format of the active cell returned by `change`, which is
+    /// given the current format of the cell
+    fn apply_format(&self, change: impl FnOnce(&Format) -> Format) -> Event {
+        let Some((cell, _)) = &self.active_cell else {
+            return Event::None;
+        };
+
+        Event::UpdateRequested(
+            self.active_sheet.workpad().master(),
+            WorkpadUpdate::SheetApplyCellFormat {
+                sheet_id: cell.sheet().id(),
+                row_id: cell.row().id(),
+                column_id: cell.column().id(),
+                format: change(&cell.resolved_format()),
+            },
+        )
+    }
+
+    fn not_found(&mut self) -> Event {
+        if let Some(find) = &mut self.find {
+            find.set_status(Some(t!("Find.NotFound")));
+        }
+        Event::None
+    }
+
     fn update_value_and_move(&self, new_value: Option<String>, mve: Move) -> Event {
         let Some((cell, _)) = &self.active_cell else {
             unreachable!();
@@ -414,7 +1295,8 @@ impl ActiveSheetUi {
             value: new_value,
         });
 
-        let update_active_cell = apply_move(cell, mve).map(|(_, update)| update);
+        let update_active_cell =
+            apply_move(cell, mve, &self.visible_cells).map(|(_, update)| update);
 
         let master = self.active_sheet.workpad().master();
         match (update_cell_value, update_active_cell) {
@@ -429,7 +1311,23 @@ impl ActiveSheetUi {
     }
 
     pub fn pad_updated(&mut self, pad: Workpad) -> Command<Message> {
+        if let Some(compare) = &mut self.compare {
+            compare.pad_updated(&pad);
+        }
+        let active_cell = pad.active_sheet().and_then(|sheet| sheet.active_cell());
+        if self.validation_list != active_cell.as_ref().map(rc_of_cell)
+            || self.active_sheet.id() != pad.active_sheet().unwrap().id()
+        {
+            self.validation_list = None;
+        }
+        if let Some(cell_history) = &mut self.cell_history {
+            cell_history.cell_updated(active_cell.as_ref());
+        }
+        if let Some(cell_comments) = &mut self.cell_comments {
+            cell_comments.cell_updated(active_cell.as_ref());
+        }
         let new_active_sheet = pad.active_sheet().unwrap();
+        let scroll_to_active_cell = std::mem::take(&mut self.scroll_to_active_cell);
 
         if self.active_sheet.id() != new_active_sheet.id() {
             // View has switched to a new sheet
@@ -442,6 +1340,7 @@ impl ActiveSheetUi {
                     ))
                     .copied()
             });
+            self.row_heights = wrapped_row_heights(&new_active_sheet);
             self.active_sheet = new_active_sheet;
             self.visible_cells = match viewport {
                 Some(viewport) => viewport.cell_range(),
@@ -452,6 +1351,12 @@ impl ActiveSheetUi {
                 (cell, active_cell_editor)
             });
 
+            if scroll_to_active_cell {
+                if let Some((cell, _)) = &self.active_cell {
+                    return scroll_to(rc_of_cell(cell));
+                }
+            }
+
             let scroll_to = self.visible_cells.cells().next().map(scroll_to);
             let ensure_visible = self
                 .active_cell
@@ -468,6 +1373,11 @@ impl ActiveSheetUi {
             }
         } else {
             // View has switched to a new version of the same sheet
+            if new_active_sheet.workpad().version().version()
+                != self.active_sheet.workpad().version().version()
+            {
+                self.row_heights = wrapped_row_heights(&new_active_sheet);
+            }
             self.active_sheet = new_active_sheet;
 
             let prior_rc = self.active_cell.as_ref().map(|(cell, _)| rc_of_cell(cell));
@@ -478,6 +1388,7 @@ impl ActiveSheetUi {
             let new_rc = self.active_cell.as_ref().map(|(cell, _)| rc_of_cell(cell));
 
             match (prior_rc, new_rc) {
+                (_, Some(new)) if scroll_to_active_cell => scroll_to(new),
                 (Some(prior), Some(new)) if prior != new => ensure_cell_visible(new),
                 _ => Command::none(),
             }
@@ -485,11 +1396,26 @@ impl ActiveSheetUi {
     }
 
     pub fn subscription(&self) -> iced::Subscription<Message> {
-        Subscription::none()
+        // F5 is an alternative shortcut for Go To that the menus have no way to express
+        subscription::events_with(|event, status| match (event, status) {
+            (
+                iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                    key_code: keyboard::KeyCode::F5,
+                    modifiers,
+                }),
+                event::Status::Ignored,
+            ) if modifiers.is_empty() => Some(Message::GotoShow),
+            _ => None,
+        })
     }
 
     pub fn menu_paths(&self) -> menu::PathVec<Message> {
         let (undo_to, redo_to) = surrounding_versions(&self.active_sheet.workpad());
+        let active_cell_msg = |msg| self.active_cell.is_some().then_some(msg);
+        let is_merged = self.active_cell.as_ref().map_or(false, |(cell, _)| {
+            let rc = rc_of_cell(cell);
+            self.active_sheet.merged_range(rc.row, rc.column).is_some()
+        });
 
         let mut paths = menu::PathVec::new()
             .with(workpad_menu::new_blank_workpad(None))
@@ -497,17 +1423,71 @@ impl ActiveSheetUi {
             .with(workpad_menu::show_properties(Some(
                 Message::PadShowProperties,
             )))
+            // Versions are renumbered so a shared workpad is not compacted
+            .with(workpad_menu::compact_history(
+                self.collaborators
+                    .is_none()
+                    .then_some(Message::PadCompactHistory),
+            ))
             // TODO No actual delete (since no actual save) at present
             .with(workpad_menu::delete_pad(Some(Message::PadClose)))
             .with(workpad_menu::close_pad(Some(Message::PadClose)))
+            .with(workpad_menu::share_pad(
+                self.collaborators.is_none().then_some(Message::PadShare),
+            ))
+            .with(workpad_menu::join_pad(Some(Message::PadJoin)))
             .with(edit_menu::undo(undo_to.map(Message::GotoVersion)))
             .with(edit_menu::redo(redo_to.map(Message::GotoVersion)))
+            .with(edit_menu::history(Some(Message::HistoryShow)))
+            .with(edit_menu::cell_history(Some(Message::CellHistoryShow)))
+            .with(edit_menu::cell_comments(active_cell_msg(
+                Message::CellCommentsShow,
+            )))
+            .with(edit_menu::comments(Some(Message::CommentsShow)))
+            .with(edit_menu::hyperlink(active_cell_msg(
+                Message::HyperlinkShow,
+            )))
+            .with(edit_menu::checkpoint(Some(Message::CheckpointShow)))
+            .with(edit_menu::compare(Some(Message::CompareShow)))
+            .with(edit_menu::find(Some(Message::FindShow)))
+            .with(edit_menu::goto(Some(Message::GotoShow)))
+            .with(format_menu::bold(active_cell_msg(Message::FormatBold)))
+            .with(format_menu::italic(active_cell_msg(Message::FormatItalic)))
+            .with(format_menu::underline(active_cell_msg(
+                Message::FormatUnderline,
+            )))
+            .with(format_menu::cells(active_cell_msg(
+                Message::FormatCellsShow,
+            )))
+            .with(format_menu::styles(active_cell_msg(Message::StylesShow)))
+            .with(format_menu::conditional_formats(active_cell_msg(
+                Message::ConditionalFormatsShow,
+            )))
+            .with(format_menu::validations(active_cell_msg(
+                Message::ValidationsShow,
+            )))
+            .with(format_menu::merge_cells(active_cell_msg(
+                Message::MergeCellsShow,
+            )))
+            .with(format_menu::unmerge_cells(
+                is_merged.then_some(Message::UnmergeCells),
+            ))
             .with(sheets_menu::show_properties(Some(
                 Message::SheetShowProperties,
             )))
             .with(sheets_menu::new_sheet(Some(Message::SheetAdd)))
             .with(sheets_menu::delete_sheet(Some(Message::SheetDelete)));
 
+        let pad = self.active_sheet.workpad();
+        let active_version = pad.version().version();
+        for (version, checkpoint) in pad.checkpoints() {
+            let on_select = (version != active_version).then_some(Message::GotoVersion(version));
+            paths = paths.with(edit_menu::goto_checkpoint(
+                checkpoint.label().to_owned(),
+                on_select,
+            ));
+        }
+
         for sheet in self.active_sheet.workpad().sheets() {
             let on_select = if sheet == self.active_sheet {
                 None
@@ -524,10 +1504,137 @@ impl ActiveSheetUi {
     }
 }
 
+/// How a cell is drawn, from its [`Format`] with defaults for the properties it does not set
+struct CellStyle {
+    font: Font,
+    font_size: f32,
+    text_color: Color,
+    underline: bool,
+    fill_color: Option<Color>,
+    horizontal_alignment: alignment::Horizontal,
+    vertical_alignment: alignment::Vertical,
+    borders: Borders,
+    text_overflow: TextOverflow,
+}
+
+impl CellStyle {
+    fn of(format: &Format) -> Self {
+        let font = Font {
+            family: format
+                .font_family
+                .as_deref()
+                .map_or(Font::DEFAULT.family, font_family),
+            weight: if format.bold.unwrap_or_default() {
+                font::Weight::Bold
+            } else {
+                font::Weight::Normal
+            },
+            style: if format.italic.unwrap_or_default() {
+                font::Style::Italic
+            } else {
+                font::Style::Normal
+            },
+            ..Font::DEFAULT
+        };
+
+        let border = |border: Option<flexpad_model::Border>| {
+            border.map_or(Border::NONE, |border| {
+                Border::new(border.style.width(), color_of(border.color))
+            })
+        };
+
+        Self {
+            font,
+            font_size: format.font_size.unwrap_or(DEFAULT_FONT_SIZE),
+            text_color: format.text_color.map_or(Color::BLACK, color_of),
+            underline: format.underline.unwrap_or_default(),
+            fill_color: format.fill_color.map(color_of),
+            horizontal_alignment: match format.horizontal_alignment {
+                Some(HorizontalAlignment::Left) => alignment::Horizontal::Left,
+                Some(HorizontalAlignment::Center) | None => alignment::Horizontal::Center,
+                Some(HorizontalAlignment::Right) => alignment::Horizontal::Right,
+            },
+            vertical_alignment: match format.vertical_alignment {
+                Some(VerticalAlignment::Top) => alignment::Vertical::Top,
+                Some(VerticalAlignment::Center) | None => alignment::Vertical::Center,
+                Some(VerticalAlignment::Bottom) => alignment::Vertical::Bottom,
+            },
+            borders: Borders {
+                top: border(format.borders.top),
+                right: border(format.borders.right),
+                bottom: border(format.borders.bottom),
+                left: border(format.borders.left),
+            },
+            text_overflow: format.text_overflow.unwrap_or(TextOverflow::Clip),
+        }
+    }
+}
+
+/// Returns the heights of the rows of a sheet that must grow to fit the text of their cells
+/// that wrap, by row index.  Other rows have the default height of the sheet.
+fn wrapped_row_heights(sheet: &Sheet) -> BTreeMap<usize, f32> {
+    // Conditional formats are overlaid on the formatting of the cells
+    let conditional = sheet.conditional_evaluator();
+
+    // Merged cells are drawn once across their range as their top-left cell
+    let merged: Vec<CellRange> = sheet.merged_ranges().map(range_of).collect();
+    let is_merged = |rc: RowCol| merged.iter().any(|range| range.contains(&rc));
+
+    let column_width = sheet.default_column_width();
+    let default_row_height = sheet.default_row_height();
+    let mut row_heights = BTreeMap::new();
+    for cell in sheet.non_empty_cells() {
+        let rc = rc_of_cell(&cell);
+        let format = cell.resolved_format();
+        let format = match conditional.format_of(&cell) {
+            Some(conditional) => format.overlay(&conditional),
+            None => format,
+        };
+        let style = CellStyle::of(&format);
+        if style.text_overflow == TextOverflow::Wrap && !is_merged(rc) {
+            let height = wrapped_height(&cell.display_value(), style.font_size, column_width);
+            if height > default_row_height {
+                let entry = row_heights.entry(rc.row).or_insert(height);
+                *entry = entry.max(height);
+            }
+        }
+    }
+    row_heights
+}
+
+/// Returns an estimate of the height of a row needed to show the text of a cell of the given
+/// width when it wraps
+fn wrapped_height(text: &str, font_size: f32, column_width: f32) -> f32 {
+    // Characters average a little over half of the font size in width
+    let chars_per_line = ((column_width - 4.0) / (font_size * 0.6)).max(1.0) as usize;
+    let lines: usize = text
+        .lines()
+        .map(|line| ((line.chars().count() + chars_per_line - 1) / chars_per_line).max(1))
+        .sum();
+    lines as f32 * text::LineHeight::default().to_absolute(font_size.into()).0 + 4.0
+}
+
+/// Returns the font family with a name, using the generic families for their names
+fn font_family(name: &str) -> font::Family {
+    match name {
+        "Serif" => font::Family::Serif,
+        "Sans Serif" => font::Family::SansSerif,
+        "Monospace" => font::Family::Monospace,
+        "Cursive" => font::Family::Cursive,
+        "Fantasy" => font::Family::Fantasy,
+        // Interned so that the name lives as long as fonts need it to
+        name => font::Family::Name(Intern::<str>::from(name).as_ref()),
+    }
+}
+
+fn color_of(color: flexpad_model::Color) -> Color {
+    Color::from_rgb8(color.r, color.g, color.b)
+}
+
 fn surrounding_versions(pad: &Workpad) -> (Option<Version>, Option<Version>) {
     (
-        pad.backward_versions().next().map(|version| version.0),
-        pad.forward_versions().next().map(|version| version.0),
+        pad.backward_versions().next().map(|info| info.version()),
+        pad.forward_versions().next().map(|info| info.version()),
     )
 }
 
@@ -541,10 +1648,29 @@ pub fn scroll_to(cell: RowCol) -> Command<Message> {
         .map(Message::ViewportChanged)
 }
 
-fn apply_move(active_cell: &Cell, mve: Move) -> Option<(RowCol, WorkpadUpdate)> {
+fn apply_move(active_cell: &Cell, mve: Move, page: &CellRange) -> Option<(RowCol, WorkpadUpdate)> {
     let sheet = active_cell.sheet();
     let prior_rc = rc_of_cell(active_cell);
-    let new_rc = mve.apply(prior_rc, sheet.rows().count(), sheet.columns().count());
+
+    // Merged cells behave as one: moves start from the edge of the merged cell in the
+    // direction moved and moves into a merged cell go to its top-left cell
+    let from = match sheet.merged_range(prior_rc.row, prior_rc.column) {
+        Some(merged) => match mve {
+            Move::Right | Move::JumpRight | Move::PageRight => {
+                RowCol::new(prior_rc.row, *merged.columns().end())
+            }
+            Move::Down | Move::JumpDown | Move::PageDown => {
+                RowCol::new(*merged.rows().end(), prior_rc.column)
+            }
+            _ => prior_rc,
+        },
+        None => prior_rc,
+    };
+    let new_rc = mve.apply(from, &sheet, page);
+    let new_rc = match sheet.merged_range(new_rc.row, new_rc.column) {
+        Some(merged) => rc_of_cell(&merged.first_cell()),
+        None => new_rc,
+    };
 
     if prior_rc != new_rc {
         let new_cell = cell_by_rc(&sheet, new_rc);
@@ -565,10 +1691,129 @@ fn rc_of_cell(cell: &Cell) -> RowCol {
     RowCol::new(cell.row().index(), cell.column().index())
 }
 
+fn range_of(reference: CellReference) -> CellRange {
+    CellRange::new(
+        rc_of_cell(&reference.first_cell()),
+        rc_of_cell(&reference.last_cell()),
+    )
+}
+
+fn is_same_cell(a: &Cell, b: &Cell) -> bool {
+    a.sheet() == b.sheet() && rc_of_cell(a) == rc_of_cell(b)
+}
+
 fn cell_by_rc(sheet: &Sheet, rc: RowCol) -> Cell {
     sheet.cell(rc.row, rc.column)
 }
 
+mod format_menu {
+    use crate::FlexpadAction;
+    use flexpad_toolkit::{menu, prelude::*};
+    use rust_i18n::t;
+
+    fn root<Message>() -> menu::PathToMenu<Message>
+    where
+        Message: Clone,
+    {
+        menu::root(t!("Menu.Format"))
+    }
+
+    pub fn bold<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(root().section("font"), FlexpadAction::FormatBold, on_select)
+    }
+
+    pub fn italic<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("font"),
+            FlexpadAction::FormatItalic,
+            on_select,
+        )
+    }
+
+    pub fn underline<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("font"),
+            FlexpadAction::FormatUnderline,
+            on_select,
+        )
+    }
+
+    pub fn cells<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("cells"),
+            FlexpadAction::FormatCells,
+            on_select,
+        )
+    }
+
+    pub fn styles<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("cells"),
+            FlexpadAction::FormatStyles,
+            on_select,
+        )
+    }
+
+    pub fn conditional_formats<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("cells"),
+            FlexpadAction::FormatConditional,
+            on_select,
+        )
+    }
+
+    pub fn validations<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("cells"),
+            FlexpadAction::FormatValidation,
+            on_select,
+        )
+    }
+
+    pub fn merge_cells<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("merge"),
+            FlexpadAction::MergeCells,
+            on_select,
+        )
+    }
+
+    pub fn unmerge_cells<Message>(on_select: Option<Message>) -> menu::Path<Message>
+    where
+        Message: Clone,
+    {
+        menu::Path::new(
+            root().section("merge"),
+            FlexpadAction::UnmergeCells,
+            on_select,
+        )
+    }
+}
+
 mod sheets_menu {
     use crate::FlexpadAction;
     use flexpad_toolkit::{menu, prelude::*};
@@ -616,3 +1861,82 @@ mod sheets_menu {
         menu::Path::new(activate_sheets(), Action::new(name), on_select)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A column of blocks of filled cells: rows 2 to 4, row 8 and rows 12 to 15
+    fn is_filled(rc: RowCol) -> bool {
+        rc.column == 0 && matches!(rc.row, 2..=4 | 8 | 12..=15)
+    }
+
+    const MAX: RowCol = RowCol::new(99, 9);
+    const USED: Option<(RowCol, RowCol)> = Some((RowCol::new(2, 0), RowCol::new(15, 0)));
+
+    fn jump_from(row: usize, column: usize, step: (isize, isize)) -> RowCol {
+        jump(RowCol::new(row, column), step, MAX, USED, is_filled)
+    }
+
+    #[test]
+    fn jumps_to_block_edges() {
+        assert_eq!(RowCol::new(4, 0), jump_from(2, 0, (1, 0)));
+        assert_eq!(RowCol::new(12, 0), jump_from(15, 0, (-1, 0)));
+        assert_eq!(RowCol::new(2, 0), jump_from(4, 0, (-1, 0)));
+    }
+
+    #[test]
+    fn jumps_across_gaps_to_next_block() {
+        assert_eq!(RowCol::new(8, 0), jump_from(4, 0, (1, 0)));
+        assert_eq!(RowCol::new(12, 0), jump_from(8, 0, (1, 0)));
+        assert_eq!(RowCol::new(2, 0), jump_from(0, 0, (1, 0)));
+        assert_eq!(RowCol::new(4, 0), jump_from(8, 0, (-1, 0)));
+    }
+
+    #[test]
+    fn jumps_past_used_range_to_sheet_edge() {
+        assert_eq!(RowCol::new(99, 0), jump_from(15, 0, (1, 0)));
+        assert_eq!(RowCol::new(0, 0), jump_from(2, 0, (-1, 0)));
+        assert_eq!(RowCol::new(3, 9), jump_from(3, 0, (0, 1)));
+        assert_eq!(RowCol::new(20, 0), jump_from(20, 5, (0, -1)));
+        assert_eq!(
+            RowCol::new(99, 3),
+            jump(RowCol::new(5, 3), (1, 0), MAX, None, |_| false)
+        );
+    }
+
+    #[test]
+    fn jumps_stay_put_at_sheet_edge() {
+        assert_eq!(RowCol::new(99, 0), jump_from(99, 0, (1, 0)));
+        assert_eq!(RowCol::new(0, 0), jump_from(0, 0, (-1, 0)));
+        assert_eq!(RowCol::new(5, 0), jump_from(5, 0, (0, -1)));
+        assert_eq!(RowCol::new(5, 9), jump_from(5, 9, (0, 1)));
+    }
+
+    #[test]
+    fn pages_clamped_at_sheet_edges() {
+        let master = WorkpadMaster::new_starter();
+        let sheet = master.active_version().active_sheet().unwrap();
+        let last_row = sheet.row_count() - 1;
+        let last_column = sheet.column_count() - 1;
+        // A page of 20 rows and 8 columns
+        let page = CellRange::new(RowCol::new(10, 5), RowCol::new(29, 12));
+        let apply = |m: Move, row, column| m.apply(RowCol::new(row, column), &sheet, &page);
+
+        assert_eq!(RowCol::new(25, 3), apply(Move::PageDown, 5, 3));
+        assert_eq!(RowCol::new(5, 3), apply(Move::PageUp, 25, 3));
+        assert_eq!(RowCol::new(5, 11), apply(Move::PageRight, 5, 3));
+        assert_eq!(RowCol::new(5, 3), apply(Move::PageLeft, 5, 11));
+
+        assert_eq!(RowCol::new(0, 3), apply(Move::PageUp, 5, 3));
+        assert_eq!(RowCol::new(5, 0), apply(Move::PageLeft, 5, 3));
+        assert_eq!(
+            RowCol::new(last_row, 3),
+            apply(Move::PageDown, last_row - 5, 3)
+        );
+        assert_eq!(
+            RowCol::new(5, last_column),
+            apply(Move::PageRight, 5, last_column - 3)
+        );
+    }
+}
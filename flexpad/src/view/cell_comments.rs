@@ -0,0 +1,294 @@
+use crate::{view::history::time_of, FLEXPAD_GRID_COLOR};
+use flexpad_model::{Cell, Comment, WorkpadUpdate};
+use flexpad_toolkit::prelude::*;
+use iced::{
+    theme,
+    widget::{
+        button, column, horizontal_rule, horizontal_space, row, scrollable, text, text_input,
+        Column,
+    },
+    Alignment, Element, Length,
+};
+use once_cell::sync::Lazy;
+use rust_i18n::t;
+
+pub static NEW_COMMENT_INPUT_ID: Lazy<text_input::Id> = Lazy::new(text_input::Id::unique);
+pub static EDIT_COMMENT_INPUT_ID: Lazy<text_input::Id> = Lazy::new(text_input::Id::unique);
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    NewText(String),
+    Add,
+    Edit(usize),
+    EditText(String),
+    SaveEdit,
+    CancelEdit,
+    Delete(usize),
+    Close,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cell_comments::Message::")?;
+        match self {
+            Self::NewText(text) => write!(f, "NewText({text})"),
+            Self::Add => write!(f, "Add"),
+            Self::Edit(index) => write!(f, "Edit({index})"),
+            Self::EditText(text) => write!(f, "EditText({text})"),
+            Self::SaveEdit => write!(f, "SaveEdit"),
+            Self::CancelEdit => write!(f, "CancelEdit"),
+            Self::Delete(index) => write!(f, "Delete({index})"),
+            Self::Close => write!(f, "Close"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Closed,
+    Focus(text_input::Id),
+    UpdateRequested(WorkpadUpdate),
+}
+
+/// The Comments panel shown beside the grid of the active sheet.  It shows the thread of
+/// comments on the active cell, allowing comments to be added, replied to, edited and deleted.
+#[derive(Debug, Default)]
+pub struct CellCommentsUi {
+    cell: Option<Cell>,
+    new_text: String,
+    // The index of the comment being edited and its text so far
+    editing: Option<(usize, String)>,
+}
+
+impl CellCommentsUi {
+    pub fn new(cell: Option<&Cell>) -> Self {
+        let mut cell_comments = Self::default();
+        cell_comments.cell_updated(cell);
+        cell_comments
+    }
+
+    /// Reloads the comments when the active cell, or the version it is part of, changes.
+    /// Text being entered is kept unless the active cell has moved.
+    pub fn cell_updated(&mut self, cell: Option<&Cell>) {
+        let moved = match (&self.cell, cell) {
+            (Some(prior), Some(cell)) => {
+                prior.sheet().id() != cell.sheet().id() || prior.name() != cell.name()
+            }
+            (None, None) => false,
+            _ => true,
+        };
+        if moved {
+            self.new_text.clear();
+            self.editing = None;
+        }
+        self.cell = cell.cloned();
+    }
+
+    /// Returns the id of the input that text typed into the panel goes to
+    pub fn input_id(&self) -> text_input::Id {
+        match self.editing {
+            Some(_) => EDIT_COMMENT_INPUT_ID.clone(),
+            None => NEW_COMMENT_INPUT_ID.clone(),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let small_button = |label: String, msg: Option<Message>| {
+            button(text(label).size(TEXT_SIZE_LABEL))
+                .on_press_maybe(msg)
+                .padding(SPACE_S / 2.0)
+                .style(theme::Button::Text)
+        };
+
+        let comments = self.cell.as_ref().map_or(&[][..], Cell::comments);
+        let mut thread = Column::new().spacing(SPACE_S);
+        if self.cell.is_none() {
+            thread = thread.push(text(t!("CellComments.NoCell")).size(TEXT_SIZE_LABEL));
+        } else if comments.is_empty() {
+            thread = thread.push(text(t!("CellComments.Empty")).size(TEXT_SIZE_LABEL));
+        }
+
+        for (index, comment) in comments.iter().enumerate() {
+            let commented = text(commented_by(comment))
+                .size(TEXT_SIZE_LABEL)
+                .style(theme::Text::Color(FLEXPAD_GRID_COLOR));
+            let entry: Element<'_, Message> = match &self.editing {
+                Some((editing, edit_text)) if *editing == index => column![
+                    commented,
+                    text_input(&t!("CellComments.EditPlaceholder"), edit_text)
+                        .id(EDIT_COMMENT_INPUT_ID.clone())
+                        .on_input(Message::EditText)
+                        .on_submit(Message::SaveEdit)
+                        .size(TEXT_SIZE_LABEL),
+                    row![
+                        small_button(t!("CellComments.Cancel"), Some(Message::CancelEdit)),
+                        small_button(
+                            t!("CellComments.Save"),
+                            (!edit_text.trim().is_empty()).then_some(Message::SaveEdit)
+                        ),
+                    ]
+                ]
+                .spacing(SPACE_S / 2.0)
+                .into(),
+                _ => {
+                    // Replies are indented beneath the comment they reply to
+                    let delete = if index == 0 {
+                        t!("CellComments.DeleteThread")
+                    } else {
+                        t!("CellComments.Delete")
+                    };
+                    column![
+                        commented,
+                        text(&comment.text).size(TEXT_SIZE_LABEL),
+                        row![
+                            small_button(t!("CellComments.Edit"), Some(Message::Edit(index))),
+                            small_button(delete, Some(Message::Delete(index))),
+                        ]
+                    ]
+                    .padding([0.0, 0.0, 0.0, if index == 0 { 0.0 } else { SPACE_M }])
+                    .into()
+                }
+            };
+            thread = thread.push(entry);
+        }
+
+        let title = row![
+            text(t!("CellComments.Title").replace(
+                "{cell}",
+                self.cell.as_ref().map(|cell| cell.name()).unwrap_or("")
+            ))
+            .size(TEXT_SIZE_LABEL),
+            horizontal_space(Length::Fill),
+            button(text(t!("CellComments.Close")).size(TEXT_SIZE_LABEL))
+                .on_press(Message::Close)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        let (placeholder, add) = if comments.is_empty() {
+            (t!("CellComments.AddPlaceholder"), t!("CellComments.Add"))
+        } else {
+            (
+                t!("CellComments.ReplyPlaceholder"),
+                t!("CellComments.Reply"),
+            )
+        };
+        let can_add = self.cell.is_some() && !self.new_text.trim().is_empty();
+        let mut new_comment = text_input(&placeholder, &self.new_text).size(TEXT_SIZE_LABEL);
+        if self.cell.is_some() {
+            new_comment = new_comment
+                .id(NEW_COMMENT_INPUT_ID.clone())
+                .on_input(Message::NewText)
+                .on_submit(Message::Add);
+        }
+        let add = button(text(add).size(TEXT_SIZE_LABEL))
+            .on_press_maybe(can_add.then_some(Message::Add))
+            .padding(SPACE_S)
+            .style(theme::Button::Primary);
+
+        column![
+            title,
+            horizontal_rule(1),
+            scrollable(thread).height(Length::Fill),
+            horizontal_rule(1),
+            row![new_comment, add]
+                .spacing(SPACE_S)
+                .align_items(Alignment::Center),
+        ]
+        .spacing(SPACE_S)
+        .padding(SPACE_S)
+        .width(250)
+        .height(Length::Fill)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        let Some(cell) = &self.cell else {
+            return match message {
+                Message::Close => Event::Closed,
+                _ => Event::None,
+            };
+        };
+        let (sheet_id, row_id, column_id) =
+            (cell.sheet().id(), cell.row().id(), cell.column().id());
+
+        match message {
+            Message::NewText(text) => {
+                self.new_text = text;
+                Event::None
+            }
+            Message::Add => {
+                if self.new_text.trim().is_empty() {
+                    return Event::None;
+                }
+                let comment = Comment::new(std::mem::take(&mut self.new_text).trim());
+                Event::UpdateRequested(WorkpadUpdate::SheetAddCellComment {
+                    sheet_id,
+                    row_id,
+                    column_id,
+                    comment,
+                })
+            }
+            Message::Edit(index) => {
+                let Some(comment) = cell.comments().get(index) else {
+                    return Event::None;
+                };
+                self.editing = Some((index, comment.text.clone()));
+                Event::Focus(EDIT_COMMENT_INPUT_ID.clone())
+            }
+            Message::EditText(text) => {
+                if let Some((_, edit_text)) = &mut self.editing {
+                    *edit_text = text;
+                }
+                Event::None
+            }
+            Message::SaveEdit => match self.editing.take() {
+                Some((index, text)) if !text.trim().is_empty() => {
+                    Event::UpdateRequested(WorkpadUpdate::SheetEditCellComment {
+                        sheet_id,
+                        row_id,
+                        column_id,
+                        index,
+                        text: text.trim().to_owned(),
+                    })
+                }
+                editing => {
+                    self.editing = editing;
+                    Event::None
+                }
+            },
+            Message::CancelEdit => {
+                self.editing = None;
+                Event::Focus(NEW_COMMENT_INPUT_ID.clone())
+            }
+            Message::Delete(index) => {
+                self.editing = None;
+                Event::UpdateRequested(WorkpadUpdate::SheetDeleteCellComment {
+                    sheet_id,
+                    row_id,
+                    column_id,
+                    index,
+                })
+            }
+            Message::Close => Event::Closed,
+        }
+    }
+}
+
+/// Describes who made a comment and when, e.g. "Dave at 14:02"
+pub fn commented_by(comment: &Comment) -> String {
+    t!("CellComments.CommentedBy")
+        .replace("{author}", &comment.author)
+        .replace("{time}", &time_of(comment.timestamp))
+}
+
+/// Returns the thread of comments of a cell as text, shown when hovering over the cell
+pub fn thread_summary(comments: &[Comment]) -> String {
+    comments
+        .iter()
+        .map(|comment| format!("{}\n{}", commented_by(comment), comment.text))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
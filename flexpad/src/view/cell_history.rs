@@ -0,0 +1,127 @@
+use crate::{view::history::changed_by, FLEXPAD_GRID_COLOR};
+use flexpad_model::{Cell, CellChange, Version};
+use flexpad_toolkit::prelude::*;
+use iced::{
+    theme,
+    widget::{button, column, horizontal_rule, horizontal_space, row, scrollable, text, Column},
+    Alignment, Element, Length,
+};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(Version),
+    Close,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cell_history::Message::")?;
+        match self {
+            Self::Select(version) => write!(f, "Select({version})"),
+            Self::Close => write!(f, "Close"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Closed,
+    VersionSelected(Version),
+}
+
+/// The Cell History panel shown beside the grid of the active sheet.  It lists the versions
+/// in which the value of the active cell changed.
+#[derive(Debug, Default)]
+pub struct CellHistoryUi {
+    cell_name: Option<String>,
+    version: Option<Version>,
+    changes: Vec<CellChange>,
+}
+
+impl CellHistoryUi {
+    pub fn new(cell: Option<&Cell>) -> Self {
+        let mut cell_history = Self::default();
+        cell_history.cell_updated(cell);
+        cell_history
+    }
+
+    /// Reloads the history when the active cell, or the version it is part of, changes
+    pub fn cell_updated(&mut self, cell: Option<&Cell>) {
+        self.cell_name = cell.map(|cell| cell.name().to_owned());
+        self.version = cell.map(|cell| cell.sheet().workpad().version().version());
+        self.changes = cell.map(Cell::history).unwrap_or_default();
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let mut changes = Column::new().spacing(SPACE_S / 2.0);
+        if self.cell_name.is_none() {
+            changes = changes.push(text(t!("CellHistory.NoCell")).size(TEXT_SIZE_LABEL));
+        } else if self.changes.is_empty() {
+            changes = changes.push(text(t!("CellHistory.Empty")).size(TEXT_SIZE_LABEL));
+        }
+
+        for change in &self.changes {
+            let is_current = Some(change.version()) == self.version;
+            let description = text(format!(
+                "{}  {}",
+                change.version(),
+                change.info().description()
+            ))
+            .size(TEXT_SIZE_LABEL);
+            let value = text(
+                t!("CellHistory.Value")
+                    .replace("{old}", change.old_value())
+                    .replace("{new}", change.new_value()),
+            )
+            .size(TEXT_SIZE_LABEL);
+            let changed = text(changed_by(change.info()))
+                .size(TEXT_SIZE_LABEL)
+                .style(theme::Text::Color(FLEXPAD_GRID_COLOR));
+
+            let mut entry = button(column![description, value, changed])
+                .padding(SPACE_S / 2.0)
+                .style(if is_current {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                });
+            if !is_current {
+                entry = entry.on_press(Message::Select(change.version()));
+            }
+            changes = changes.push(entry);
+        }
+
+        let title = row![
+            text(
+                t!("CellHistory.Title").replace("{cell}", self.cell_name.as_deref().unwrap_or(""))
+            )
+            .size(TEXT_SIZE_LABEL),
+            horizontal_space(Length::Fill),
+            button(text(t!("CellHistory.Close")).size(TEXT_SIZE_LABEL))
+                .on_press(Message::Close)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        column![
+            title,
+            horizontal_rule(1),
+            scrollable(changes).height(Length::Fill)
+        ]
+        .spacing(SPACE_S)
+        .padding(SPACE_S)
+        .width(250)
+        .height(Length::Fill)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Select(version) => Event::VersionSelected(version),
+            Message::Close => Event::Closed,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::FlexpadAction;
+use flexpad_model::{Workpad, WorkpadMaster, WorkpadUpdate};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{widget::column, Subscription};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Label(String),
+    Note(String),
+    Submit,
+    Cancel,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CheckpointMessage::")?;
+        match self {
+            Self::Label(label) => write!(f, "Label({label})"),
+            Self::Note(note) => write!(f, "Note({note})"),
+            Self::Cancel => write!(f, "Cancel"),
+            Self::Submit => write!(f, "Submit"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(WorkpadMaster, WorkpadUpdate),
+}
+
+/// Dialog to set, change or (by emptying the label) clear the checkpoint of a version
+#[derive(Debug)]
+pub struct CheckpointUi {
+    pad: Workpad,
+    label: String,
+    note: String,
+    existing: bool,
+}
+
+// TODO Focus management
+impl CheckpointUi {
+    pub fn new(pad: Workpad) -> Self {
+        let checkpoint = pad.checkpoint();
+        Self {
+            pad,
+            label: checkpoint
+                .as_ref()
+                .map(|checkpoint| checkpoint.label().to_owned())
+                .unwrap_or_default(),
+            note: checkpoint
+                .as_ref()
+                .map(|checkpoint| checkpoint.note().to_owned())
+                .unwrap_or_default(),
+            existing: checkpoint.is_some(),
+        }
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if self.existing || !self.label.trim().is_empty() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let body = column![
+            text_input(
+                t!("CheckpointLabel.Label"),
+                t!("CheckpointLabel.Placeholder"),
+                &self.label,
+                Message::Label,
+                None,
+            ),
+            text_input(
+                t!("CheckpointNote.Label"),
+                t!("CheckpointNote.Placeholder"),
+                &self.note,
+                Message::Note,
+                None,
+            ),
+            ButtonBar::new().push(cancel).push(ok)
+        ]
+        .spacing(SPACE_S);
+
+        let info = self.pad.version();
+        Dialog::new(
+            dialog_title(
+                t!("Checkpoint.Title")
+                    .replace("{version}", &info.version().to_string())
+                    .replace("{description}", info.description()),
+                Default::default(),
+            ),
+            body,
+        )
+        .max_width(400.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Label(label) => {
+                self.label = label;
+                Event::None
+            }
+            Message::Note(note) => {
+                self.note = note;
+                Event::None
+            }
+            Message::Cancel => Event::Cancelled,
+            Message::Submit => {
+                let version = self.pad.version().version();
+                let update = if self.label.trim().is_empty() {
+                    WorkpadUpdate::ClearCheckpoint { version }
+                } else {
+                    WorkpadUpdate::SetCheckpoint {
+                        version,
+                        label: self.label.clone(),
+                        note: self.note.clone(),
+                    }
+                };
+                Event::Submitted(self.pad.master(), update)
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{view::cell_comments::commented_by, FLEXPAD_GRID_COLOR};
+use flexpad_model::{Cell, Workpad};
+use flexpad_toolkit::prelude::*;
+use iced::{
+    theme,
+    widget::{button, column, horizontal_rule, horizontal_space, row, scrollable, text, Column},
+    Alignment, Element, Length,
+};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(Cell),
+    Close,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "comments::Message::")?;
+        match self {
+            Self::Select(cell) => write!(f, "Select({}!{})", cell.sheet().name(), cell.name()),
+            Self::Close => write!(f, "Close"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Closed,
+    CellSelected(Cell),
+}
+
+/// The All Comments panel shown beside the grid of the active sheet.  It lists the comments
+/// on the cells of every sheet of the workpad; selecting one makes its cell the active cell.
+#[derive(Debug, Default)]
+pub struct CommentsUi;
+
+impl CommentsUi {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn view(&self, pad: &Workpad) -> Element<'_, Message> {
+        let mut threads = Column::new().spacing(SPACE_S / 2.0);
+        let mut is_empty = true;
+        for sheet in pad.sheets() {
+            for cell in sheet.commented_cells() {
+                let comments = cell.comments();
+                let location = text(
+                    t!("Comments.Cell")
+                        .replace("{sheet}", sheet.name())
+                        .replace("{cell}", cell.name()),
+                )
+                .size(TEXT_SIZE_LABEL);
+                let first = text(&comments[0].text).size(TEXT_SIZE_LABEL);
+                let replies = match comments.len() - 1 {
+                    0 => String::new(),
+                    1 => t!("Comments.OneReply"),
+                    count => t!("Comments.Replies").replace("{count}", &count.to_string()),
+                };
+                let commented = text(format!("{}  {}", commented_by(&comments[0]), replies))
+                    .size(TEXT_SIZE_LABEL)
+                    .style(theme::Text::Color(FLEXPAD_GRID_COLOR));
+
+                threads = threads.push(
+                    button(column![location, first, commented])
+                        .on_press(Message::Select(cell.clone()))
+                        .padding(SPACE_S / 2.0)
+                        .width(Length::Fill)
+                        .style(theme::Button::Text),
+                );
+                is_empty = false;
+            }
+        }
+        if is_empty {
+            threads = threads.push(text(t!("Comments.Empty")).size(TEXT_SIZE_LABEL));
+        }
+
+        let title = row![
+            text(t!("Comments.Title")).size(TEXT_SIZE_LABEL),
+            horizontal_space(Length::Fill),
+            button(text(t!("Comments.Close")).size(TEXT_SIZE_LABEL))
+                .on_press(Message::Close)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        column![
+            title,
+            horizontal_rule(1),
+            scrollable(threads).height(Length::Fill)
+        ]
+        .spacing(SPACE_S)
+        .padding(SPACE_S)
+        .width(250)
+        .height(Length::Fill)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Select(cell) => Event::CellSelected(cell),
+            Message::Close => Event::Closed,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::FlexpadAction;
+use flexpad_model::{Workpad, WorkpadMaster};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{
+    widget::{column, text},
+    Subscription,
+};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Retain(String),
+    Submit,
+    Cancel,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CompactHistoryMessage::")?;
+        match self {
+            Self::Retain(retain) => write!(f, "Retain({retain})"),
+            Self::Cancel => write!(f, "Cancel"),
+            Self::Submit => write!(f, "Submit"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    /// The history of the workpad is to be compacted, keeping at most the given number of
+    /// versions (if any)
+    Submitted(WorkpadMaster, Option<usize>),
+}
+
+/// Dialog to compact the history of a workpad, removing the versions that can no longer be
+/// reached by undo or redo and optionally all but a number of the latest versions
+#[derive(Debug)]
+pub struct CompactHistoryUi {
+    pad: Workpad,
+    retain: String,
+    retain_error: Option<String>,
+}
+
+// TODO Focus management
+impl CompactHistoryUi {
+    pub fn new(pad: Workpad) -> Self {
+        Self {
+            pad,
+            retain: String::new(),
+            retain_error: None,
+        }
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if self.retain_error.is_none() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let body = column![
+            text_input(
+                t!("CompactHistory.Retain"),
+                t!("CompactHistory.RetainPlaceholder"),
+                &self.retain,
+                Message::Retain,
+                self.retain_error.as_ref(),
+            ),
+            text(t!("CompactHistory.Note"))
+                .size(TEXT_SIZE_LABEL)
+                .style(style::TextStyle::Label),
+            ButtonBar::new().push(cancel).push(ok)
+        ]
+        .spacing(SPACE_S);
+
+        Dialog::new(
+            dialog_title(t!("CompactHistory.Title"), Default::default()),
+            body,
+        )
+        .max_width(400.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Retain(retain) => {
+                self.retain_error = match parse_retain(&retain) {
+                    Ok(_) => None,
+                    Err(()) => Some(t!("CompactHistory.RetainError")),
+                };
+                self.retain = retain;
+                Event::None
+            }
+            Message::Cancel => Event::Cancelled,
+            Message::Submit => match parse_retain(&self.retain) {
+                Ok(retain) => Event::Submitted(self.pad.master(), retain),
+                Err(()) => Event::None,
+            },
+        }
+    }
+}
+
+/// Parses the number of versions to keep, where none is given by leaving it empty
+fn parse_retain(retain: &str) -> Result<Option<usize>, ()> {
+    let retain = retain.trim();
+    if retain.is_empty() {
+        return Ok(None);
+    }
+    match retain.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(Some(count)),
+        _ => Err(()),
+    }
+}
@@ -0,0 +1,170 @@
+use crate::FLEXPAD_GRID_COLOR;
+use flexpad_model::{SheetId, Version, Workpad, WorkpadDiff};
+use flexpad_toolkit::prelude::*;
+use iced::{
+    theme,
+    widget::{
+        button, column, horizontal_rule, horizontal_space, pick_list, row, scrollable, text, Column,
+    },
+    Alignment, Element, Length,
+};
+use rust_i18n::t;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(VersionChoice),
+    Close,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "compare::Message::")?;
+        match self {
+            Self::Select(choice) => write!(f, "Select({})", choice.version),
+            Self::Close => write!(f, "Close"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Closed,
+}
+
+/// A version offered for comparison
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionChoice {
+    version: Version,
+    description: String,
+}
+
+impl std::fmt::Display for VersionChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}  {}", self.version, self.description)
+    }
+}
+
+/// The Compare panel shown beside the grid of the active sheet.  It lists the changes made
+/// since a chosen version and the cells changed are highlighted in the grid.
+#[derive(Debug)]
+pub struct CompareUi {
+    from: Option<Version>,
+    diff: Option<WorkpadDiff>,
+    changed_cells: HashSet<(SheetId, usize, usize)>,
+}
+
+impl CompareUi {
+    /// Returns a panel comparing the workpad with its prior version (if any)
+    pub fn new(pad: &Workpad) -> Self {
+        let mut compare = Self {
+            from: pad.backward_versions().next().map(|info| info.version()),
+            diff: None,
+            changed_cells: HashSet::new(),
+        };
+        compare.pad_updated(pad);
+        compare
+    }
+
+    /// Returns true if the value, style or format of the cell differs from that in the
+    /// compared version
+    pub fn is_changed(&self, sheet_id: SheetId, row: usize, column: usize) -> bool {
+        self.changed_cells.contains(&(sheet_id, row, column))
+    }
+
+    /// Recomputes the changes when the workpad has moved to another version
+    pub fn pad_updated(&mut self, pad: &Workpad) {
+        let to = pad.version().version();
+        // Compacting the history may leave the compared version behind
+        self.diff = self.from.and_then(|from| pad.master().diff(from, to).ok());
+        if self.diff.is_none() {
+            self.from = None;
+        }
+        self.changed_cells.clear();
+        if let Some(diff) = &self.diff {
+            for sheet in pad.sheets() {
+                let changed = diff
+                    .changed_cells(sheet.id())
+                    .map(|(row, column)| (sheet.id(), row, column));
+                self.changed_cells.extend(changed);
+            }
+        }
+    }
+
+    pub fn view(&self, pad: &Workpad) -> Element<'_, Message> {
+        let active_version = pad.version().version();
+        let choices: Vec<VersionChoice> = pad
+            .version_tree()
+            .iter()
+            .filter(|node| node.version() != active_version)
+            .map(|node| VersionChoice {
+                version: node.version(),
+                description: node.description().to_owned(),
+            })
+            .collect();
+        let selected = self
+            .from
+            .and_then(|from| choices.iter().find(|choice| choice.version == from))
+            .cloned();
+        let versions = pick_list(choices, selected, Message::Select)
+            .placeholder(t!("Compare.Placeholder"))
+            .text_size(TEXT_SIZE_LABEL)
+            .width(Length::Fill);
+
+        let mut changes = Column::new().spacing(SPACE_S / 2.0);
+        if let Some(diff) = &self.diff {
+            let summary = match diff.changes().len() {
+                0 => t!("Compare.NoChanges"),
+                1 => t!("Compare.OneChange"),
+                count => t!("Compare.Changes").replace("{count}", &count.to_string()),
+            };
+            changes = changes.push(text(summary).size(TEXT_SIZE_LABEL));
+            for change in diff.changes() {
+                let sheet = change
+                    .sheet_id()
+                    .and_then(|id| pad.sheet_by_id(id))
+                    .map(|sheet| format!("{}: ", sheet.name()))
+                    .unwrap_or_default();
+                changes = changes.push(
+                    text(format!("{sheet}{change}"))
+                        .size(TEXT_SIZE_LABEL)
+                        .style(theme::Text::Color(FLEXPAD_GRID_COLOR)),
+                );
+            }
+        }
+
+        let title = row![
+            text(t!("Compare.Title")).size(TEXT_SIZE_LABEL),
+            horizontal_space(Length::Fill),
+            button(text(t!("Compare.Close")).size(TEXT_SIZE_LABEL))
+                .on_press(Message::Close)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        column![
+            title,
+            horizontal_rule(1),
+            versions,
+            scrollable(changes).height(Length::Fill)
+        ]
+        .spacing(SPACE_S)
+        .padding(SPACE_S)
+        .width(250)
+        .height(Length::Fill)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message, pad: &Workpad) -> Event {
+        match message {
+            Message::Select(choice) => {
+                self.from = Some(choice.version);
+                self.pad_updated(pad);
+                Event::None
+            }
+            Message::Close => Event::Closed,
+        }
+    }
+}
@@ -0,0 +1,563 @@
+use crate::FlexpadAction;
+use flexpad_model::{
+    Cell, Color, Comparison, Condition, ConditionalFormat, Format, SheetRange, WorkpadMaster,
+    WorkpadUpdate,
+};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{
+    theme,
+    widget::{button, checkbox, column, container, pick_list, row, scrollable, text, Column},
+    Alignment, Length, Subscription,
+};
+use rust_i18n::t;
+
+/// The colours offered for fills, text and colour scales, by locale key
+const COLORS: [(&str, Color); 9] = [
+    ("LightRed", Color::rgb(255, 199, 206)),
+    ("LightYellow", Color::rgb(255, 235, 156)),
+    ("LightGreen", Color::rgb(198, 239, 206)),
+    ("LightBlue", Color::rgb(189, 215, 238)),
+    ("Red", Color::rgb(192, 0, 0)),
+    ("Green", Color::rgb(0, 128, 0)),
+    ("Blue", Color::rgb(0, 0, 192)),
+    ("White", Color::WHITE),
+    ("Black", Color::BLACK),
+];
+
+const DEFAULT_COUNT: usize = 10;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(usize),
+    Add,
+    Remove,
+    MoveUp,
+    MoveDown,
+    Range(String),
+    Kind(Kind),
+    Comparison(Comparison),
+    Value(String),
+    Count(String),
+    Fill(Swatch),
+    TextColor(Swatch),
+    Bold(bool),
+    Low(Swatch),
+    High(Swatch),
+    Submit,
+    Cancel,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ConditionalFormatsMessage::")?;
+        match self {
+            Self::Select(index) => write!(f, "Select({index})"),
+            Self::Add => write!(f, "Add"),
+            Self::Remove => write!(f, "Remove"),
+            Self::MoveUp => write!(f, "MoveUp"),
+            Self::MoveDown => write!(f, "MoveDown"),
+            Self::Range(range) => write!(f, "Range({range})"),
+            Self::Kind(kind) => write!(f, "Kind({kind:?})"),
+            Self::Comparison(comparison) => write!(f, "Comparison({comparison})"),
+            Self::Value(value) => write!(f, "Value({value})"),
+            Self::Count(count) => write!(f, "Count({count})"),
+            Self::Fill(swatch) => write!(f, "Fill({swatch})"),
+            Self::TextColor(swatch) => write!(f, "TextColor({swatch})"),
+            Self::Bold(bold) => write!(f, "Bold({bold})"),
+            Self::Low(swatch) => write!(f, "Low({swatch})"),
+            Self::High(swatch) => write!(f, "High({swatch})"),
+            Self::Submit => write!(f, "Submit"),
+            Self::Cancel => write!(f, "Cancel"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(WorkpadMaster, WorkpadUpdate),
+}
+
+/// The kinds of [`Condition`] offered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Compare,
+    Contains,
+    Top,
+    Bottom,
+    Duplicates,
+    ColorScale,
+}
+
+impl Kind {
+    const ALL: [Kind; 6] = [
+        Kind::Compare,
+        Kind::Contains,
+        Kind::Top,
+        Kind::Bottom,
+        Kind::Duplicates,
+        Kind::ColorScale,
+    ];
+
+    fn of(condition: &Condition) -> Self {
+        match condition {
+            Condition::Compare { .. } => Kind::Compare,
+            Condition::Contains(_) => Kind::Contains,
+            Condition::Top(_) => Kind::Top,
+            Condition::Bottom(_) => Kind::Bottom,
+            Condition::Duplicates => Kind::Duplicates,
+            Condition::ColorScale { .. } => Kind::ColorScale,
+        }
+    }
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Kind::Compare => t!("ConditionalFormats.Compare"),
+            Kind::Contains => t!("ConditionalFormats.Contains"),
+            Kind::Top => t!("ConditionalFormats.Top"),
+            Kind::Bottom => t!("ConditionalFormats.Bottom"),
+            Kind::Duplicates => t!("ConditionalFormats.Duplicates"),
+            Kind::ColorScale => t!("ConditionalFormats.ColorScale"),
+        };
+        f.write_str(&name)
+    }
+}
+
+/// A colour (or no colour) that can be chosen, named if it is one of [`COLORS`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swatch(Option<Color>);
+
+impl Swatch {
+    /// Returns the swatches offered, including `current` if it is not one of them
+    fn options(current: Swatch, optional: bool) -> Vec<Swatch> {
+        let mut options: Vec<Swatch> = optional.then_some(Swatch(None)).into_iter().collect();
+        options.extend(COLORS.iter().map(|(_, color)| Swatch(Some(*color))));
+        if !options.contains(&current) {
+            options.push(current);
+        }
+        options
+    }
+}
+
+impl std::fmt::Display for Swatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(color) = self.0 else {
+            return f.write_str(&t!("ConditionalFormats.NoColor"));
+        };
+        match COLORS.iter().find(|(_, c)| *c == color) {
+            Some((key, _)) => f.write_str(&t!(&format!("ConditionalFormats.Colors.{key}"))),
+            None => write!(f, "#{:02x}{:02x}{:02x}", color.r, color.g, color.b),
+        }
+    }
+}
+
+/// Dialog to manage the conditional formatting rules of the sheet of the active cell: rules
+/// are listed in order of precedence and can be added, edited, removed and reordered.
+/// Nothing changes until the rules are submitted.
+#[derive(Debug)]
+pub struct ConditionalFormatsUi {
+    cell: Cell,
+    rules: Vec<ConditionalFormat>,
+    selected: Option<usize>,
+    // The fields of the selected rule being edited as text
+    range: String,
+    range_error: Option<String>,
+    value: String,
+    count: String,
+    count_error: Option<String>,
+}
+
+// TODO Focus management
+impl ConditionalFormatsUi {
+    pub fn new(cell: Cell) -> Self {
+        let rules = cell.sheet().conditional_formats().to_vec();
+        let mut ui = Self {
+            cell,
+            rules,
+            selected: None,
+            range: String::new(),
+            range_error: None,
+            value: String::new(),
+            count: String::new(),
+            count_error: None,
+        };
+        if !ui.rules.is_empty() {
+            ui.select(0);
+        }
+        ui
+    }
+
+    fn select(&mut self, index: usize) {
+        let rule = &self.rules[index];
+        self.range = self.cell.sheet().range_name(&rule.range);
+        self.value = match &rule.condition {
+            Condition::Compare { value, .. } => value.clone(),
+            Condition::Contains(text) => text.clone(),
+            _ => String::new(),
+        };
+        self.count = match rule.condition {
+            Condition::Top(n) | Condition::Bottom(n) => n.to_string(),
+            _ => DEFAULT_COUNT.to_string(),
+        };
+        self.range_error = None;
+        self.count_error = None;
+        self.selected = Some(index);
+    }
+
+    fn selected_rule(&mut self) -> Option<&mut ConditionalFormat> {
+        self.selected.and_then(|index| self.rules.get_mut(index))
+    }
+
+    fn has_errors(&self) -> bool {
+        self.range_error.is_some() || self.count_error.is_some()
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if !self.has_errors() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let choice = |name: String, element: iced::Element<'static, Message>| {
+            row![container(label(name)).width(100), element]
+                .spacing(SPACE_S)
+                .align_items(Alignment::Center)
+        };
+        let button = |label: String, msg: Option<Message>| {
+            button(text(label).size(TEXT_SIZE_LABEL))
+                .on_press_maybe(msg)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary)
+        };
+        let swatches =
+            |name: String, swatch: Swatch, optional: bool, msg: fn(Swatch) -> Message| {
+                choice(
+                    name,
+                    pick_list(Swatch::options(swatch, optional), Some(swatch), msg)
+                        .text_size(TEXT_SIZE_LABEL)
+                        .width(Length::Fill)
+                        .into(),
+                )
+            };
+
+        let mut rules = Column::new().spacing(SPACE_S / 2.0);
+        if self.rules.is_empty() {
+            rules = rules.push(
+                text(t!("ConditionalFormats.NoRules"))
+                    .size(TEXT_SIZE_LABEL)
+                    .style(style::TextStyle::Label),
+            );
+        }
+        let sheet = self.cell.sheet();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let is_selected = self.selected == Some(index);
+            let description = format!("{}: {}", sheet.range_name(&rule.range), rule.condition);
+            let mut entry = iced::widget::button(text(description).size(TEXT_SIZE_LABEL))
+                .width(Length::Fill)
+                .padding(SPACE_S / 2.0)
+                .style(if is_selected {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                });
+            if !is_selected {
+                entry = entry.on_press(Message::Select(index));
+            }
+            rules = rules.push(entry);
+        }
+
+        let selected = self.selected;
+        let last = self.rules.len().saturating_sub(1);
+        let actions = row![
+            button(t!("ConditionalFormats.Add"), Some(Message::Add)),
+            button(
+                t!("ConditionalFormats.Remove"),
+                selected.map(|_| Message::Remove)
+            ),
+            button(
+                t!("ConditionalFormats.MoveUp"),
+                selected.filter(|i| *i > 0).map(|_| Message::MoveUp)
+            ),
+            button(
+                t!("ConditionalFormats.MoveDown"),
+                selected.filter(|i| *i < last).map(|_| Message::MoveDown)
+            ),
+        ]
+        .spacing(SPACE_S);
+
+        let mut body = column![
+            label(t!("ConditionalFormats.Rules")),
+            scrollable(rules).height(120),
+            actions,
+        ]
+        .spacing(SPACE_M);
+
+        if let Some(rule) = selected.and_then(|index| self.rules.get(index)) {
+            let kind = Kind::of(&rule.condition);
+            body = body
+                .push(text_input(
+                    t!("ConditionalFormats.Range"),
+                    t!("ConditionalFormats.RangePlaceholder"),
+                    &self.range,
+                    Message::Range,
+                    self.range_error.as_ref(),
+                ))
+                .push(choice(
+                    t!("ConditionalFormats.Condition"),
+                    pick_list(&Kind::ALL[..], Some(kind), Message::Kind)
+                        .text_size(TEXT_SIZE_LABEL)
+                        .width(Length::Fill)
+                        .into(),
+                ));
+            body = match &rule.condition {
+                Condition::Compare { comparison, .. } => body.push(
+                    row![
+                        pick_list(&Comparison::ALL[..], Some(*comparison), Message::Comparison)
+                            .text_size(TEXT_SIZE_LABEL),
+                        text_input(
+                            t!("ConditionalFormats.Value"),
+                            t!("ConditionalFormats.ValuePlaceholder"),
+                            &self.value,
+                            Message::Value,
+                            None,
+                        ),
+                    ]
+                    .spacing(SPACE_S)
+                    .align_items(Alignment::End),
+                ),
+                Condition::Contains(_) => body.push(text_input(
+                    t!("ConditionalFormats.Text"),
+                    t!("ConditionalFormats.TextPlaceholder"),
+                    &self.value,
+                    Message::Value,
+                    None,
+                )),
+                Condition::Top(_) | Condition::Bottom(_) => body.push(text_input(
+                    t!("ConditionalFormats.Count"),
+                    t!("ConditionalFormats.CountPlaceholder"),
+                    &self.count,
+                    Message::Count,
+                    self.count_error.as_ref(),
+                )),
+                Condition::Duplicates => body,
+                Condition::ColorScale { low, high } => body
+                    .push(swatches(
+                        t!("ConditionalFormats.Low"),
+                        Swatch(Some(*low)),
+                        false,
+                        Message::Low,
+                    ))
+                    .push(swatches(
+                        t!("ConditionalFormats.High"),
+                        Swatch(Some(*high)),
+                        false,
+                        Message::High,
+                    )),
+            };
+            if kind != Kind::ColorScale {
+                body = body
+                    .push(swatches(
+                        t!("ConditionalFormats.Fill"),
+                        Swatch(rule.format.fill_color),
+                        true,
+                        Message::Fill,
+                    ))
+                    .push(swatches(
+                        t!("ConditionalFormats.TextColor"),
+                        Swatch(rule.format.text_color),
+                        true,
+                        Message::TextColor,
+                    ))
+                    .push(
+                        checkbox(
+                            t!("ConditionalFormats.Bold"),
+                            rule.format.bold.unwrap_or_default(),
+                            Message::Bold,
+                        )
+                        .size(TEXT_SIZE_LABEL.0)
+                        .text_size(TEXT_SIZE_LABEL),
+                    );
+            }
+        }
+
+        let body = body
+            .push(
+                text(t!("ConditionalFormats.Note"))
+                    .size(TEXT_SIZE_LABEL)
+                    .style(style::TextStyle::Label),
+            )
+            .push(ButtonBar::new().push(cancel).push(ok));
+
+        Dialog::new(
+            dialog_title(
+                t!("ConditionalFormats.Title").replace("{sheet}", self.cell.sheet().name()),
+                Default::default(),
+            ),
+            body,
+        )
+        .max_width(500.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Select(index) => self.select(index),
+            Message::Add => {
+                let (row, column) = (self.cell.row().index(), self.cell.column().index());
+                self.rules.push(ConditionalFormat {
+                    range: SheetRange::single(row, column),
+                    condition: Condition::Compare {
+                        comparison: Comparison::Greater,
+                        value: "0".to_owned(),
+                    },
+                    format: Format {
+                        fill_color: Some(COLORS[0].1),
+                        ..Format::NONE
+                    },
+                });
+                self.select(self.rules.len() - 1);
+            }
+            Message::Remove => {
+                if let Some(index) = self.selected.take() {
+                    self.rules.remove(index);
+                    if !self.rules.is_empty() {
+                        self.select(index.min(self.rules.len() - 1));
+                    }
+                }
+            }
+            Message::MoveUp => {
+                if let Some(index) = self.selected.filter(|i| *i > 0) {
+                    self.rules.swap(index, index - 1);
+                    self.selected = Some(index - 1);
+                }
+            }
+            Message::MoveDown => {
+                if let Some(index) = self.selected.filter(|i| *i + 1 < self.rules.len()) {
+                    self.rules.swap(index, index + 1);
+                    self.selected = Some(index + 1);
+                }
+            }
+            Message::Range(range) => {
+                let sheet = self.cell.sheet();
+                let result = sheet.workpad().resolve_reference(&range);
+                self.range = range;
+                self.range_error = match result {
+                    Ok(reference) if reference.sheet() != sheet => {
+                        Some(t!("ConditionalFormats.OtherSheetError"))
+                    }
+                    Ok(reference) => {
+                        if let Some(rule) = self.selected_rule() {
+                            rule.range = reference.range();
+                        }
+                        None
+                    }
+                    Err(err) => Some(err.to_string()),
+                };
+            }
+            Message::Kind(kind) => {
+                let count = self.count.trim().parse().unwrap_or(DEFAULT_COUNT);
+                let value = self.value.clone();
+                let condition = match kind {
+                    Kind::Compare => Condition::Compare {
+                        comparison: Comparison::Equal,
+                        value,
+                    },
+                    Kind::Contains => Condition::Contains(value),
+                    Kind::Top => Condition::Top(count),
+                    Kind::Bottom => Condition::Bottom(count),
+                    Kind::Duplicates => Condition::Duplicates,
+                    Kind::ColorScale => Condition::ColorScale {
+                        low: Color::WHITE,
+                        high: COLORS[2].1,
+                    },
+                };
+                if let Some(rule) = self.selected_rule() {
+                    if Kind::of(&rule.condition) != kind {
+                        rule.condition = condition;
+                    }
+                }
+                self.count = count.to_string();
+                self.count_error = None;
+            }
+            Message::Comparison(new_comparison) => {
+                if let Some(ConditionalFormat {
+                    condition: Condition::Compare { comparison, .. },
+                    ..
+                }) = self.selected_rule()
+                {
+                    *comparison = new_comparison;
+                }
+            }
+            Message::Value(new_value) => {
+                match self.selected_rule().map(|rule| &mut rule.condition) {
+                    Some(Condition::Compare { value, .. }) => *value = new_value.clone(),
+                    Some(Condition::Contains(text)) => *text = new_value.clone(),
+                    _ => (),
+                }
+                self.value = new_value;
+            }
+            Message::Count(count) => {
+                let parsed = count.trim().parse::<usize>().ok().filter(|n| *n > 0);
+                self.count = count;
+                self.count_error = match parsed {
+                    Some(count) => {
+                        match self.selected_rule().map(|rule| &mut rule.condition) {
+                            Some(Condition::Top(n)) | Some(Condition::Bottom(n)) => *n = count,
+                            _ => (),
+                        }
+                        None
+                    }
+                    None => Some(t!("ConditionalFormats.CountError")),
+                };
+            }
+            Message::Fill(Swatch(color)) => {
+                if let Some(rule) = self.selected_rule() {
+                    rule.format.fill_color = color;
+                }
+            }
+            Message::TextColor(Swatch(color)) => {
+                if let Some(rule) = self.selected_rule() {
+                    rule.format.text_color = color;
+                }
+            }
+            Message::Bold(bold) => {
+                if let Some(rule) = self.selected_rule() {
+                    rule.format.bold = bold.then_some(true);
+                }
+            }
+            Message::Low(Swatch(color)) => {
+                if let Some(Condition::ColorScale { low, .. }) =
+                    self.selected_rule().map(|rule| &mut rule.condition)
+                {
+                    *low = color.unwrap_or(Color::WHITE);
+                }
+            }
+            Message::High(Swatch(color)) => {
+                if let Some(Condition::ColorScale { high, .. }) =
+                    self.selected_rule().map(|rule| &mut rule.condition)
+                {
+                    *high = color.unwrap_or(Color::WHITE);
+                }
+            }
+            Message::Cancel => return Event::Cancelled,
+            Message::Submit => {
+                let update = WorkpadUpdate::SheetSetConditionalFormats {
+                    sheet_id: self.cell.sheet().id(),
+                    conditional_formats: self.rules.clone(),
+                };
+                return Event::Submitted(self.cell.sheet().workpad().master(), update);
+            }
+        }
+        Event::None
+    }
+}
@@ -0,0 +1,199 @@
+use flexpad_model::{FindDirection, FindOptions, FindScope, Finder};
+use flexpad_toolkit::prelude::*;
+use iced::{
+    theme,
+    widget::{button, checkbox, column, horizontal_rule, horizontal_space, row, text, text_input},
+    Alignment, Element, Length,
+};
+use once_cell::sync::Lazy;
+use rust_i18n::t;
+
+pub static FIND_INPUT_ID: Lazy<text_input::Id> = Lazy::new(text_input::Id::unique);
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Text(String),
+    Replacement(String),
+    MatchCase(bool),
+    WholeCell(bool),
+    Regex(bool),
+    AllSheets(bool),
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+    Close,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "find::Message::")?;
+        match self {
+            Self::Text(value) => write!(f, "Text({value})"),
+            Self::Replacement(value) => write!(f, "Replacement({value})"),
+            Self::MatchCase(value) => write!(f, "MatchCase({value})"),
+            Self::WholeCell(value) => write!(f, "WholeCell({value})"),
+            Self::Regex(value) => write!(f, "Regex({value})"),
+            Self::AllSheets(value) => write!(f, "AllSheets({value})"),
+            Self::Next => write!(f, "Next"),
+            Self::Previous => write!(f, "Previous"),
+            Self::Replace => write!(f, "Replace"),
+            Self::ReplaceAll => write!(f, "ReplaceAll"),
+            Self::Close => write!(f, "Close"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Closed,
+    FindNext(Finder, FindScope, FindDirection),
+    Replace(Finder, FindScope, String),
+    ReplaceAll(Finder, FindScope, String),
+}
+
+/// The Find panel shown above the grid of the active sheet
+#[derive(Debug, Default)]
+pub struct FindUi {
+    text: String,
+    replacement: String,
+    options: FindOptions,
+    scope: FindScope,
+    status: Option<String>,
+}
+
+impl FindUi {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Shows a status message (e.g. nothing found) in the panel
+    pub fn set_status(&mut self, status: Option<String>) {
+        self.status = status;
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let button = |label: String, msg| {
+            button(text(label).size(TEXT_SIZE_LABEL))
+                .on_press(msg)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary)
+        };
+        let check = |label: String, checked, f: fn(bool) -> Message| {
+            checkbox(label, checked, f)
+                .size(TEXT_SIZE_LABEL.0)
+                .text_size(TEXT_SIZE_LABEL)
+        };
+
+        let find_row = row![
+            text_input(&t!("Find.Placeholder"), &self.text)
+                .id(FIND_INPUT_ID.clone())
+                .on_input(Message::Text)
+                .on_submit(Message::Next)
+                .size(TEXT_SIZE_LABEL)
+                .width(250),
+            button(t!("Find.Previous"), Message::Previous),
+            button(t!("Find.Next"), Message::Next),
+            check(
+                t!("Find.MatchCase"),
+                self.options.match_case,
+                Message::MatchCase
+            ),
+            check(
+                t!("Find.WholeCell"),
+                self.options.whole_cell,
+                Message::WholeCell
+            ),
+            check(t!("Find.Regex"), self.options.regex, Message::Regex),
+            check(
+                t!("Find.AllSheets"),
+                self.scope == FindScope::Workpad,
+                Message::AllSheets
+            ),
+            text(self.status.as_deref().unwrap_or_default())
+                .size(TEXT_SIZE_LABEL)
+                .style(style::TextStyle::Error),
+            horizontal_space(Length::Fill),
+            button(t!("Find.Close"), Message::Close),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        let replace_row = row![
+            text_input(&t!("Find.ReplacePlaceholder"), &self.replacement)
+                .on_input(Message::Replacement)
+                .on_submit(Message::Replace)
+                .size(TEXT_SIZE_LABEL)
+                .width(250),
+            button(t!("Find.Replace"), Message::Replace),
+            button(t!("Find.ReplaceAll"), Message::ReplaceAll),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        column![
+            horizontal_rule(1),
+            column![find_row, replace_row]
+                .spacing(SPACE_S)
+                .padding(SPACE_S),
+        ]
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Text(value) => {
+                self.text = value;
+                self.status = None;
+                Event::None
+            }
+            Message::Replacement(value) => {
+                self.replacement = value;
+                Event::None
+            }
+            Message::MatchCase(value) => {
+                self.options.match_case = value;
+                Event::None
+            }
+            Message::WholeCell(value) => {
+                self.options.whole_cell = value;
+                Event::None
+            }
+            Message::Regex(value) => {
+                self.options.regex = value;
+                self.status = None;
+                Event::None
+            }
+            Message::AllSheets(value) => {
+                self.scope = if value {
+                    FindScope::Workpad
+                } else {
+                    FindScope::Sheet
+                };
+                Event::None
+            }
+            Message::Next => self.with_finder(|finder, scope, _| {
+                Event::FindNext(finder, scope, FindDirection::Forward)
+            }),
+            Message::Previous => self.with_finder(|finder, scope, _| {
+                Event::FindNext(finder, scope, FindDirection::Backward)
+            }),
+            Message::Replace => self.with_finder(Event::Replace),
+            Message::ReplaceAll => self.with_finder(Event::ReplaceAll),
+            Message::Close => Event::Closed,
+        }
+    }
+
+    fn with_finder(&mut self, f: impl FnOnce(Finder, FindScope, String) -> Event) -> Event {
+        match Finder::new(&self.text, self.options) {
+            Ok(finder) => {
+                self.status = None;
+                f(finder, self.scope, self.replacement.clone())
+            }
+            Err(err) => {
+                self.status = Some(err.to_string());
+                Event::None
+            }
+        }
+    }
+}
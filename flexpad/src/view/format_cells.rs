@@ -0,0 +1,406 @@
+use crate::FlexpadAction;
+use flexpad_model::{
+    Cell, Format, NumberFormat, NumberFormatError, TextOverflow, WorkpadMaster, WorkpadUpdate,
+};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{
+    widget::{checkbox, column, container, pick_list, row, text},
+    Alignment, Length, Subscription,
+};
+use rust_i18n::t;
+
+/// The date patterns offered, the first being the default
+const DATE_PATTERNS: [&str; 5] = [
+    "yyyy-mm-dd",
+    "dd/mm/yyyy",
+    "mm/dd/yyyy",
+    "d mmm yyyy",
+    "dddd, d mmmm yyyy",
+];
+
+/// The time patterns offered, the first being the default
+const TIME_PATTERNS: [&str; 4] = ["hh:mm", "hh:mm:ss", "h:mm AM/PM", "yyyy-mm-dd hh:mm"];
+
+const DEFAULT_DECIMALS: u8 = 2;
+const MAX_DECIMALS: u8 = 10;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Category(Category),
+    Decimals(u8),
+    Thousands(bool),
+    Symbol(String),
+    Pattern(String),
+    Code(String),
+    TextControl(TextControl),
+    Submit,
+    Cancel,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FormatCellsMessage::")?;
+        match self {
+            Self::Category(category) => write!(f, "Category({category:?})"),
+            Self::Decimals(decimals) => write!(f, "Decimals({decimals})"),
+            Self::Thousands(thousands) => write!(f, "Thousands({thousands})"),
+            Self::Symbol(symbol) => write!(f, "Symbol({symbol})"),
+            Self::Pattern(pattern) => write!(f, "Pattern({pattern})"),
+            Self::Code(code) => write!(f, "Code({code})"),
+            Self::TextControl(control) => write!(f, "TextControl({control:?})"),
+            Self::Cancel => write!(f, "Cancel"),
+            Self::Submit => write!(f, "Submit"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(WorkpadMaster, WorkpadUpdate),
+}
+
+/// The kinds of [`NumberFormat`] offered, plus `General` for none
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    General,
+    Number,
+    Currency,
+    Percentage,
+    Scientific,
+    Date,
+    Time,
+    Custom,
+}
+
+impl Category {
+    const ALL: [Category; 8] = [
+        Category::General,
+        Category::Number,
+        Category::Currency,
+        Category::Percentage,
+        Category::Scientific,
+        Category::Date,
+        Category::Time,
+        Category::Custom,
+    ];
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Category::General => t!("FormatCells.General"),
+            Category::Number => t!("FormatCells.Number"),
+            Category::Currency => t!("FormatCells.Currency"),
+            Category::Percentage => t!("FormatCells.Percentage"),
+            Category::Scientific => t!("FormatCells.Scientific"),
+            Category::Date => t!("FormatCells.Date"),
+            Category::Time => t!("FormatCells.Time"),
+            Category::Custom => t!("FormatCells.Custom"),
+        };
+        f.write_str(&name)
+    }
+}
+
+/// How text too wide for the cell is displayed, a [`TextOverflow`] named for the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextControl(TextOverflow);
+
+impl TextControl {
+    const ALL: [TextControl; 4] = [
+        TextControl(TextOverflow::Clip),
+        TextControl(TextOverflow::Overflow),
+        TextControl(TextOverflow::Wrap),
+        TextControl(TextOverflow::ShrinkToFit),
+    ];
+}
+
+impl std::fmt::Display for TextControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self.0 {
+            TextOverflow::Clip => t!("FormatCells.Clip"),
+            TextOverflow::Overflow => t!("FormatCells.Overflow"),
+            TextOverflow::Wrap => t!("FormatCells.Wrap"),
+            TextOverflow::ShrinkToFit => t!("FormatCells.ShrinkToFit"),
+        };
+        f.write_str(&name)
+    }
+}
+
+/// Dialog to choose how the value of the active cell is displayed, with a preview of the
+/// value in the format chosen
+#[derive(Debug)]
+pub struct FormatCellsUi {
+    cell: Cell,
+    category: Category,
+    decimals: u8,
+    thousands: bool,
+    symbol: String,
+    pattern: String,
+    code: String,
+    text_control: TextControl,
+}
+
+// TODO Focus management
+impl FormatCellsUi {
+    pub fn new(cell: Cell) -> Self {
+        let mut ui = Self {
+            cell,
+            category: Category::General,
+            decimals: DEFAULT_DECIMALS,
+            thousands: true,
+            symbol: t!("FormatCells.DefaultSymbol"),
+            pattern: DATE_PATTERNS[0].to_owned(),
+            code: String::new(),
+            text_control: TextControl(TextOverflow::Clip),
+        };
+
+        let format = ui.cell.resolved_format();
+        if let Some(text_overflow) = format.text_overflow {
+            ui.text_control = TextControl(text_overflow);
+        }
+        let Some(number_format) = format.number_format else {
+            return ui;
+        };
+        ui.code = number_format.code().into_owned();
+        match number_format {
+            NumberFormat::Number {
+                decimals,
+                thousands,
+            } => {
+                ui.category = Category::Number;
+                ui.decimals = decimals;
+                ui.thousands = thousands;
+            }
+            NumberFormat::Currency { symbol, decimals } => {
+                ui.category = Category::Currency;
+                ui.symbol = symbol;
+                ui.decimals = decimals;
+            }
+            NumberFormat::Percentage { decimals } => {
+                ui.category = Category::Percentage;
+                ui.decimals = decimals;
+            }
+            NumberFormat::Scientific { decimals } => {
+                ui.category = Category::Scientific;
+                ui.decimals = decimals;
+            }
+            NumberFormat::DateTime(pattern) => {
+                ui.category = if TIME_PATTERNS.contains(&pattern.as_str()) {
+                    Category::Time
+                } else {
+                    Category::Date
+                };
+                ui.pattern = pattern;
+            }
+            NumberFormat::Custom(_) => ui.category = Category::Custom,
+        }
+        ui
+    }
+
+    /// Returns the number format chosen, `None` for `General`
+    fn number_format(&self) -> Option<NumberFormat> {
+        match self.category {
+            Category::General => None,
+            Category::Number => Some(NumberFormat::Number {
+                decimals: self.decimals,
+                thousands: self.thousands,
+            }),
+            Category::Currency => Some(NumberFormat::Currency {
+                symbol: self.symbol.clone(),
+                decimals: self.decimals,
+            }),
+            Category::Percentage => Some(NumberFormat::Percentage {
+                decimals: self.decimals,
+            }),
+            Category::Scientific => Some(NumberFormat::Scientific {
+                decimals: self.decimals,
+            }),
+            Category::Date | Category::Time => Some(NumberFormat::DateTime(self.pattern.clone())),
+            Category::Custom => Some(NumberFormat::Custom(self.code.clone())),
+        }
+    }
+
+    fn error(&self) -> Option<NumberFormatError> {
+        self.number_format()
+            .and_then(|number_format| number_format.validate().err())
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let error = self.error().map(|err| err.to_string());
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if error.is_none() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let choice = |name: String, element: iced::Element<'static, Message>| {
+            row![container(label(name)).width(100), element]
+                .spacing(SPACE_S)
+                .align_items(Alignment::Center)
+        };
+        let decimals = || {
+            choice(
+                t!("FormatCells.Decimals"),
+                pick_list(
+                    (0..=MAX_DECIMALS).collect::<Vec<_>>(),
+                    Some(self.decimals),
+                    Message::Decimals,
+                )
+                .text_size(TEXT_SIZE_LABEL)
+                .into(),
+            )
+        };
+        let patterns = |patterns: &[&str]| {
+            choice(
+                t!("FormatCells.Pattern"),
+                pick_list(
+                    patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+                    Some(self.pattern.clone()),
+                    Message::Pattern,
+                )
+                .text_size(TEXT_SIZE_LABEL)
+                .width(Length::Fill)
+                .into(),
+            )
+        };
+
+        let mut body = column![choice(
+            t!("FormatCells.Category"),
+            pick_list(&Category::ALL[..], Some(self.category), Message::Category)
+                .text_size(TEXT_SIZE_LABEL)
+                .width(Length::Fill)
+                .into(),
+        )]
+        .spacing(SPACE_M);
+        body = match self.category {
+            Category::General => body,
+            Category::Number => body.push(decimals()).push(
+                checkbox(
+                    t!("FormatCells.Thousands"),
+                    self.thousands,
+                    Message::Thousands,
+                )
+                .size(TEXT_SIZE_LABEL.0)
+                .text_size(TEXT_SIZE_LABEL),
+            ),
+            Category::Currency => body.push(decimals()).push(text_input(
+                t!("FormatCells.Symbol"),
+                t!("FormatCells.SymbolPlaceholder"),
+                &self.symbol,
+                Message::Symbol,
+                None,
+            )),
+            Category::Percentage | Category::Scientific => body.push(decimals()),
+            Category::Date => body.push(patterns(&DATE_PATTERNS)),
+            Category::Time => body.push(patterns(&TIME_PATTERNS)),
+            Category::Custom => body.push(text_input(
+                t!("FormatCells.Code"),
+                t!("FormatCells.CodePlaceholder"),
+                &self.code,
+                Message::Code,
+                error.as_ref(),
+            )),
+        };
+
+        let preview = match self.number_format() {
+            Some(number_format) if error.is_none() => {
+                number_format.apply(self.cell.value()).into_owned()
+            }
+            _ => self.cell.value().to_owned(),
+        };
+        let body = body
+            .push(choice(
+                t!("FormatCells.TextControl"),
+                pick_list(
+                    &TextControl::ALL[..],
+                    Some(self.text_control),
+                    Message::TextControl,
+                )
+                .text_size(TEXT_SIZE_LABEL)
+                .width(Length::Fill)
+                .into(),
+            ))
+            .push(choice(
+                t!("FormatCells.Preview"),
+                text(preview).size(TEXT_SIZE_INPUT).into(),
+            ))
+            .push(ButtonBar::new().push(cancel).push(ok));
+
+        Dialog::new(
+            dialog_title(
+                t!("FormatCells.Title").replace("{cell}", self.cell.name()),
+                Default::default(),
+            ),
+            body,
+        )
+        .max_width(400.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Category(category) => {
+                if category == Category::Custom && self.code.is_empty() {
+                    self.code = self
+                        .number_format()
+                        .map(|number_format| number_format.code().into_owned())
+                        .unwrap_or_else(|| "General".to_owned());
+                }
+                if category == Category::Time && !TIME_PATTERNS.contains(&self.pattern.as_str()) {
+                    self.pattern = TIME_PATTERNS[0].to_owned();
+                }
+                if category == Category::Date && !DATE_PATTERNS.contains(&self.pattern.as_str()) {
+                    self.pattern = DATE_PATTERNS[0].to_owned();
+                }
+                self.category = category;
+                Event::None
+            }
+            Message::Decimals(decimals) => {
+                self.decimals = decimals;
+                Event::None
+            }
+            Message::Thousands(thousands) => {
+                self.thousands = thousands;
+                Event::None
+            }
+            Message::Symbol(symbol) => {
+                self.symbol = symbol;
+                Event::None
+            }
+            Message::Pattern(pattern) => {
+                self.pattern = pattern;
+                Event::None
+            }
+            Message::Code(code) => {
+                self.code = code;
+                Event::None
+            }
+            Message::TextControl(control) => {
+                self.text_control = control;
+                Event::None
+            }
+            Message::Cancel => Event::Cancelled,
+            Message::Submit => {
+                let update = WorkpadUpdate::SheetSetCellFormat {
+                    sheet_id: self.cell.sheet().id(),
+                    row_id: self.cell.row().id(),
+                    column_id: self.cell.column().id(),
+                    format: Format {
+                        number_format: self.number_format(),
+                        text_overflow: Some(self.text_control.0),
+                        ..self.cell.format().clone()
+                    },
+                };
+                Event::Submitted(self.cell.sheet().workpad().master(), update)
+            }
+        }
+    }
+}
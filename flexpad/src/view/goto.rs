@@ -0,0 +1,96 @@
+use crate::FlexpadAction;
+use flexpad_model::{CellReference, Workpad};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{widget::column, Subscription};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Reference(String),
+    Cancel,
+    Submit,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GotoMessage::")?;
+        match self {
+            Self::Reference(reference) => write!(f, "Reference({reference})"),
+            Self::Cancel => write!(f, "Cancel"),
+            Self::Submit => write!(f, "Submit"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(CellReference),
+}
+
+#[derive(Debug)]
+pub struct GotoUi {
+    pad: Workpad,
+    reference: String,
+    reference_error: Option<String>,
+}
+
+// TODO Focus management
+impl GotoUi {
+    pub fn new(pad: Workpad) -> Self {
+        Self {
+            pad,
+            reference: String::new(),
+            reference_error: None,
+        }
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if self.reference_error.is_none() && !self.reference.trim().is_empty() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let body = column![
+            text_input(
+                t!("GotoReference.Label"),
+                t!("GotoReference.Placeholder"),
+                &self.reference,
+                Message::Reference,
+                self.reference_error.as_ref(),
+            ),
+            ButtonBar::new().push(cancel).push(ok)
+        ]
+        .spacing(SPACE_S);
+
+        Dialog::new(dialog_title(t!("Goto.Title"), Default::default()), body)
+            .max_width(400.0)
+            .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Reference(reference) => {
+                self.reference = reference;
+                self.reference_error = None;
+                Event::None
+            }
+            Message::Cancel => Event::Cancelled,
+            Message::Submit => match self.pad.resolve_reference(&self.reference) {
+                Ok(reference) => Event::Submitted(reference),
+                Err(err) => {
+                    self.reference_error = Some(err.to_string());
+                    Event::None
+                }
+            },
+        }
+    }
+}
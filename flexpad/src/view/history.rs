@@ -0,0 +1,128 @@
+use crate::FLEXPAD_GRID_COLOR;
+use chrono::{DateTime, Local};
+use flexpad_model::{Version, VersionInfo, Workpad};
+use flexpad_toolkit::prelude::*;
+use iced::{
+    theme,
+    widget::{button, column, horizontal_rule, horizontal_space, row, scrollable, text, Column},
+    Alignment, Element, Length,
+};
+use rust_i18n::t;
+use std::time::SystemTime;
+
+const INDENT: f32 = SPACE_M;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(Version),
+    Close,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "history::Message::")?;
+        match self {
+            Self::Select(version) => write!(f, "Select({version})"),
+            Self::Close => write!(f, "Close"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Closed,
+    VersionSelected(Version),
+}
+
+/// The History panel shown beside the grid of the active sheet.  It lists every version of
+/// the workpad, including those on branches that undo and redo cannot reach.
+#[derive(Debug, Default)]
+pub struct HistoryUi {}
+
+impl HistoryUi {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn view(&self, pad: &Workpad) -> Element<'_, Message> {
+        let active_version = pad.version().version();
+
+        let mut versions = Column::new().spacing(SPACE_S / 2.0);
+        for node in pad.version_tree() {
+            let is_active = node.version() == active_version;
+            let description =
+                text(format!("{}  {}", node.version(), node.description())).size(TEXT_SIZE_LABEL);
+            let changed = text(changed_by(node.info()))
+                .size(TEXT_SIZE_LABEL)
+                .style(theme::Text::Color(FLEXPAD_GRID_COLOR));
+            let mut label = column![description, changed];
+            if let Some(checkpoint) = node.checkpoint() {
+                label = label.push(
+                    text(format!("\u{2691} {}", checkpoint.label()))
+                        .size(TEXT_SIZE_LABEL)
+                        .style(theme::Text::Color(FLEXPAD_GRID_COLOR)),
+                );
+            }
+            let mut entry = button(label).padding(SPACE_S / 2.0).style(if is_active {
+                theme::Button::Primary
+            } else {
+                theme::Button::Text
+            });
+            if !is_active {
+                entry = entry.on_press(Message::Select(node.version()));
+            }
+            versions = versions.push(row![
+                horizontal_space(Length::Fixed(INDENT * node.depth() as f32)),
+                entry
+            ]);
+        }
+
+        let title = row![
+            text(t!("History.Title")).size(TEXT_SIZE_LABEL),
+            horizontal_space(Length::Fill),
+            button(text(t!("History.Close")).size(TEXT_SIZE_LABEL))
+                .on_press(Message::Close)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        column![
+            title,
+            horizontal_rule(1),
+            scrollable(versions).height(Length::Fill)
+        ]
+        .spacing(SPACE_S)
+        .padding(SPACE_S)
+        .width(250)
+        .height(Length::Fill)
+        .into()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Select(version) => Event::VersionSelected(version),
+            Message::Close => Event::Closed,
+        }
+    }
+}
+
+/// Describes who changed the workpad and when, e.g. "changed by Dave at 14:02".  Changes
+/// made before today show the date as well as the time.
+pub fn changed_by(info: &VersionInfo) -> String {
+    t!("History.ChangedBy")
+        .replace("{author}", info.author())
+        .replace("{time}", &time_of(info.timestamp()))
+}
+
+/// Formats a time for display, e.g. "14:02".  Times before today show the date as well.
+pub fn time_of(timestamp: SystemTime) -> String {
+    let when: DateTime<Local> = timestamp.into();
+    let format = if when.date_naive() == Local::now().date_naive() {
+        "%H:%M"
+    } else {
+        "%Y-%m-%d %H:%M"
+    };
+    when.format(format).to_string()
+}
@@ -0,0 +1,240 @@
+use crate::FlexpadAction;
+use flexpad_model::{Cell, Hyperlink, ReferenceError, Workpad, WorkpadMaster, WorkpadUpdate};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{
+    theme,
+    widget::{button, column, container, pick_list, row, text},
+    Alignment, Length, Subscription,
+};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Kind(LinkKind),
+    Target(String),
+    Remove,
+    Cancel,
+    Submit,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HyperlinkMessage::")?;
+        match self {
+            Self::Kind(kind) => write!(f, "Kind({kind:?})"),
+            Self::Target(target) => write!(f, "Target({target})"),
+            Self::Remove => write!(f, "Remove"),
+            Self::Cancel => write!(f, "Cancel"),
+            Self::Submit => write!(f, "Submit"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(WorkpadMaster, WorkpadUpdate),
+}
+
+/// The kinds of [`Hyperlink`] offered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Reference,
+    External,
+}
+
+impl LinkKind {
+    const ALL: [LinkKind; 2] = [LinkKind::Reference, LinkKind::External];
+}
+
+impl std::fmt::Display for LinkKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LinkKind::Reference => t!("Hyperlink.Reference"),
+            LinkKind::External => t!("Hyperlink.External"),
+        };
+        f.write_str(&name)
+    }
+}
+
+/// Dialog to add, change or remove the link of the active cell
+#[derive(Debug)]
+pub struct HyperlinkUi {
+    cell: Cell,
+    kind: LinkKind,
+    target: String,
+    target_error: Option<String>,
+}
+
+// TODO Focus management
+impl HyperlinkUi {
+    pub fn new(cell: Cell) -> Self {
+        let (kind, target) = match cell.hyperlink() {
+            Some(Hyperlink::Reference(target)) => (LinkKind::Reference, target.clone()),
+            Some(Hyperlink::External(target)) => (LinkKind::External, target.clone()),
+            None => (LinkKind::Reference, String::new()),
+        };
+        Self {
+            cell,
+            kind,
+            target,
+            target_error: None,
+        }
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if self.target_error.is_none() && !self.target.trim().is_empty() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let remove = button(text(t!("Hyperlink.Remove")).size(TEXT_SIZE_LABEL))
+            .on_press_maybe(self.cell.hyperlink().map(|_| Message::Remove))
+            .padding(SPACE_S)
+            .style(theme::Button::Secondary);
+
+        let placeholder = match self.kind {
+            LinkKind::Reference => t!("Hyperlink.ReferencePlaceholder"),
+            LinkKind::External => t!("Hyperlink.ExternalPlaceholder"),
+        };
+
+        let body = column![
+            row![
+                container(label(t!("Hyperlink.Kind"))).width(100),
+                pick_list(&LinkKind::ALL[..], Some(self.kind), Message::Kind)
+                    .text_size(TEXT_SIZE_LABEL)
+                    .width(Length::Fill),
+                remove,
+            ]
+            .spacing(SPACE_S)
+            .align_items(Alignment::Center),
+            text_input(
+                t!("Hyperlink.Target"),
+                placeholder,
+                &self.target,
+                Message::Target,
+                self.target_error.as_ref(),
+            ),
+            text(t!("Hyperlink.Note"))
+                .size(TEXT_SIZE_LABEL)
+                .style(style::TextStyle::Label),
+            ButtonBar::new().push(cancel).push(ok)
+        ]
+        .spacing(SPACE_S);
+
+        Dialog::new(
+            dialog_title(
+                t!("Hyperlink.Title").replace("{cell}", self.cell.name()),
+                Default::default(),
+            ),
+            body,
+        )
+        .max_width(400.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Kind(kind) => {
+                self.kind = kind;
+                self.target_error = None;
+                Event::None
+            }
+            Message::Target(target) => {
+                self.target = target;
+                self.target_error = None;
+                Event::None
+            }
+            Message::Remove => self.submit(None),
+            Message::Cancel => Event::Cancelled,
+            Message::Submit => {
+                let target = self.target.trim().to_owned();
+                match self.kind {
+                    LinkKind::Reference => {
+                        match link_target(&self.cell.sheet().workpad(), &target) {
+                            Ok(_) => self.submit(Some(Hyperlink::Reference(target))),
+                            Err(err) => {
+                                self.target_error = Some(err.to_string());
+                                Event::None
+                            }
+                        }
+                    }
+                    LinkKind::External => {
+                        let hyperlink = Hyperlink::External(target);
+                        if hyperlink.is_allowed() {
+                            self.submit(Some(hyperlink))
+                        } else {
+                            self.target_error = Some(t!("Hyperlink.ExternalError"));
+                            Event::None
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn submit(&self, hyperlink: Option<Hyperlink>) -> Event {
+        let update = WorkpadUpdate::SheetSetCellHyperlink {
+            sheet_id: self.cell.sheet().id(),
+            row_id: self.cell.row().id(),
+            column_id: self.cell.column().id(),
+            hyperlink,
+        };
+        Event::Submitted(self.cell.sheet().workpad().master(), update)
+    }
+}
+
+/// Returns the cell a link to a place in the workpad leads to.  The target is the name of a
+/// sheet, leading to the active cell of that sheet, or a reference to a cell or range of cells,
+/// leading to its top-left cell.
+pub fn link_target(pad: &Workpad, target: &str) -> Result<Cell, ReferenceError> {
+    let target = target.trim();
+    let name = target
+        .strip_prefix('\'')
+        .and_then(|name| name.strip_suffix('\''))
+        .unwrap_or(target);
+    match pad.sheets().find(|sheet| sheet.name() == name) {
+        Some(sheet) => Ok(sheet.active_cell().unwrap_or_else(|| sheet.cell(0, 0))),
+        None => Ok(pad.resolve_reference(target)?.first_cell()),
+    }
+}
+
+/// Opens the web address or local file an external link leads to with the program the
+/// desktop uses for it.  Links that are not openable (see [`Hyperlink::is_openable`]) are
+/// refused, so the target is never taken for an option of the opener.
+pub fn open_external(link: &Hyperlink) -> std::io::Result<()> {
+    use std::{
+        io::{Error, ErrorKind},
+        process::Command,
+    };
+
+    if !matches!(link, Hyperlink::External(_)) || !link.is_openable() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            t!("Hyperlink.NotOpenable"),
+        ));
+    }
+
+    // The target is passed as an argument of its own, never through a shell
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let mut child = command.arg(link.target().trim()).spawn()?;
+    // Reap the opener once it exits, which it may not do until the target is closed
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
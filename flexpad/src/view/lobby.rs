@@ -11,6 +11,7 @@ use rust_i18n::t;
 pub enum Message {
     NewBlankWorkpad,
     NewStarterWorkpad,
+    JoinWorkpad,
 }
 
 impl std::fmt::Display for Message {
@@ -19,6 +20,7 @@ impl std::fmt::Display for Message {
         match self {
             Self::NewBlankWorkpad => write!(f, "NewBlankWorkpad"),
             Self::NewStarterWorkpad => write!(f, "NewStarterWorkpad"),
+            Self::JoinWorkpad => write!(f, "JoinWorkpad"),
         }
     }
 }
@@ -26,6 +28,7 @@ impl std::fmt::Display for Message {
 pub enum Event {
     NewBlankWorkpadRequested,
     NewStarterWorkpadRequested,
+    JoinWorkpadRequested,
 }
 
 pub struct Lobby {
@@ -97,6 +100,7 @@ impl Lobby {
         match message {
             Message::NewBlankWorkpad => Event::NewBlankWorkpadRequested,
             Message::NewStarterWorkpad => Event::NewStarterWorkpadRequested,
+            Message::JoinWorkpad => Event::JoinWorkpadRequested,
         }
     }
 
@@ -108,5 +112,6 @@ impl Lobby {
             .with(workpad_menu::new_starter_workpad(Some(
                 Message::NewStarterWorkpad,
             )))
+            .with(workpad_menu::join_pad(Some(Message::JoinWorkpad)))
     }
 }
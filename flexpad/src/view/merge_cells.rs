@@ -0,0 +1,129 @@
+use crate::FlexpadAction;
+use flexpad_model::{Cell, WorkpadMaster, WorkpadUpdate};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{
+    widget::{column, text},
+    Subscription,
+};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Range(String),
+    Cancel,
+    Submit,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MergeCellsMessage::")?;
+        match self {
+            Self::Range(range) => write!(f, "Range({range})"),
+            Self::Cancel => write!(f, "Cancel"),
+            Self::Submit => write!(f, "Submit"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(WorkpadMaster, WorkpadUpdate),
+}
+
+/// Dialog to merge a range of cells of the sheet of the active cell, starting from the
+/// active cell, into one cell
+#[derive(Debug)]
+pub struct MergeCellsUi {
+    cell: Cell,
+    range: String,
+    range_error: Option<String>,
+}
+
+// TODO Focus management
+impl MergeCellsUi {
+    pub fn new(cell: Cell) -> Self {
+        let range = format!("{}:", cell.name());
+        Self {
+            cell,
+            range,
+            range_error: None,
+        }
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if self.range_error.is_none() && !self.range.trim().is_empty() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let body = column![
+            text_input(
+                t!("MergeCells.Range"),
+                t!("MergeCells.RangePlaceholder"),
+                &self.range,
+                Message::Range,
+                self.range_error.as_ref(),
+            ),
+            text(t!("MergeCells.Note"))
+                .size(TEXT_SIZE_LABEL)
+                .style(style::TextStyle::Label),
+            ButtonBar::new().push(cancel).push(ok)
+        ]
+        .spacing(SPACE_S);
+
+        Dialog::new(
+            dialog_title(t!("MergeCells.Title"), Default::default()),
+            body,
+        )
+        .max_width(400.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Range(range) => {
+                self.range = range;
+                self.range_error = None;
+                Event::None
+            }
+            Message::Cancel => Event::Cancelled,
+            Message::Submit => {
+                let sheet = self.cell.sheet();
+                match sheet.workpad().resolve_reference(&self.range) {
+                    Ok(reference) if reference.sheet() != sheet => {
+                        self.range_error = Some(t!("MergeCells.OtherSheetError"));
+                        Event::None
+                    }
+                    Ok(reference) if reference.is_single_cell() => {
+                        self.range_error = Some(t!("MergeCells.SingleCellError"));
+                        Event::None
+                    }
+                    Ok(reference) => {
+                        let (first, last) = (reference.first_cell(), reference.last_cell());
+                        let update = WorkpadUpdate::SheetMergeCells {
+                            sheet_id: sheet.id(),
+                            first_row_id: first.row().id(),
+                            first_column_id: first.column().id(),
+                            last_row_id: last.row().id(),
+                            last_column_id: last.column().id(),
+                        };
+                        Event::Submitted(sheet.workpad().master(), update)
+                    }
+                    Err(err) => {
+                        self.range_error = Some(err.to_string());
+                        Event::None
+                    }
+                }
+            }
+        }
+    }
+}
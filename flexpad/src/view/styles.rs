@@ -0,0 +1,308 @@
+use crate::FlexpadAction;
+use flexpad_model::{Cell, WorkpadMaster, WorkpadUpdate};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{
+    theme,
+    widget::{button, column, container, pick_list, row, text},
+    Alignment, Length, Subscription,
+};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Style(String),
+    Target(Target),
+    Range(String),
+    Name(String),
+    Apply,
+    Clear,
+    Define,
+    Remove,
+    Cancel,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StylesMessage::")?;
+        match self {
+            Self::Style(style) => write!(f, "Style({style})"),
+            Self::Target(target) => write!(f, "Target({target:?})"),
+            Self::Range(range) => write!(f, "Range({range})"),
+            Self::Name(name) => write!(f, "Name({name})"),
+            Self::Apply => write!(f, "Apply"),
+            Self::Clear => write!(f, "Clear"),
+            Self::Define => write!(f, "Define"),
+            Self::Remove => write!(f, "Remove"),
+            Self::Cancel => write!(f, "Cancel"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(WorkpadMaster, WorkpadUpdate),
+}
+
+/// What a style is applied to: the active cell, a range of cells, the row or column of the
+/// active cell, or the whole sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Cell,
+    Range,
+    Row,
+    Column,
+    Sheet,
+}
+
+impl Target {
+    const ALL: [Target; 5] = [
+        Target::Cell,
+        Target::Range,
+        Target::Row,
+        Target::Column,
+        Target::Sheet,
+    ];
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Target::Cell => t!("Styles.Cell"),
+            Target::Range => t!("Styles.Range"),
+            Target::Row => t!("Styles.Row"),
+            Target::Column => t!("Styles.Column"),
+            Target::Sheet => t!("Styles.Sheet"),
+        };
+        f.write_str(&name)
+    }
+}
+
+/// Dialog to apply the named styles of a workpad to the active cell, a range of cells, the
+/// row or column of the active cell or its sheet, and to define styles from the formatting
+/// of the active cell
+#[derive(Debug)]
+pub struct StylesUi {
+    cell: Cell,
+    styles: Vec<String>,
+    style: Option<String>,
+    target: Target,
+    range: String,
+    range_error: Option<String>,
+    name: String,
+}
+
+// TODO Focus management
+impl StylesUi {
+    pub fn new(cell: Cell) -> Self {
+        let styles: Vec<String> = cell
+            .sheet()
+            .workpad()
+            .styles()
+            .map(|(name, _)| name.to_owned())
+            .collect();
+        let style = cell
+            .style()
+            .map(str::to_owned)
+            .or_else(|| styles.first().cloned());
+        let range = format!("{}:", cell.name());
+        Self {
+            cell,
+            styles,
+            style,
+            target: Target::Cell,
+            range,
+            range_error: None,
+            name: String::new(),
+        }
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let choice = |name: String, element: iced::Element<'static, Message>| {
+            row![container(label(name)).width(100), element]
+                .spacing(SPACE_S)
+                .align_items(Alignment::Center)
+        };
+        let button = |label: String, msg: Option<Message>| {
+            button(text(label).size(TEXT_SIZE_LABEL))
+                .on_press_maybe(msg)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary)
+        };
+
+        let apply = row![
+            choice(
+                t!("Styles.Style"),
+                pick_list(self.styles.clone(), self.style.clone(), Message::Style)
+                    .placeholder(t!("Styles.Placeholder"))
+                    .text_size(TEXT_SIZE_LABEL)
+                    .width(Length::Fill)
+                    .into(),
+            ),
+            button(
+                t!("Styles.Remove"),
+                self.style.as_ref().map(|_| Message::Remove)
+            ),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+        let target = row![
+            choice(
+                t!("Styles.AppliesTo"),
+                pick_list(&Target::ALL[..], Some(self.target), Message::Target)
+                    .text_size(TEXT_SIZE_LABEL)
+                    .width(Length::Fill)
+                    .into(),
+            ),
+            button(
+                t!("Styles.Apply"),
+                self.style.as_ref().map(|_| Message::Apply)
+            ),
+            button(t!("Styles.Clear"), Some(Message::Clear)),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        let define = row![
+            text_input(
+                t!("Styles.Name"),
+                t!("Styles.NamePlaceholder"),
+                &self.name,
+                Message::Name,
+                None,
+            ),
+            button(
+                t!("Styles.Define"),
+                (!self.name.trim().is_empty()).then_some(Message::Define),
+            ),
+        ]
+        .spacing(SPACE_S)
+        .align_items(Alignment::Center);
+
+        let mut body = column![apply, target].spacing(SPACE_M);
+        if self.target == Target::Range {
+            body = body.push(text_input(
+                t!("Styles.Cells"),
+                t!("Styles.RangePlaceholder"),
+                &self.range,
+                Message::Range,
+                self.range_error.as_ref(),
+            ));
+        }
+        let body = body
+            .push(define)
+            .push(
+                text(t!("Styles.Note"))
+                    .size(TEXT_SIZE_LABEL)
+                    .style(style::TextStyle::Label),
+            )
+            .push(ButtonBar::new().push(cancel));
+
+        Dialog::new(
+            dialog_title(
+                t!("Styles.Title").replace("{cell}", self.cell.name()),
+                Default::default(),
+            ),
+            body,
+        )
+        .max_width(500.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Style(style) => {
+                self.style = Some(style);
+                Event::None
+            }
+            Message::Target(target) => {
+                self.target = target;
+                Event::None
+            }
+            Message::Range(range) => {
+                self.range = range;
+                self.range_error = None;
+                Event::None
+            }
+            Message::Name(name) => {
+                self.name = name;
+                Event::None
+            }
+            Message::Apply => self.set_style(self.style.clone()),
+            Message::Clear => self.set_style(None),
+            Message::Define => self.submit(WorkpadUpdate::SetStyle {
+                name: self.name.trim().to_owned(),
+                format: self.cell.resolved_format(),
+            }),
+            Message::Remove => match self.style.clone() {
+                Some(name) => self.submit(WorkpadUpdate::RemoveStyle { name }),
+                None => Event::None,
+            },
+            Message::Cancel => Event::Cancelled,
+        }
+    }
+
+    /// Sets (or with `None` clears) the style of the target
+    fn set_style(&mut self, style: Option<String>) -> Event {
+        let sheet = self.cell.sheet();
+        let sheet_id = sheet.id();
+        let row_id = self.cell.row().id();
+        let column_id = self.cell.column().id();
+        self.submit(match self.target {
+            Target::Cell => WorkpadUpdate::SheetSetCellStyle {
+                sheet_id,
+                row_id,
+                column_id,
+                style,
+            },
+            Target::Range => match sheet.workpad().resolve_reference(&self.range) {
+                Ok(reference) if reference.sheet() != sheet => {
+                    self.range_error = Some(t!("Styles.OtherSheetError"));
+                    return Event::None;
+                }
+                Ok(reference) => {
+                    let mut updates = vec![];
+                    for row in reference.rows() {
+                        for column in reference.columns() {
+                            let cell = sheet.cell(row, column);
+                            updates.push(WorkpadUpdate::SheetSetCellStyle {
+                                sheet_id,
+                                row_id: cell.row().id(),
+                                column_id: cell.column().id(),
+                                style: style.clone(),
+                            });
+                        }
+                    }
+                    WorkpadUpdate::Multi(updates)
+                }
+                Err(err) => {
+                    self.range_error = Some(err.to_string());
+                    return Event::None;
+                }
+            },
+            Target::Row => WorkpadUpdate::SheetSetRowStyle {
+                sheet_id,
+                row_id,
+                style,
+            },
+            Target::Column => WorkpadUpdate::SheetSetColumnStyle {
+                sheet_id,
+                column_id,
+                style,
+            },
+            Target::Sheet => WorkpadUpdate::SheetSetStyle { sheet_id, style },
+        })
+    }
+
+    fn submit(&self, update: WorkpadUpdate) -> Event {
+        Event::Submitted(self.cell.sheet().workpad().master(), update)
+    }
+}
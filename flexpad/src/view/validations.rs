@@ -0,0 +1,499 @@
+use crate::FlexpadAction;
+use flexpad_model::{Cell, SheetRange, Validation, ValidationRule, WorkpadMaster, WorkpadUpdate};
+use flexpad_toolkit::{button_bar::ButtonBar, dialog::Dialog, prelude::*};
+use iced::{
+    theme,
+    widget::{button, checkbox, column, container, pick_list, row, scrollable, text, Column},
+    Alignment, Length, Subscription,
+};
+use rust_i18n::t;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(usize),
+    Add,
+    Remove,
+    MoveUp,
+    MoveDown,
+    Range(String),
+    Kind(Kind),
+    Values(String),
+    Min(String),
+    Max(String),
+    Pattern(String),
+    Reject(bool),
+    Submit,
+    Cancel,
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ValidationsMessage::")?;
+        match self {
+            Self::Select(index) => write!(f, "Select({index})"),
+            Self::Add => write!(f, "Add"),
+            Self::Remove => write!(f, "Remove"),
+            Self::MoveUp => write!(f, "MoveUp"),
+            Self::MoveDown => write!(f, "MoveDown"),
+            Self::Range(range) => write!(f, "Range({range})"),
+            Self::Kind(kind) => write!(f, "Kind({kind:?})"),
+            Self::Values(values) => write!(f, "Values({values})"),
+            Self::Min(min) => write!(f, "Min({min})"),
+            Self::Max(max) => write!(f, "Max({max})"),
+            Self::Pattern(pattern) => write!(f, "Pattern({pattern})"),
+            Self::Reject(reject) => write!(f, "Reject({reject})"),
+            Self::Submit => write!(f, "Submit"),
+            Self::Cancel => write!(f, "Cancel"),
+        }
+    }
+}
+
+pub enum Event {
+    None,
+    Cancelled,
+    Submitted(WorkpadMaster, WorkpadUpdate),
+}
+
+/// The kinds of [`ValidationRule`] offered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    List,
+    Number,
+    Date,
+    TextLength,
+    Pattern,
+}
+
+impl Kind {
+    const ALL: [Kind; 5] = [
+        Kind::List,
+        Kind::Number,
+        Kind::Date,
+        Kind::TextLength,
+        Kind::Pattern,
+    ];
+
+    fn of(rule: &ValidationRule) -> Self {
+        match rule {
+            ValidationRule::List(_) => Kind::List,
+            ValidationRule::Number { .. } => Kind::Number,
+            ValidationRule::Date { .. } => Kind::Date,
+            ValidationRule::TextLength { .. } => Kind::TextLength,
+            ValidationRule::Pattern(_) => Kind::Pattern,
+        }
+    }
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Kind::List => t!("Validations.List"),
+            Kind::Number => t!("Validations.Number"),
+            Kind::Date => t!("Validations.Date"),
+            Kind::TextLength => t!("Validations.TextLength"),
+            Kind::Pattern => t!("Validations.Pattern"),
+        };
+        f.write_str(&name)
+    }
+}
+
+/// Dialog to manage the data validation rules of the sheet of the active cell: rules can be
+/// added, edited, removed and reordered.  Nothing changes until the rules are submitted.
+#[derive(Debug)]
+pub struct ValidationsUi {
+    cell: Cell,
+    rules: Vec<Validation>,
+    selected: Option<usize>,
+    // The fields of the selected rule being edited as text
+    range: String,
+    range_error: Option<String>,
+    values: String,
+    min: String,
+    max: String,
+    pattern: String,
+    rule_error: Option<String>,
+}
+
+// TODO Focus management
+impl ValidationsUi {
+    pub fn new(cell: Cell) -> Self {
+        let rules = cell.sheet().validations().to_vec();
+        let mut ui = Self {
+            cell,
+            rules,
+            selected: None,
+            range: String::new(),
+            range_error: None,
+            values: String::new(),
+            min: String::new(),
+            max: String::new(),
+            pattern: String::new(),
+            rule_error: None,
+        };
+        if !ui.rules.is_empty() {
+            ui.select(0);
+        }
+        ui
+    }
+
+    fn select(&mut self, index: usize) {
+        let rule = &self.rules[index];
+        let bound = |bound: Option<String>| bound.unwrap_or_default();
+        self.range = self.cell.sheet().range_name(&rule.range);
+        (self.values, self.min, self.max, self.pattern) = match &rule.rule {
+            ValidationRule::List(values) => (
+                values.join(", "),
+                String::new(),
+                String::new(),
+                String::new(),
+            ),
+            ValidationRule::Number { min, max } => (
+                String::new(),
+                bound(min.map(|n| n.to_string())),
+                bound(max.map(|n| n.to_string())),
+                String::new(),
+            ),
+            ValidationRule::Date { min, max } => (
+                String::new(),
+                bound(min.clone()),
+                bound(max.clone()),
+                String::new(),
+            ),
+            ValidationRule::TextLength { min, max } => (
+                String::new(),
+                bound(min.map(|n| n.to_string())),
+                bound(max.map(|n| n.to_string())),
+                String::new(),
+            ),
+            ValidationRule::Pattern(pattern) => {
+                (String::new(), String::new(), String::new(), pattern.clone())
+            }
+        };
+        self.range_error = None;
+        self.rule_error = None;
+        self.selected = Some(index);
+    }
+
+    fn selected_rule(&mut self) -> Option<&mut Validation> {
+        self.selected.and_then(|index| self.rules.get_mut(index))
+    }
+
+    fn has_errors(&self) -> bool {
+        self.range_error.is_some() || self.rule_error.is_some()
+    }
+
+    /// Rebuilds the selected rule, of a kind, from the fields being edited
+    fn rebuild(&mut self, kind: Kind) {
+        fn bound<T: std::str::FromStr>(text: &str) -> Result<Option<T>, String> {
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            text.parse()
+                .map(Some)
+                .map_err(|_| t!("Validations.BoundError").replace("{bound}", text))
+        }
+        let text_bound = |text: &str| Some(text.trim().to_owned()).filter(|t| !t.is_empty());
+
+        let rule = match kind {
+            Kind::List => Ok(ValidationRule::List(
+                self.values
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+            )),
+            Kind::Number => bound(&self.min).and_then(|min| {
+                Ok(ValidationRule::Number {
+                    min,
+                    max: bound(&self.max)?,
+                })
+            }),
+            Kind::Date => Ok(ValidationRule::Date {
+                min: text_bound(&self.min),
+                max: text_bound(&self.max),
+            }),
+            Kind::TextLength => bound(&self.min).and_then(|min| {
+                Ok(ValidationRule::TextLength {
+                    min,
+                    max: bound(&self.max)?,
+                })
+            }),
+            Kind::Pattern => Ok(ValidationRule::Pattern(self.pattern.trim().to_owned())),
+        };
+        self.rule_error = match rule {
+            Ok(rule) => match self.selected_rule() {
+                Some(validation) => {
+                    validation.rule = rule;
+                    validation.validate().err().map(|err| err.to_string())
+                }
+                None => None,
+            },
+            Err(err) => Some(err),
+        };
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        let cancel = action_button(FlexpadAction::Cancel)
+            .style(style::ButtonStyle::Cancel)
+            .on_press(Message::Cancel);
+
+        let mut ok = action_button(FlexpadAction::Ok).style(style::ButtonStyle::Ok);
+        if !self.has_errors() {
+            ok = ok.on_press(Message::Submit)
+        }
+
+        let button = |label: String, msg: Option<Message>| {
+            button(text(label).size(TEXT_SIZE_LABEL))
+                .on_press_maybe(msg)
+                .padding(SPACE_S)
+                .style(theme::Button::Secondary)
+        };
+
+        let mut rules = Column::new().spacing(SPACE_S / 2.0);
+        if self.rules.is_empty() {
+            rules = rules.push(
+                text(t!("Validations.NoRules"))
+                    .size(TEXT_SIZE_LABEL)
+                    .style(style::TextStyle::Label),
+            );
+        }
+        let sheet = self.cell.sheet();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let is_selected = self.selected == Some(index);
+            let description = format!("{}: {}", sheet.range_name(&rule.range), rule.rule);
+            let mut entry = iced::widget::button(text(description).size(TEXT_SIZE_LABEL))
+                .width(Length::Fill)
+                .padding(SPACE_S / 2.0)
+                .style(if is_selected {
+                    theme::Button::Primary
+                } else {
+                    theme::Button::Text
+                });
+            if !is_selected {
+                entry = entry.on_press(Message::Select(index));
+            }
+            rules = rules.push(entry);
+        }
+
+        let selected = self.selected;
+        let last = self.rules.len().saturating_sub(1);
+        let actions = row![
+            button(t!("Validations.Add"), Some(Message::Add)),
+            button(t!("Validations.Remove"), selected.map(|_| Message::Remove)),
+            button(
+                t!("Validations.MoveUp"),
+                selected.filter(|i| *i > 0).map(|_| Message::MoveUp)
+            ),
+            button(
+                t!("Validations.MoveDown"),
+                selected.filter(|i| *i < last).map(|_| Message::MoveDown)
+            ),
+        ]
+        .spacing(SPACE_S);
+
+        let mut body = column![
+            label(t!("Validations.Rules")),
+            scrollable(rules).height(120),
+            actions,
+        ]
+        .spacing(SPACE_M);
+
+        if let Some(rule) = selected.and_then(|index| self.rules.get(index)) {
+            let kind = Kind::of(&rule.rule);
+            body = body
+                .push(text_input(
+                    t!("Validations.Range"),
+                    t!("Validations.RangePlaceholder"),
+                    &self.range,
+                    Message::Range,
+                    self.range_error.as_ref(),
+                ))
+                .push(
+                    row![
+                        container(label(t!("Validations.Allow"))).width(100),
+                        pick_list(&Kind::ALL[..], Some(kind), Message::Kind)
+                            .text_size(TEXT_SIZE_LABEL)
+                            .width(Length::Fill),
+                    ]
+                    .spacing(SPACE_S)
+                    .align_items(Alignment::Center),
+                );
+            body = match kind {
+                Kind::List => body.push(text_input(
+                    t!("Validations.Values"),
+                    t!("Validations.ValuesPlaceholder"),
+                    &self.values,
+                    Message::Values,
+                    self.rule_error.as_ref(),
+                )),
+                Kind::Pattern => body.push(text_input(
+                    t!("Validations.Expression"),
+                    t!("Validations.ExpressionPlaceholder"),
+                    &self.pattern,
+                    Message::Pattern,
+                    self.rule_error.as_ref(),
+                )),
+                Kind::Number | Kind::Date | Kind::TextLength => {
+                    let placeholder = match kind {
+                        Kind::Date => t!("Validations.DatePlaceholder"),
+                        _ => t!("Validations.BoundPlaceholder"),
+                    };
+                    body.push(
+                        row![
+                            text_input(
+                                t!("Validations.Min"),
+                                placeholder.clone(),
+                                &self.min,
+                                Message::Min,
+                                self.rule_error.as_ref(),
+                            ),
+                            text_input(
+                                t!("Validations.Max"),
+                                placeholder,
+                                &self.max,
+                                Message::Max,
+                                None,
+                            ),
+                        ]
+                        .spacing(SPACE_S)
+                        .align_items(Alignment::Start),
+                    )
+                }
+            };
+            body = body.push(
+                checkbox(t!("Validations.Reject"), rule.reject, Message::Reject)
+                    .size(TEXT_SIZE_LABEL.0)
+                    .text_size(TEXT_SIZE_LABEL),
+            );
+        }
+
+        let body = body
+            .push(
+                text(t!("Validations.Note"))
+                    .size(TEXT_SIZE_LABEL)
+                    .style(style::TextStyle::Label),
+            )
+            .push(ButtonBar::new().push(cancel).push(ok));
+
+        Dialog::new(
+            dialog_title(
+                t!("Validations.Title").replace("{sheet}", self.cell.sheet().name()),
+                Default::default(),
+            ),
+            body,
+        )
+        .max_width(500.0)
+        .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Select(index) => self.select(index),
+            Message::Add => {
+                let (row, column) = (self.cell.row().index(), self.cell.column().index());
+                self.rules.push(Validation {
+                    range: SheetRange::single(row, column),
+                    rule: ValidationRule::Number {
+                        min: None,
+                        max: None,
+                    },
+                    reject: true,
+                });
+                self.select(self.rules.len() - 1);
+            }
+            Message::Remove => {
+                if let Some(index) = self.selected.take() {
+                    self.rules.remove(index);
+                    self.range_error = None;
+                    self.rule_error = None;
+                    if !self.rules.is_empty() {
+                        self.select(index.min(self.rules.len() - 1));
+                    }
+                }
+            }
+            Message::MoveUp => {
+                if let Some(index) = self.selected.filter(|i| *i > 0) {
+                    self.rules.swap(index, index - 1);
+                    self.selected = Some(index - 1);
+                }
+            }
+            Message::MoveDown => {
+                if let Some(index) = self.selected.filter(|i| *i + 1 < self.rules.len()) {
+                    self.rules.swap(index, index + 1);
+                    self.selected = Some(index + 1);
+                }
+            }
+            Message::Range(range) => {
+                let sheet = self.cell.sheet();
+                let result = sheet.workpad().resolve_reference(&range);
+                self.range = range;
+                self.range_error = match result {
+                    Ok(reference) if reference.sheet() != sheet => {
+                        Some(t!("Validations.OtherSheetError"))
+                    }
+                    Ok(reference) => {
+                        if let Some(rule) = self.selected_rule() {
+                            rule.range = reference.range();
+                        }
+                        None
+                    }
+                    Err(err) => Some(err.to_string()),
+                };
+            }
+            Message::Kind(kind) => self.rebuild(kind),
+            Message::Values(values) => {
+                self.values = values;
+                self.rebuild(Kind::List);
+            }
+            Message::Min(min) => {
+                self.min = min;
+                self.rebuild_selected();
+            }
+            Message::Max(max) => {
+                self.max = max;
+                self.rebuild_selected();
+            }
+            Message::Pattern(pattern) => {
+                self.pattern = pattern;
+                self.rebuild(Kind::Pattern);
+            }
+            Message::Reject(reject) => {
+                if let Some(rule) = self.selected_rule() {
+                    rule.reject = reject;
+                }
+            }
+            Message::Cancel => return Event::Cancelled,
+            Message::Submit => {
+                // Rules not selected since the dialog opened may still be invalid
+                if let Some((index, err)) = self
+                    .rules
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, rule)| rule.validate().err().map(|err| (index, err)))
+                {
+                    self.select(index);
+                    self.rule_error = Some(err.to_string());
+                    return Event::None;
+                }
+                let update = WorkpadUpdate::SheetSetValidations {
+                    sheet_id: self.cell.sheet().id(),
+                    validations: self.rules.clone(),
+                };
+                return Event::Submitted(self.cell.sheet().workpad().master(), update);
+            }
+        }
+        Event::None
+    }
+
+    fn rebuild_selected(&mut self) {
+        if let Some(kind) = self.selected_rule().map(|rule| Kind::of(&rule.rule)) {
+            self.rebuild(kind);
+        }
+    }
+}
@@ -25,7 +25,7 @@ mod editor;
 mod platform;
 pub use editor::Editor;
 
-use crate::view::active_sheet::Message;
+use crate::{view::active_sheet::Message, widget::inactive_cell::underline_bounds};
 
 /// The identifier of a [`ActiveCell`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -61,6 +61,9 @@ where
     vertical_alignment: alignment::Vertical,
     font: Option<Renderer::Font>,
     font_size: Pixels,
+    text_color: Color,
+    underline: bool,
+    display_text: Option<String>,
     edit_when_clicked: click::Kind,
 }
 
@@ -78,6 +81,9 @@ where
             vertical_alignment: alignment::Vertical::Center,
             font: None,
             font_size: Pixels::from(10.0),
+            text_color: Color::BLACK,
+            underline: false,
+            display_text: None,
             edit_when_clicked: click::Kind::Single,
         }
     }
@@ -122,6 +128,24 @@ where
         self.font_size = size.into();
         self
     }
+
+    /// Sets the text [`Color`] of the [`ActiveCell`].
+    pub fn text_color(mut self, color: impl Into<Color>) -> Self {
+        self.text_color = color.into();
+        self
+    }
+
+    /// Sets whether the text of the [`ActiveCell`] is underlined.
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    /// Sets the text shown in place of the value of the [`ActiveCell`] while not editing.
+    pub fn display_text(mut self, text: impl Into<String>) -> Self {
+        self.display_text = Some(text.into());
+        self
+    }
 }
 
 impl<Renderer> Widget<Message, Renderer> for ActiveCell<Renderer>
@@ -184,7 +208,10 @@ where
 
         let editor = self.editor.borrow();
         let value = editor.value();
-        let text = editor.contents();
+        let text = match &self.display_text {
+            Some(display_text) if !editor.is_editing() => display_text.clone(),
+            _ => editor.contents(),
+        };
         let font = self.font.unwrap_or_else(|| renderer.default_font());
         let size = self.font_size;
 
@@ -286,15 +313,7 @@ where
                 renderer.with_translation(Vector::ZERO, |_| {});
             }
 
-            // TODO Colors
-            // color: if text.is_empty() {
-            //     theme.placeholder_color(style)
-            // } else if is_disabled {
-            //     theme.disabled_color(style)
-            // } else {
-            //     theme.value_color(style)
-            // },
-            let color = Color::BLACK;
+            let color = self.text_color;
             let (h_align, v_align) = if state.is_focused() && editor.is_editing() {
                 (alignment::Horizontal::Left, alignment::Vertical::Center)
             } else {
@@ -308,7 +327,7 @@ where
             let y = match v_align {
                 alignment::Vertical::Top => bounds.y,
                 alignment::Vertical::Center => bounds.center_y(),
-                alignment::Vertical::Bottom => bounds.y + bounds.width,
+                alignment::Vertical::Bottom => bounds.y + bounds.height,
             };
             let text_position = Point::new(x, y);
             renderer.fill_text(
@@ -326,6 +345,19 @@ where
                 color,
                 bounds,
             );
+
+            if self.underline {
+                let underline = underline_bounds(bounds, h_align, v_align, text_width, size);
+                renderer.fill_quad(
+                    Quad {
+                        bounds: underline,
+                        border_radius: 0.0.into(),
+                        border_width: 0.0,
+                        border_color: Color::TRANSPARENT,
+                    },
+                    color,
+                );
+            }
         };
 
         if text_width > bounds.width {
@@ -540,6 +572,14 @@ where
                     keyboard::KeyCode::Right => publish(editor.right()),
                     keyboard::KeyCode::Up => publish(editor.up()),
                     keyboard::KeyCode::Down => publish(editor.down()),
+                    keyboard::KeyCode::PageUp if modifiers.alt() => publish(editor.page_left()),
+                    keyboard::KeyCode::PageDown if modifiers.alt() => publish(editor.page_right()),
+                    keyboard::KeyCode::PageUp => publish(editor.page_up()),
+                    keyboard::KeyCode::PageDown => publish(editor.page_down()),
+                    keyboard::KeyCode::Home if jump => publish(editor.first()),
+                    keyboard::KeyCode::End if jump => publish(editor.last()),
+                    keyboard::KeyCode::Home => publish(editor.home()),
+                    keyboard::KeyCode::End => publish(editor.end()),
                     _ => {}
                 };
 
@@ -136,6 +136,57 @@ impl Editor {
         }
     }
 
+    pub fn page_up(&mut self) -> Option<Message> {
+        self.move_when_viewing(Move::PageUp)
+    }
+
+    pub fn page_down(&mut self) -> Option<Message> {
+        self.move_when_viewing(Move::PageDown)
+    }
+
+    pub fn page_left(&mut self) -> Option<Message> {
+        self.move_when_viewing(Move::PageLeft)
+    }
+
+    pub fn page_right(&mut self) -> Option<Message> {
+        self.move_when_viewing(Move::PageRight)
+    }
+
+    pub fn home(&mut self) -> Option<Message> {
+        match self.mode {
+            Mode::Viewing => Some(Message::ActiveCellMove(Move::Home)),
+            Mode::Editing => {
+                self.cursor.move_to(0);
+                None
+            }
+        }
+    }
+
+    pub fn end(&mut self) -> Option<Message> {
+        match self.mode {
+            Mode::Viewing => Some(Message::ActiveCellMove(Move::End)),
+            Mode::Editing => {
+                self.cursor.move_to(self.edit_value.len());
+                None
+            }
+        }
+    }
+
+    pub fn first(&mut self) -> Option<Message> {
+        self.move_when_viewing(Move::First)
+    }
+
+    pub fn last(&mut self) -> Option<Message> {
+        self.move_when_viewing(Move::Last)
+    }
+
+    fn move_when_viewing(&self, mve: Move) -> Option<Message> {
+        match self.mode {
+            Mode::Viewing => Some(Message::ActiveCellMove(mve)),
+            Mode::Editing => None,
+        }
+    }
+
     pub fn enter(&mut self) -> Option<Message> {
         match self.mode {
             Mode::Viewing => Some(Message::ActiveCellMove(Move::Down)),
@@ -1,22 +1,52 @@
 use flexpad_grid::RowCol;
+use flexpad_model::TextOverflow;
 use iced::{
     advanced::{
         layout::{Limits, Node},
         mouse,
-        renderer::Style,
+        renderer::{Quad, Style},
         text::{self, Paragraph},
-        widget::{Operation, Tree},
+        widget::{tree, Operation, Tree},
         Clipboard, Layout, Shell, Text, Widget,
     },
     alignment,
     event::Status,
-    touch,
+    keyboard, touch,
     widget::{text::LineHeight, text_input::Value},
     Color, Element, Event, Length, Pixels, Point, Rectangle, Size,
 };
 
 use crate::view::active_sheet::{Message, Move};
 
+/// The smallest font size text is shrunk to when fitting it to its cell
+const MIN_SHRINK_FONT_SIZE: f32 = 4.0;
+
+/// The colour and size of the marker in the top-right corner of cells with comments
+const COMMENT_MARKER_COLOR: Color = Color {
+    r: 0.9,
+    g: 0.5,
+    b: 0.0,
+    a: 1.0,
+};
+const COMMENT_MARKER_SIZE: f32 = 5.0;
+
+/// The colour of the marker in the top-left corner of cells with values breaking a
+/// validation rule, the same size as the comment marker
+const INVALID_MARKER_COLOR: Color = Color {
+    r: 0.85,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+/// The colour of the text of cells with links
+const LINK_COLOR: Color = Color {
+    r: 0.0,
+    g: 0.3,
+    b: 0.8,
+    a: 1.0,
+};
+
 pub struct InactiveCell<Renderer>
 where
     Renderer: text::Renderer,
@@ -27,6 +57,13 @@ where
     vertical_alignment: alignment::Vertical,
     font: Option<Renderer::Font>,
     font_size: Pixels,
+    text_color: Color,
+    underline: bool,
+    text_overflow: TextOverflow,
+    spill: (f32, f32),
+    commented: bool,
+    hyperlink: bool,
+    invalid: bool,
 }
 
 impl<Renderer> InactiveCell<Renderer>
@@ -42,6 +79,13 @@ where
             vertical_alignment: alignment::Vertical::Center,
             font: None,
             font_size: Pixels::from(10.0),
+            text_color: Color::BLACK,
+            underline: false,
+            text_overflow: TextOverflow::Clip,
+            spill: (0.0, 0.0),
+            commented: false,
+            hyperlink: false,
+            invalid: false,
         }
     }
 
@@ -68,6 +112,52 @@ where
         self.font_size = size.into();
         self
     }
+
+    /// Sets the text [`Color`] of the [`InactiveCell`].
+    pub fn text_color(mut self, color: impl Into<Color>) -> Self {
+        self.text_color = color.into();
+        self
+    }
+
+    /// Sets whether the text of the [`InactiveCell`] is underlined.
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    /// Sets how text too wide for the [`InactiveCell`] is displayed.
+    pub fn text_overflow(mut self, text_overflow: TextOverflow) -> Self {
+        self.text_overflow = text_overflow;
+        self
+    }
+
+    /// Sets the width of the space to the left and right of the [`InactiveCell`] that text
+    /// may spill into when it overflows, typically that of empty neighbouring cells.
+    pub fn spill(mut self, left: f32, right: f32) -> Self {
+        self.spill = (left, right);
+        self
+    }
+
+    /// Sets whether the [`InactiveCell`] has comments, shown by a marker in its corner.
+    pub fn commented(mut self, commented: bool) -> Self {
+        self.commented = commented;
+        self
+    }
+
+    /// Sets whether the [`InactiveCell`] has a link, shown as underlined text in the link
+    /// colour.  Clicking the cell with the command modifier (Ctrl, or Cmd on macOS) held
+    /// follows the link rather than making it the active cell.
+    pub fn hyperlink(mut self, hyperlink: bool) -> Self {
+        self.hyperlink = hyperlink;
+        self
+    }
+
+    /// Sets whether the value of the [`InactiveCell`] breaks a validation rule, shown by a
+    /// marker in its corner.
+    pub fn invalid(mut self, invalid: bool) -> Self {
+        self.invalid = invalid;
+        self
+    }
 }
 
 impl<Renderer> Widget<Message, Renderer> for InactiveCell<Renderer>
@@ -75,6 +165,14 @@ where
     Renderer: iced::advanced::Renderer,
     Renderer: text::Renderer,
 {
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
     fn width(&self) -> iced::Length {
         iced::Length::Fill
     }
@@ -105,7 +203,7 @@ where
 
         let text = self.value.to_string();
         let font = self.font.unwrap_or_else(|| renderer.default_font());
-        let size = self.font_size;
+        let mut size = self.font_size;
 
         let paragraph = Renderer::Paragraph::with_text(Text {
             content: &text,
@@ -117,11 +215,47 @@ where
             vertical_alignment: alignment::Vertical::Top,
             shaping: text::Shaping::Advanced,
         });
-        let text_width = paragraph.min_width();
+        let mut text_width = paragraph.min_width();
+        let too_wide = text_width > bounds.width;
+
+        if too_wide && self.text_overflow == TextOverflow::ShrinkToFit {
+            let scale = bounds.width / text_width;
+            size = Pixels((size.0 * scale).max(MIN_SHRINK_FONT_SIZE));
+            text_width *= size.0 / self.font_size.0;
+        }
+        let wrapped = too_wide && self.text_overflow == TextOverflow::Wrap;
+
+        // Wrapped text breaks at the width of the cell, otherwise it is kept to one line
+        let text_bounds = if wrapped {
+            bounds.size()
+        } else {
+            Size::new(f32::INFINITY, bounds.height)
+        };
+
+        let clip_bounds = match (self.text_overflow, self.horizontal_alignment) {
+            (TextOverflow::Overflow, alignment::Horizontal::Left) => Rectangle {
+                width: bounds.width + self.spill.1,
+                ..bounds
+            },
+            (TextOverflow::Overflow, alignment::Horizontal::Center) => Rectangle {
+                x: bounds.x - self.spill.0,
+                width: bounds.width + self.spill.0 + self.spill.1,
+                ..bounds
+            },
+            (TextOverflow::Overflow, alignment::Horizontal::Right) => Rectangle {
+                x: bounds.x - self.spill.0,
+                width: bounds.width + self.spill.0,
+                ..bounds
+            },
+            _ => bounds,
+        };
 
         let render = |renderer: &mut Renderer| {
-            // TODO Colors
-            let color = Color::BLACK;
+            let color = if self.hyperlink {
+                LINK_COLOR
+            } else {
+                self.text_color
+            };
             let h_align = self.horizontal_alignment;
             let v_align = self.vertical_alignment;
             let x = match h_align {
@@ -132,14 +266,14 @@ where
             let y = match v_align {
                 alignment::Vertical::Top => bounds.y,
                 alignment::Vertical::Center => bounds.center_y(),
-                alignment::Vertical::Bottom => bounds.y + bounds.width,
+                alignment::Vertical::Bottom => bounds.y + bounds.height,
             };
             let text_position = Point::new(x, y);
             renderer.fill_text(
                 Text {
                     content: &text,
                     font,
-                    bounds: bounds.size(),
+                    bounds: text_bounds,
                     size,
                     line_height: LineHeight::default(),
                     horizontal_alignment: h_align,
@@ -148,18 +282,67 @@ where
                 },
                 text_position,
                 color,
-                bounds,
+                clip_bounds,
             );
+
+            // TODO Underline each line of wrapped text
+            if (self.underline || self.hyperlink) && !wrapped {
+                let underline = underline_bounds(bounds, h_align, v_align, text_width, size);
+                renderer.fill_quad(
+                    Quad {
+                        bounds: underline,
+                        border_radius: 0.0.into(),
+                        border_width: 0.0,
+                        border_color: Color::TRANSPARENT,
+                    },
+                    color,
+                );
+            }
         };
 
         if text_width > bounds.width {
-            renderer.with_layer(bounds, |renderer| {
-                // TODO Overflow rules
+            renderer.with_layer(clip_bounds, |renderer| {
                 render(renderer);
             });
         } else {
             render(renderer);
         }
+
+        if self.commented {
+            let size = COMMENT_MARKER_SIZE.min(bounds.width).min(bounds.height);
+            renderer.fill_quad(
+                Quad {
+                    bounds: Rectangle {
+                        x: bounds.x + bounds.width - size,
+                        y: bounds.y,
+                        width: size,
+                        height: size,
+                    },
+                    border_radius: 0.0.into(),
+                    border_width: 0.0,
+                    border_color: Color::TRANSPARENT,
+                },
+                COMMENT_MARKER_COLOR,
+            );
+        }
+
+        if self.invalid {
+            let size = COMMENT_MARKER_SIZE.min(bounds.width).min(bounds.height);
+            renderer.fill_quad(
+                Quad {
+                    bounds: Rectangle {
+                        x: bounds.x,
+                        y: bounds.y,
+                        width: size,
+                        height: size,
+                    },
+                    border_radius: 0.0.into(),
+                    border_width: 0.0,
+                    border_color: Color::TRANSPARENT,
+                },
+                INVALID_MARKER_COLOR,
+            );
+        }
     }
 
     fn operate(
@@ -177,7 +360,7 @@ where
 
     fn on_event(
         &mut self,
-        _tree: &mut Tree,
+        tree: &mut Tree,
         event: Event,
         layout: Layout<'_>,
         cursor: mouse::Cursor,
@@ -186,7 +369,23 @@ where
         shell: &mut Shell<'_, Message>,
         _viewport: &Rectangle,
     ) -> Status {
+        let state = tree.state.downcast_mut::<State>();
+
         match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.keyboard_modifiers = modifiers;
+                Status::Ignored
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if self.hyperlink && state.keyboard_modifiers.command() =>
+            {
+                if cursor.position_over(layout.bounds()).is_some() {
+                    shell.publish(Message::HyperlinkActivate(self.rc));
+                    Status::Captured
+                } else {
+                    Status::Ignored
+                }
+            }
             Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
             | Event::Touch(touch::Event::FingerPressed { .. }) => {
                 if cursor.position_over(layout.bounds()).is_some() {
@@ -199,6 +398,58 @@ where
             _ => Status::Ignored,
         }
     }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        let state = tree.state.downcast_ref::<State>();
+        if self.hyperlink && state.keyboard_modifiers.command() && cursor.is_over(layout.bounds()) {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// Returns the bounds of the line underlining text of a given width and size drawn with the
+/// given alignment within the bounds of a cell
+pub(crate) fn underline_bounds(
+    bounds: Rectangle,
+    h_align: alignment::Horizontal,
+    v_align: alignment::Vertical,
+    text_width: f32,
+    size: Pixels,
+) -> Rectangle {
+    let line_height = LineHeight::default().to_absolute(size).0;
+    let x = match h_align {
+        alignment::Horizontal::Left => bounds.x,
+        alignment::Horizontal::Center => bounds.center_x() - text_width / 2.0,
+        alignment::Horizontal::Right => bounds.x + bounds.width - text_width,
+    };
+    let top = match v_align {
+        alignment::Vertical::Top => bounds.y,
+        alignment::Vertical::Center => bounds.center_y() - line_height / 2.0,
+        alignment::Vertical::Bottom => bounds.y + bounds.height - line_height,
+    };
+    // Just below the baseline, which sits about a fifth of the size below the middle
+    let y = top + line_height / 2.0 + size.0 * 0.3;
+    Rectangle {
+        x,
+        y,
+        width: text_width,
+        height: (size.0 / 12.0).max(1.0),
+    }
+}
+
+/// The state of an [`InactiveCell`].
+#[derive(Debug, Clone, Default)]
+struct State {
+    keyboard_modifiers: keyboard::Modifiers,
 }
 
 impl<'a, Renderer> From<InactiveCell<Renderer>> for Element<'a, Message, Renderer>